@@ -0,0 +1,835 @@
+//! Encoders for animations, so that a sequence of frames can be shared as a single file: animated
+//! GIFs, which are small but limited to 256 colors per frame, and uncompressed animated PNGs
+//! (APNG), which are lossless but large.
+
+use crate::bits::{BitOrder, BitWriter};
+use crate::colormap::Rgb;
+use crate::error::{Error, Result};
+use crate::image::Image;
+use std::collections::HashMap;
+use std::io::{Seek, SeekFrom, Write};
+use std::time::Duration;
+
+/// The supported animation formats.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    Gif,
+    Apng,
+}
+
+impl Format {
+    /// Picks the format from a path's extension: `gif`, or `png` for APNG.
+    pub fn from_path(path: &std::path::Path) -> Option<Format> {
+        match path.extension()?.to_str()? {
+            "gif" => Some(Format::Gif),
+            "png" | "apng" => Some(Format::Apng),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Gif => "gif",
+            Format::Apng => "png",
+        }
+    }
+}
+
+/// Saves frames as a looping animation, returning the number of frames. All the frames must have
+/// the same size, and there must be at least one.
+pub fn save_animation(
+    path: &std::path::Path,
+    format: Format,
+    frames: impl IntoIterator<Item = Image<Rgb>>,
+    frame_delay: Duration,
+) -> Result<usize> {
+    let mut frames = frames.into_iter().peekable();
+    let (height, width) = match frames.peek() {
+        Some(frame) => frame.size_hw(),
+        None => {
+            return Err(Error::io(
+                path,
+                invalid_input("No frames to save".to_string()),
+            ))
+        }
+    };
+    let file = std::fs::File::create(path).map_err(|err| Error::io(path, err))?;
+    let writer = std::io::BufWriter::new(file);
+
+    let write_frames = || -> std::io::Result<usize> {
+        let mut num_frames = 0;
+        match format {
+            Format::Gif => {
+                let mut encoder = GifEncoder::new(writer, width, height, frame_delay)?;
+                for frame in frames {
+                    encoder.add_frame(&frame)?;
+                    num_frames += 1;
+                }
+                encoder.finish()?;
+            }
+            Format::Apng => {
+                let mut encoder = ApngEncoder::new(writer, width, height, frame_delay)?;
+                for frame in frames {
+                    encoder.add_frame(&frame)?;
+                    num_frames += 1;
+                }
+                encoder.finish()?;
+            }
+        }
+        Ok(num_frames)
+    };
+    write_frames().map_err(|err| Error::io(path, err))
+}
+
+/// An animated GIF encoder, for looping animations.
+///
+/// Each frame gets its own palette of up to 256 colors, see `quantize`. Only the rectangle that
+/// changed since the previous frame is stored, which keeps progressive animations small.
+pub struct GifEncoder<W: Write> {
+    writer: W,
+    width: usize,
+    height: usize,
+    /// The delay between frames, in hundredths of a second.
+    delay_cs: u16,
+    previous: Option<Image<Rgb>>,
+}
+
+impl<W: Write> GifEncoder<W> {
+    /// Writes the header of an animation with `width` x `height` frames.
+    pub fn new(
+        mut writer: W,
+        width: usize,
+        height: usize,
+        frame_delay: Duration,
+    ) -> std::io::Result<Self> {
+        if width == 0 || height == 0 || width > u16::MAX as usize || height > u16::MAX as usize {
+            return Err(invalid_input(format!(
+                "Invalid GIF size {}x{}, the max is 65535x65535",
+                width, height
+            )));
+        }
+
+        writer.write_all(b"GIF89a")?;
+        // Logical screen descriptor: no global color table, background color 0, square pixels.
+        writer.write_all(&(width as u16).to_le_bytes())?;
+        writer.write_all(&(height as u16).to_le_bytes())?;
+        writer.write_all(&[0, 0, 0])?;
+        // The NETSCAPE2.0 application extension, to loop forever.
+        writer.write_all(&[0x21, 0xFF, 11])?;
+        writer.write_all(b"NETSCAPE2.0")?;
+        writer.write_all(&[3, 1, 0, 0, 0])?;
+
+        let delay_cs = (frame_delay.as_millis() + 5) / 10;
+        Ok(Self {
+            writer,
+            width,
+            height,
+            delay_cs: delay_cs.min(u16::MAX as u128) as u16,
+            previous: None,
+        })
+    }
+
+    pub fn add_frame(&mut self, frame: &Image<Rgb>) -> std::io::Result<()> {
+        check_frame_size(frame, self.width, self.height)?;
+
+        // An unchanged frame still needs some pixels, so it redraws the top-left one.
+        let (top, left, height, width) = match &self.previous {
+            None => (0, 0, self.height, self.width),
+            Some(previous) => changed_rect(previous, frame).unwrap_or((0, 0, 1, 1)),
+        };
+        let (palette, indices) = quantize(&frame.view(top, left, height, width).to_image(), 256);
+        // The palette has 2^bits entries.
+        let bits = (usize::BITS - (palette.len() - 1).leading_zeros()).max(1);
+
+        // Graphic control extension: the delay, and keep this frame when drawing the next one.
+        let delay = self.delay_cs.to_le_bytes();
+        self.writer
+            .write_all(&[0x21, 0xF9, 4, 0b0000_0100, delay[0], delay[1], 0, 0])?;
+
+        // Image descriptor, with a local color table.
+        self.writer.write_all(&[0x2C])?;
+        for value in [left, top, width, height] {
+            self.writer.write_all(&(value as u16).to_le_bytes())?;
+        }
+        self.writer.write_all(&[0x80 | (bits as u8 - 1)])?;
+        for i in 0..1 << bits {
+            let color = palette.get(i).copied().unwrap_or(Rgb::new(0, 0, 0));
+            self.writer.write_all(&[color.r(), color.g(), color.b()])?;
+        }
+
+        // The LZW-compressed indices, in sub-blocks of up to 255 bytes.
+        let min_code_size = bits.max(2) as u8;
+        self.writer.write_all(&[min_code_size])?;
+        for block in lzw_encode(&indices, min_code_size).chunks(255) {
+            self.writer.write_all(&[block.len() as u8])?;
+            self.writer.write_all(block)?;
+        }
+        self.writer.write_all(&[0])?;
+
+        self.previous = Some(frame.clone());
+        Ok(())
+    }
+
+    /// Writes the end of the file, and returns the writer.
+    pub fn finish(mut self) -> std::io::Result<W> {
+        if self.previous.is_none() {
+            return Err(invalid_input(
+                "An animation needs at least one frame".to_string(),
+            ));
+        }
+        self.writer.write_all(&[0x3B])?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// An animated PNG encoder, for looping animations. The frames are stored uncompressed.
+///
+/// The number of frames is only known at the end, so the writer must be seekable.
+pub struct ApngEncoder<W: Write + Seek> {
+    writer: W,
+    width: usize,
+    height: usize,
+    /// The delay between frames, in milliseconds.
+    delay_ms: u16,
+    num_frames: u32,
+    /// The sequence number of the next fcTL or fdAT chunk.
+    sequence_number: u32,
+    /// The position of the acTL chunk, which holds the number of frames.
+    actl_pos: u64,
+}
+
+impl<W: Write + Seek> ApngEncoder<W> {
+    /// Writes the header of an animation with `width` x `height` frames.
+    pub fn new(
+        mut writer: W,
+        width: usize,
+        height: usize,
+        frame_delay: Duration,
+    ) -> std::io::Result<Self> {
+        if width == 0 || height == 0 || width > i32::MAX as usize || height > i32::MAX as usize {
+            return Err(invalid_input(format!(
+                "Invalid PNG size {}x{}",
+                width, height
+            )));
+        }
+
+        writer.write_all(b"\x89PNG\r\n\x1a\n")?;
+        let mut ihdr = Vec::new();
+        ihdr.extend((width as u32).to_be_bytes());
+        ihdr.extend((height as u32).to_be_bytes());
+        // 8 bits RGB, default compression and filtering, no interlacing.
+        ihdr.extend([8, 2, 0, 0, 0]);
+        write_png_chunk(&mut writer, b"IHDR", &ihdr)?;
+
+        // Filled in by `finish`.
+        let actl_pos = writer.stream_position()?;
+        write_png_chunk(&mut writer, b"acTL", &actl_data(0))?;
+
+        Ok(Self {
+            writer,
+            width,
+            height,
+            delay_ms: frame_delay.as_millis().min(u16::MAX as u128) as u16,
+            num_frames: 0,
+            sequence_number: 0,
+            actl_pos,
+        })
+    }
+
+    pub fn add_frame(&mut self, frame: &Image<Rgb>) -> std::io::Result<()> {
+        check_frame_size(frame, self.width, self.height)?;
+
+        // Frame control: the whole image, drawn over nothing, with the delay in ms.
+        let mut fctl = Vec::new();
+        fctl.extend(self.next_sequence_number().to_be_bytes());
+        fctl.extend((self.width as u32).to_be_bytes());
+        fctl.extend((self.height as u32).to_be_bytes());
+        fctl.extend([0; 8]);
+        fctl.extend(self.delay_ms.to_be_bytes());
+        fctl.extend(1000u16.to_be_bytes());
+        fctl.extend([0, 0]);
+        write_png_chunk(&mut self.writer, b"fcTL", &fctl)?;
+
+        // Each row has a filter type byte (0 = none), then the pixels.
+        let mut pixels = Vec::with_capacity(self.height * (1 + 3 * self.width));
+        for row in frame.data.chunks(self.width) {
+            pixels.push(0);
+            pixels.extend(row.iter().flat_map(|px| [px.r(), px.g(), px.b()]));
+        }
+        let data = zlib_stored(&pixels);
+
+        // The first frame is also the default image, shown by decoders without APNG support.
+        if self.num_frames == 0 {
+            write_png_chunk(&mut self.writer, b"IDAT", &data)?;
+        } else {
+            let mut fdat = self.next_sequence_number().to_be_bytes().to_vec();
+            fdat.extend(data);
+            write_png_chunk(&mut self.writer, b"fdAT", &fdat)?;
+        }
+        self.num_frames += 1;
+        Ok(())
+    }
+
+    /// Writes the end of the file, and returns the writer.
+    pub fn finish(mut self) -> std::io::Result<W> {
+        if self.num_frames == 0 {
+            return Err(invalid_input(
+                "An animation needs at least one frame".to_string(),
+            ));
+        }
+        write_png_chunk(&mut self.writer, b"IEND", &[])?;
+
+        let end = self.writer.stream_position()?;
+        self.writer.seek(SeekFrom::Start(self.actl_pos))?;
+        write_png_chunk(&mut self.writer, b"acTL", &actl_data(self.num_frames))?;
+        self.writer.seek(SeekFrom::Start(end))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn next_sequence_number(&mut self) -> u32 {
+        self.sequence_number += 1;
+        self.sequence_number - 1
+    }
+}
+
+/// Reduces the colors of an image to at most `max_colors`, using the median cut algorithm.
+/// Returns the palette, and the index in the palette of each pixel.
+///
+/// Images with few enough colors are kept as is.
+pub fn quantize(image: &Image<Rgb>, max_colors: usize) -> (Vec<Rgb>, Vec<u8>) {
+    assert!((1..=256).contains(&max_colors));
+
+    let mut histogram = HashMap::new();
+    for &px in &image.data {
+        *histogram.entry(px).or_insert(0usize) += 1;
+    }
+    // Sorted, so that the output doesn't depend on the hash map's order.
+    let mut colors: Vec<(Rgb, usize)> = histogram.into_iter().collect();
+    colors.sort_by_key(|&(color, _)| [color.r(), color.g(), color.b()]);
+
+    // Split the boxes of colors until there are enough, always splitting the one with the
+    // largest range along a channel.
+    let mut boxes = vec![colors];
+    while boxes.len() < max_colors {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .map(|(i, colors)| (i, widest_channel(colors)))
+            .max_by_key(|&(_, (_, range))| range);
+        let (i, channel) = match widest {
+            Some((i, (channel, range))) if range > 0 => (i, channel),
+            // All the boxes have a single color.
+            _ => break,
+        };
+
+        let mut colors = boxes.swap_remove(i);
+        colors.sort_by_key(|&(color, _)| channel_value(color, channel));
+        // Split at the median pixel, keeping at least one color on each side.
+        let total: usize = colors.iter().map(|&(_, count)| count).sum();
+        let mut seen = 0;
+        let split = colors
+            .iter()
+            .position(|&(_, count)| {
+                seen += count;
+                seen * 2 >= total
+            })
+            .unwrap()
+            .min(colors.len() - 2);
+        let upper = colors.split_off(split + 1);
+        boxes.push(colors);
+        boxes.push(upper);
+    }
+
+    // Each box becomes the average of its colors.
+    let mut palette = Vec::with_capacity(boxes.len());
+    let mut color_indices = HashMap::new();
+    for colors in &boxes {
+        let total: usize = colors.iter().map(|&(_, count)| count).sum();
+        let average = |channel: usize| -> u8 {
+            let sum: usize = colors
+                .iter()
+                .map(|&(color, count)| channel_value(color, channel) as usize * count)
+                .sum();
+            ((sum + total / 2) / total) as u8
+        };
+        for &(color, _) in colors {
+            color_indices.insert(color, palette.len() as u8);
+        }
+        palette.push(Rgb::new(average(0), average(1), average(2)));
+    }
+
+    let indices = image.data.iter().map(|px| color_indices[px]).collect();
+    (palette, indices)
+}
+
+/// The channel along which colors have the largest range, and that range.
+fn widest_channel(colors: &[(Rgb, usize)]) -> (usize, u8) {
+    (0..3)
+        .map(|channel| {
+            let values = colors
+                .iter()
+                .map(|&(color, _)| channel_value(color, channel));
+            let range = values.clone().max().unwrap() - values.min().unwrap();
+            (channel, range)
+        })
+        .max_by_key(|&(_, range)| range)
+        .unwrap()
+}
+
+/// The value of channel 0 (red), 1 (green) or 2 (blue).
+fn channel_value(color: Rgb, channel: usize) -> u8 {
+    [color.r(), color.g(), color.b()][channel]
+}
+
+/// The smallest rectangle containing the pixels which differ, as (top, left, height, width), or
+/// None if the images are identical.
+fn changed_rect(a: &Image<Rgb>, b: &Image<Rgb>) -> Option<(usize, usize, usize, usize)> {
+    let changed = a
+        .enumerate_pixels()
+        .zip(&b.data)
+        .filter(|((_, _, pa), pb)| pa != pb)
+        .map(|((row, col, _), _)| (row, col));
+
+    let mut rect: Option<(usize, usize, usize, usize)> = None;
+    for (row, col) in changed {
+        let (min_row, min_col, max_row, max_col) = rect.unwrap_or((row, col, row, col));
+        rect = Some((
+            min_row.min(row),
+            min_col.min(col),
+            max_row.max(row),
+            max_col.max(col),
+        ));
+    }
+    let (min_row, min_col, max_row, max_col) = rect?;
+    Some((
+        min_row,
+        min_col,
+        max_row - min_row + 1,
+        max_col - min_col + 1,
+    ))
+}
+
+/// Compresses data with the variable-length LZW flavor of GIF, with codes packed starting from the
+/// lowest bits.
+fn lzw_encode(data: &[u8], min_code_size: u8) -> Vec<u8> {
+    const MAX_CODES: u16 = 4096;
+    let clear_code = 1u16 << min_code_size;
+    let end_code = clear_code + 1;
+
+    let mut out = BitWriter::with_order(BitOrder::LsbFirst);
+    // The decoder learns each code one step later than us, so when we're about to assign
+    // `next_code`, it's at `next_code - 1`.
+    let mut write_code = |code: u16, next_code: u16| {
+        let width =
+            (u16::BITS - (next_code - 1).leading_zeros()).clamp(min_code_size as u32 + 1, 12);
+        out.put_bits_u64(code as u64, width as u8);
+    };
+
+    let mut codes: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next_code = end_code + 1;
+    write_code(clear_code, next_code);
+
+    let mut data = data.iter().copied();
+    if let Some(first) = data.next() {
+        let mut prefix = first as u16;
+        for byte in data {
+            if let Some(&code) = codes.get(&(prefix, byte)) {
+                prefix = code;
+                continue;
+            }
+            write_code(prefix, next_code);
+            if next_code < MAX_CODES {
+                codes.insert((prefix, byte), next_code);
+                next_code += 1;
+            } else {
+                // The table is full, start over.
+                write_code(clear_code, next_code);
+                codes.clear();
+                next_code = end_code + 1;
+            }
+            prefix = byte as u16;
+        }
+        write_code(prefix, next_code);
+    }
+    write_code(end_code, next_code);
+
+    out.into_bytes()
+}
+
+fn check_frame_size(frame: &Image<Rgb>, width: usize, height: usize) -> std::io::Result<()> {
+    if frame.size_hw() != (height, width) {
+        return Err(invalid_input(format!(
+            "Expected a {}x{} frame, got {}x{}",
+            width, height, frame.width, frame.height
+        )));
+    }
+    Ok(())
+}
+
+fn invalid_input(msg: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, msg)
+}
+
+/// The data of an acTL chunk, for an animation which loops forever.
+fn actl_data(num_frames: u32) -> Vec<u8> {
+    let mut data = num_frames.to_be_bytes().to_vec();
+    data.extend(0u32.to_be_bytes());
+    data
+}
+
+fn write_png_chunk(writer: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> std::io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data));
+    writer.write_all(&crc.to_be_bytes())
+}
+
+/// Wraps data in a zlib stream, without compressing it (deflate "stored" blocks).
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK_LEN: usize = u16::MAX as usize;
+    let mut out = Vec::with_capacity(data.len() + data.len() / MAX_BLOCK_LEN * 5 + 11);
+    // Deflate with a 32K window, no preset dictionary, and a header checksum.
+    out.extend([0x78, 0x01]);
+    let mut blocks = data.chunks(MAX_BLOCK_LEN).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(is_final as u8);
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+/// The CRC-32 checksum of PNG chunks (the same as zlib's).
+fn crc32<'a>(bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// The Adler-32 checksum of zlib streams.
+fn adler32(bytes: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // Reducing every 5552 bytes is enough to avoid overflows.
+    for chunk in bytes.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{adler32, crc32, quantize, ApngEncoder, GifEncoder};
+    use crate::colormap::Rgb;
+    use crate::image::Image;
+    use std::time::Duration;
+
+    /// A simple pseudo-random image, with up to `num_colors` colors.
+    fn noise_image(height: usize, width: usize, num_colors: u32, seed: u32) -> Image<Rgb> {
+        let mut state = seed;
+        let data = (0..height * width)
+            .map(|_| {
+                // A linear congruential generator, good enough for noise.
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let c = (state >> 8) % num_colors;
+                Rgb::new(c as u8, (c >> 8) as u8, (c * 7) as u8)
+            })
+            .collect();
+        Image {
+            height,
+            width,
+            data,
+        }
+    }
+
+    /// Decodes GIF LZW data, as described by the GIF89a spec.
+    fn lzw_decode(data: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear_code = 1usize << min_code_size;
+        let end_code = clear_code + 1;
+        let initial_table: Vec<Vec<u8>> = (0..=end_code).map(|i| vec![i as u8]).collect();
+
+        let mut table = initial_table.clone();
+        let mut width = min_code_size as usize + 1;
+        let mut bit_pos = 0;
+        let mut prev: Option<usize> = None;
+        let mut out = Vec::new();
+        loop {
+            let code: usize = (0..width)
+                .map(|i| ((data[(bit_pos + i) / 8] >> ((bit_pos + i) % 8)) as usize & 1) << i)
+                .sum();
+            bit_pos += width;
+
+            if code == clear_code {
+                table = initial_table.clone();
+                width = min_code_size as usize + 1;
+                prev = None;
+                continue;
+            }
+            if code == end_code {
+                break;
+            }
+            let entry = match prev {
+                None => table[code].clone(),
+                Some(prev) => {
+                    let entry = if code < table.len() {
+                        table[code].clone()
+                    } else {
+                        assert_eq!(code, table.len());
+                        let mut entry = table[prev].clone();
+                        entry.push(entry[0]);
+                        entry
+                    };
+                    if table.len() < 4096 {
+                        let mut new_entry = table[prev].clone();
+                        new_entry.push(entry[0]);
+                        table.push(new_entry);
+                    }
+                    entry
+                }
+            };
+            out.extend(&entry);
+            if table.len() == 1 << width && width < 12 {
+                width += 1;
+            }
+            prev = Some(code);
+        }
+        out
+    }
+
+    /// Decodes the frames of a GIF with local color tables, drawing each over the previous one.
+    fn decode_gif(bytes: &[u8]) -> Vec<Image<Rgb>> {
+        assert_eq!(&bytes[..6], b"GIF89a");
+        let u16_at = |pos: usize| u16::from_le_bytes([bytes[pos], bytes[pos + 1]]) as usize;
+        let mut canvas = Image::filled(u16_at(8), u16_at(6), Rgb::new(0, 0, 0));
+        let mut frames = Vec::new();
+
+        let mut pos = 13;
+        // Skips sub-blocks, returning their concatenated data.
+        let read_sub_blocks = |pos: &mut usize| {
+            let mut data = Vec::new();
+            while bytes[*pos] != 0 {
+                let len = bytes[*pos] as usize;
+                data.extend(&bytes[*pos + 1..*pos + 1 + len]);
+                *pos += len + 1;
+            }
+            *pos += 1;
+            data
+        };
+        loop {
+            match bytes[pos] {
+                // Extension.
+                0x21 => {
+                    pos += 2;
+                    read_sub_blocks(&mut pos);
+                }
+                // Image.
+                0x2C => {
+                    let (left, top, width, height) = (
+                        u16_at(pos + 1),
+                        u16_at(pos + 3),
+                        u16_at(pos + 5),
+                        u16_at(pos + 7),
+                    );
+                    let flags = bytes[pos + 9];
+                    assert_ne!(flags & 0x80, 0, "Expected a local color table");
+                    let table_len = 2 << (flags & 7);
+                    pos += 10;
+                    let palette: Vec<Rgb> = bytes[pos..pos + 3 * table_len]
+                        .chunks(3)
+                        .map(|c| Rgb::new(c[0], c[1], c[2]))
+                        .collect();
+                    pos += 3 * table_len;
+                    let min_code_size = bytes[pos];
+                    pos += 1;
+
+                    let indices = lzw_decode(&read_sub_blocks(&mut pos), min_code_size);
+                    assert_eq!(indices.len(), width * height);
+                    for (i, &index) in indices.iter().enumerate() {
+                        *canvas.pixel_mut(top + i / width, left + i % width) =
+                            palette[index as usize];
+                    }
+                    frames.push(canvas.clone());
+                }
+                0x3B => break,
+                b => panic!("Unexpected block {:#x}", b),
+            }
+        }
+        assert_eq!(pos + 1, bytes.len());
+        frames
+    }
+
+    #[test]
+    fn gif_round_trip() {
+        // Few colors, so no quantization. Large enough to fill the LZW table several times.
+        let mut frames = vec![noise_image(150, 200, 256, 1), noise_image(150, 200, 3, 2)];
+        // Small changes, then no change at all.
+        let mut changed = frames[1].clone();
+        *changed.pixel_mut(100, 20) = Rgb::new(1, 2, 3);
+        *changed.pixel_mut(5, 150) = Rgb::new(4, 5, 6);
+        frames.push(changed.clone());
+        frames.push(changed);
+        frames.push(Image::filled(150, 200, Rgb::new(9, 9, 9)));
+
+        let mut encoder = GifEncoder::new(Vec::new(), 200, 150, Duration::from_millis(40)).unwrap();
+        for frame in &frames {
+            encoder.add_frame(frame).unwrap();
+        }
+        let bytes = encoder.finish().unwrap();
+        assert_eq!(decode_gif(&bytes), frames);
+
+        // 4cs delay, in the graphic control extensions.
+        assert!(bytes.windows(6).any(|w| w == [0x21, 0xF9, 4, 0b100, 4, 0]));
+    }
+
+    #[test]
+    fn gif_errors() {
+        let mut encoder = GifEncoder::new(Vec::new(), 2, 3, Duration::ZERO).unwrap();
+        assert!(encoder
+            .add_frame(&Image::filled(2, 3, Rgb::new(0, 0, 0)))
+            .is_err());
+        assert!(encoder.finish().is_err());
+        assert!(GifEncoder::new(Vec::new(), 70_000, 1, Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn quantize_colors() {
+        // Few enough colors: exact.
+        let img = noise_image(20, 20, 100, 3);
+        let (palette, indices) = quantize(&img, 256);
+        assert!(palette.len() <= 100);
+        let restored: Vec<Rgb> = indices.iter().map(|&i| palette[i as usize]).collect();
+        assert_eq!(restored, img.data);
+
+        // A gradient with more colors than the palette: the error stays small.
+        let gradient = Image {
+            height: 1,
+            width: 1000,
+            data: (0..1000)
+                .map(|i| Rgb::new((i / 4) as u8, (i % 256) as u8, 0))
+                .collect(),
+        };
+        let (palette, indices) = quantize(&gradient, 16);
+        assert_eq!(palette.len(), 16);
+        for (px, &i) in gradient.data.iter().zip(&indices) {
+            let q = palette[i as usize];
+            let diff = |a: u8, b: u8| (a as i32 - b as i32).abs();
+            assert!(
+                diff(px.r(), q.r()) + diff(px.g(), q.g()) <= 96,
+                "{:?} {:?}",
+                px,
+                q
+            );
+        }
+    }
+
+    /// Splits a PNG into its (kind, data) chunks, checking the CRCs.
+    fn png_chunks(bytes: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+        assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+        let mut chunks = Vec::new();
+        let mut pos = 8;
+        while pos < bytes.len() {
+            let len = u32::from_be_bytes(bytes[pos..pos + 4].try_into().unwrap()) as usize;
+            let kind: [u8; 4] = bytes[pos + 4..pos + 8].try_into().unwrap();
+            let data = bytes[pos + 8..pos + 8 + len].to_vec();
+            let crc = u32::from_be_bytes(bytes[pos + 8 + len..pos + 12 + len].try_into().unwrap());
+            assert_eq!(crc, crc32(kind.iter().chain(&data)));
+            chunks.push((kind, data));
+            pos += 12 + len;
+        }
+        chunks
+    }
+
+    /// Decodes a zlib stream made of stored blocks.
+    fn zlib_stored_decode(bytes: &[u8]) -> Vec<u8> {
+        assert_eq!((bytes[0] as u16 * 256 + bytes[1] as u16) % 31, 0);
+        let mut out = Vec::new();
+        let mut pos = 2;
+        loop {
+            let is_final = bytes[pos] & 1 == 1;
+            assert_eq!(bytes[pos] >> 1, 0, "Expected a stored block");
+            let len = u16::from_le_bytes([bytes[pos + 1], bytes[pos + 2]]);
+            let nlen = u16::from_le_bytes([bytes[pos + 3], bytes[pos + 4]]);
+            assert_eq!(len, !nlen);
+            out.extend(&bytes[pos + 5..pos + 5 + len as usize]);
+            pos += 5 + len as usize;
+            if is_final {
+                break;
+            }
+        }
+        assert_eq!(bytes[pos..], adler32(&out).to_be_bytes());
+        out
+    }
+
+    #[test]
+    fn apng_round_trip() {
+        // Large enough for several deflate blocks per frame.
+        let frames: Vec<Image<Rgb>> = (0..3).map(|i| noise_image(120, 300, 1000, i)).collect();
+        let mut encoder = ApngEncoder::new(
+            std::io::Cursor::new(Vec::new()),
+            300,
+            120,
+            Duration::from_millis(40),
+        )
+        .unwrap();
+        for frame in &frames {
+            encoder.add_frame(frame).unwrap();
+        }
+        let bytes = encoder.finish().unwrap().into_inner();
+
+        let chunks = png_chunks(&bytes);
+        let kinds: Vec<&[u8]> = chunks.iter().map(|(kind, _)| &kind[..]).collect();
+        assert_eq!(
+            kinds,
+            [b"IHDR", b"acTL", b"fcTL", b"IDAT", b"fcTL", b"fdAT", b"fcTL", b"fdAT", b"IEND"]
+        );
+        // 3 frames, looping forever.
+        assert_eq!(chunks[1].1, [0, 0, 0, 3, 0, 0, 0, 0]);
+        // Sequence number, size, offset, delay of 40/1000s, no dispose or blend.
+        assert_eq!(
+            chunks[4].1,
+            [0, 0, 0, 1, 0, 0, 1, 44, 0, 0, 0, 120, 0, 0, 0, 0, 0, 0, 0, 0, 0, 40, 3, 232, 0, 0]
+        );
+
+        let frame_data = [&chunks[3].1[..], &chunks[5].1[4..], &chunks[7].1[4..]];
+        assert_eq!(chunks[7].1[..4], [0, 0, 0, 4]);
+        for (data, frame) in frame_data.iter().zip(&frames) {
+            let pixels = zlib_stored_decode(data);
+            let expected: Vec<u8> = frame
+                .data
+                .chunks(300)
+                .flat_map(|row| {
+                    std::iter::once(0).chain(row.iter().flat_map(|px| [px.r(), px.g(), px.b()]))
+                })
+                .collect();
+            assert_eq!(pixels, expected);
+        }
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"123456789".iter()), 0xCBF4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!(adler32(&[255; 100_000]), 0x149A_302C);
+    }
+}
@@ -0,0 +1,346 @@
+//! The registry of known-good answers, used to check that the solvers still produce them.
+//!
+//! It's read from `answers.toml` in the data dir, with one table per puzzle input, named after
+//! the input file:
+//!
+//! ```toml
+//! ["2021-12-01.txt"]
+//! part1 = 1529
+//! part2 = "1567"
+//! ```
+//!
+//! Only the subset of TOML needed for this is supported: tables, comments, integers, and basic
+//! strings (single or multi-line).
+
+use crate::error::{Error, Result};
+use crate::solver::Answer;
+
+/// The file name of the registry, in the data dir.
+pub const ANSWERS_FILENAME: &str = "answers.toml";
+
+/// The known-good answers for a single puzzle input.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExpectedAnswers {
+    /// The input's file name, in the data dir, eg "2021-12-15.sample.txt".
+    pub input: String,
+    /// The day, taken from the input's file name.
+    pub day: u8,
+    pub part1: Option<Answer>,
+    pub part2: Option<Answer>,
+}
+
+impl ExpectedAnswers {
+    pub fn part(&self, part: u8) -> Option<&Answer> {
+        match part {
+            1 => self.part1.as_ref(),
+            2 => self.part2.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+/// Reads the registry from the data dir.
+pub fn read_answers() -> Result<Vec<ExpectedAnswers>> {
+    let mut path = crate::env::try_get_data_dir()?;
+    path.push(ANSWERS_FILENAME);
+    let text = std::fs::read_to_string(&path).map_err(|err| Error::io(&path, err))?;
+    parse_answers(&text)
+}
+
+/// Parses the registry, see the module docs for the format.
+pub fn parse_answers(text: &str) -> Result<Vec<ExpectedAnswers>> {
+    let mut answers: Vec<ExpectedAnswers> = Vec::new();
+    // The remainder of the text to parse, always at the start of a line.
+    let mut rest = text;
+    let error_at = |at: &str, msg: String| Error::parse_at_offset(text, text.len() - at.len(), msg);
+
+    while !rest.is_empty() {
+        let line_end = rest.find('\n').map(|i| i + 1).unwrap_or(rest.len());
+        let line = strip_comment(&rest[..line_end]).trim();
+
+        if line.is_empty() {
+            rest = &rest[line_end..];
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[') {
+            // A new table, for a new input.
+            let name = header
+                .strip_suffix(']')
+                .ok_or_else(|| error_at(rest, "Expected ']'".to_string()))?
+                .trim();
+            let input = match name.strip_prefix('"') {
+                Some(quoted) => parse_basic_string(quoted, &error_at)?.0,
+                None => name.to_string(),
+            };
+            let day = day_from_input_name(&input).ok_or_else(|| {
+                error_at(
+                    rest,
+                    format!("Expected a 2021-12-NN file name, got {:?}", input),
+                )
+            })?;
+            if answers.iter().any(|a| a.input == input) {
+                return Err(error_at(rest, format!("Duplicate input {:?}", input)));
+            }
+
+            answers.push(ExpectedAnswers {
+                input,
+                day,
+                part1: None,
+                part2: None,
+            });
+            rest = &rest[line_end..];
+            continue;
+        }
+
+        // A `key = value` pair, where the value may span several lines.
+        let (key, value_start) = rest
+            .split_once('=')
+            .ok_or_else(|| error_at(rest, "Expected a table header or key = value".to_string()))?;
+        let key = key.trim();
+        let value_start = value_start.trim_start_matches([' ', '\t']);
+        let (value, after_value) = parse_value(value_start, &error_at)?;
+
+        // Only comments may follow the value on its line.
+        let after_value_line_end = after_value
+            .find('\n')
+            .map(|i| i + 1)
+            .unwrap_or(after_value.len());
+        if !strip_comment(&after_value[..after_value_line_end])
+            .trim()
+            .is_empty()
+        {
+            return Err(error_at(
+                after_value.trim_start(),
+                "Unexpected text after value".to_string(),
+            ));
+        }
+
+        let table = answers
+            .last_mut()
+            .ok_or_else(|| error_at(rest, "Expected a table header first".to_string()))?;
+        let slot = match key {
+            "part1" => &mut table.part1,
+            "part2" => &mut table.part2,
+            _ => return Err(error_at(rest, format!("Unknown key {:?}", key))),
+        };
+        if slot.is_some() {
+            return Err(error_at(rest, format!("Duplicate key {:?}", key)));
+        }
+        *slot = Some(value);
+
+        rest = &after_value[after_value_line_end..];
+    }
+
+    Ok(answers)
+}
+
+/// Compares an answer with the expected one. Returns None if they match, or a line-by-line diff
+/// otherwise, with expected lines prefixed by '-' and actual ones by '+'.
+pub fn diff_answers(expected: &str, actual: &str) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+
+    let mut diff = String::new();
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (None, None) => break,
+            (Some(e), Some(a)) if e == a => diff.push_str(&format!(" {}\n", e)),
+            (e, a) => {
+                if let Some(e) = e {
+                    diff.push_str(&format!("-{}\n", e));
+                }
+                if let Some(a) = a {
+                    diff.push_str(&format!("+{}\n", a));
+                }
+            }
+        }
+    }
+    Some(diff)
+}
+
+/// Extracts the day from an input file name, eg "2021-12-05.sample.txt" => 5.
+fn day_from_input_name(name: &str) -> Option<u8> {
+    let day = name
+        .strip_prefix("2021-12-")?
+        .get(..2)?
+        .parse::<u8>()
+        .ok()?;
+    if (1..=25).contains(&day) {
+        Some(day)
+    } else {
+        None
+    }
+}
+
+/// Removes a trailing comment from a line, if any. Comments can't appear in strings, except for
+/// the table headers.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Parses a value at the start of `text`, returning it and the rest of the text.
+fn parse_value<'a>(
+    text: &'a str,
+    error_at: &dyn Fn(&str, String) -> Error,
+) -> Result<(String, &'a str)> {
+    if let Some(multiline) = text.strip_prefix("\"\"\"") {
+        // A newline right after the opening quotes isn't part of the string.
+        let multiline = multiline
+            .strip_prefix("\r\n")
+            .or_else(|| multiline.strip_prefix('\n'))
+            .unwrap_or(multiline);
+        let end = multiline
+            .find("\"\"\"")
+            .ok_or_else(|| error_at(text, "Unterminated multi-line string".to_string()))?;
+        let value = unescape(&multiline[..end], error_at)?;
+        Ok((value, &multiline[end + 3..]))
+    } else if let Some(quoted) = text.strip_prefix('"') {
+        parse_basic_string(quoted, error_at)
+    } else {
+        // An integer, kept as text.
+        let end = text
+            .find(|c: char| !(c.is_ascii_digit() || c == '-' || c == '+' || c == '_'))
+            .unwrap_or(text.len());
+        let digits: String = text[..end]
+            .chars()
+            .filter(|&c| c != '_' && c != '+')
+            .collect();
+        if digits.parse::<i128>().is_err() {
+            return Err(error_at(
+                text,
+                "Expected a string or an integer".to_string(),
+            ));
+        }
+        Ok((digits, &text[end..]))
+    }
+}
+
+/// Parses a single-line string, right after its opening quote, returning it and the rest of the
+/// text after the closing quote.
+fn parse_basic_string<'a>(
+    text: &'a str,
+    error_at: &dyn Fn(&str, String) -> Error,
+) -> Result<(String, &'a str)> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            '\n' => break,
+            '"' if !escaped => return Ok((unescape(&text[..i], error_at)?, &text[i + 1..])),
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+    Err(error_at(text, "Unterminated string".to_string()))
+}
+
+/// Replaces the escape sequences from a string's contents.
+fn unescape(text: &str, error_at: &dyn Fn(&str, String) -> Error) -> Result<String> {
+    let mut res = String::with_capacity(text.len());
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c != '\\' {
+            res.push(c);
+            continue;
+        }
+        match chars.next().map(|(_, c)| c) {
+            Some('n') => res.push('\n'),
+            Some('t') => res.push('\t'),
+            Some('"') => res.push('"'),
+            Some('\\') => res.push('\\'),
+            _ => {
+                return Err(error_at(
+                    &text[i..],
+                    "Unsupported escape sequence".to_string(),
+                ))
+            }
+        }
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExpectedAnswers;
+
+    #[test]
+    fn parse_answers() {
+        let text = r#"
+# Some comment.
+["2021-12-01.txt"]
+part1 = 1529  # An integer.
+part2 = "15\"67"
+
+[2021-12-13.sample.txt]
+part2 = """
+#####
+#   #
+#####"""
+"#;
+        assert_eq!(
+            super::parse_answers(text).unwrap(),
+            vec![
+                ExpectedAnswers {
+                    input: "2021-12-01.txt".to_string(),
+                    day: 1,
+                    part1: Some("1529".to_string()),
+                    part2: Some("15\"67".to_string()),
+                },
+                ExpectedAnswers {
+                    input: "2021-12-13.sample.txt".to_string(),
+                    day: 13,
+                    part1: None,
+                    part2: Some("#####\n#   #\n#####".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_invalid_answers() {
+        let error_pos = |text: &str| match super::parse_answers(text) {
+            Err(crate::error::Error::Parse { line, column, .. }) => (line, column),
+            res => panic!("Unexpected result {:?}", res),
+        };
+        assert_eq!(error_pos("part1 = 1"), (1, 1));
+        assert_eq!(error_pos("[2021-12-01.txt]\npart1 = 1 2"), (2, 11));
+        assert_eq!(error_pos("[2021-12-01.txt]\npart3 = 1"), (2, 1));
+        assert_eq!(error_pos("[2021-12-26.txt]"), (1, 1));
+        assert_eq!(error_pos("[2021-12-01.txt]\n\npart1 = \"\"\"\nabc"), (3, 9));
+    }
+
+    /// The registry in the data dir is well-formed, and only refers to existing inputs.
+    #[test]
+    fn read_answers() {
+        let registry = super::read_answers().unwrap();
+        assert!(!registry.is_empty());
+        for expected in registry {
+            let path = crate::env::get_puzzle_input_path(&expected.input);
+            assert!(path.is_file(), "Missing input {:?}", path);
+        }
+    }
+
+    #[test]
+    fn diff_answers() {
+        assert_eq!(super::diff_answers("123", "123"), None);
+        assert_eq!(
+            super::diff_answers("123", "124"),
+            Some("-123\n+124\n".to_string())
+        );
+        assert_eq!(
+            super::diff_answers("ab\ncd\nef", "ab\nxy"),
+            Some(" ab\n-cd\n+xy\n-ef\n".to_string())
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! A small benchmarking harness, timing each phase of the solvers (parsing, part 1 and part 2)
+//! separately.
+
+use crate::solver::DynSolver;
+use std::time::{Duration, Instant};
+
+/// Summary statistics over the timings of several iterations.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    /// 95th percentile, using the nearest-rank method.
+    pub p95: Duration,
+}
+
+impl Stats {
+    /// Computes the stats of a non-empty set of samples.
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        assert!(!samples.is_empty());
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+
+        let n = sorted.len();
+        let median = if n.is_multiple_of(2) {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+        } else {
+            sorted[n / 2]
+        };
+        // Smallest sample that's >= 95% of all samples.
+        let p95_rank = (n * 95).div_ceil(100);
+
+        Self {
+            min: sorted[0],
+            median,
+            p95: sorted[p95_rank - 1],
+        }
+    }
+}
+
+/// The stats for each phase of a solver. Parts that weren't run are None.
+#[derive(Clone, Debug)]
+pub struct PhaseStats {
+    pub parse: Stats,
+    pub part1: Option<Stats>,
+    pub part2: Option<Stats>,
+}
+
+/// Runs a solver on an input `warmup + iterations` times, and returns the stats of the last
+/// `iterations` runs. Only the given `parts` are run.
+///
+/// Each iteration parses the input again, then runs the parts on it.
+pub fn bench_solver(
+    solver: &dyn DynSolver,
+    text: &str,
+    parts: &[u8],
+    warmup: usize,
+    iterations: usize,
+) -> crate::error::Result<PhaseStats> {
+    assert!(iterations > 0);
+
+    let mut parse_samples = Vec::with_capacity(iterations);
+    let mut part_samples = [
+        Vec::with_capacity(iterations),
+        Vec::with_capacity(iterations),
+    ];
+
+    for iteration in 0..warmup + iterations {
+        let is_warmup = iteration < warmup;
+
+        let start = Instant::now();
+        let input = solver.parse(text)?;
+        let elapsed = start.elapsed();
+        if !is_warmup {
+            parse_samples.push(elapsed);
+        }
+
+        for &part in parts {
+            let start = Instant::now();
+            let answer = match part {
+                1 => solver.part1(input.as_ref()),
+                2 => solver.part2(input.as_ref()),
+                _ => panic!("Invalid part {}", part),
+            };
+            let elapsed = start.elapsed();
+            // Make sure computing the answer can't be optimized away.
+            std::hint::black_box(answer);
+            if !is_warmup {
+                part_samples[part as usize - 1].push(elapsed);
+            }
+        }
+    }
+
+    let part_stats = |samples: &[Duration]| {
+        if samples.is_empty() {
+            None
+        } else {
+            Some(Stats::from_samples(samples))
+        }
+    };
+    Ok(PhaseStats {
+        parse: Stats::from_samples(&parse_samples),
+        part1: part_stats(&part_samples[0]),
+        part2: part_stats(&part_samples[1]),
+    })
+}
+
+/// The benchmark results for a single day.
+#[derive(Clone, Debug)]
+pub struct DayReport {
+    pub day: u8,
+    pub title: &'static str,
+    pub input: std::path::PathBuf,
+    /// The stats, or an error message if the day couldn't be benchmarked.
+    pub stats: Result<PhaseStats, String>,
+}
+
+/// The results of a benchmark run, over several days.
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub warmup: usize,
+    pub iterations: usize,
+    pub days: Vec<DayReport>,
+}
+
+impl Report {
+    /// Serializes the report as JSON, with durations in nanoseconds, eg:
+    ///
+    /// ```json
+    /// {"warmup": 1, "iterations": 10, "days": [
+    ///   {"day": 1, "title": "Sonar Sweep", "input": "data/2021-12-01.txt",
+    ///    "parse": {"min_ns": 1, "median_ns": 2, "p95_ns": 3}, "part1": {...}, "part2": null}
+    /// ]}
+    /// ```
+    ///
+    /// Days that failed have an "error" field instead of the phases.
+    pub fn to_json(&self) -> String {
+        use std::fmt::Write;
+
+        let stats_json = |stats: Option<&Stats>| match stats {
+            None => "null".to_string(),
+            Some(s) => format!(
+                "{{\"min_ns\": {}, \"median_ns\": {}, \"p95_ns\": {}}}",
+                s.min.as_nanos(),
+                s.median.as_nanos(),
+                s.p95.as_nanos()
+            ),
+        };
+
+        let mut json = String::new();
+        writeln!(
+            json,
+            "{{\"warmup\": {}, \"iterations\": {}, \"days\": [",
+            self.warmup, self.iterations
+        )
+        .unwrap();
+        for (i, day) in self.days.iter().enumerate() {
+            write!(
+                json,
+                "  {{\"day\": {}, \"title\": {}, \"input\": {}, ",
+                day.day,
+                json_string(day.title),
+                json_string(&day.input.to_string_lossy())
+            )
+            .unwrap();
+            match &day.stats {
+                Ok(stats) => write!(
+                    json,
+                    "\"parse\": {}, \"part1\": {}, \"part2\": {}}}",
+                    stats_json(Some(&stats.parse)),
+                    stats_json(stats.part1.as_ref()),
+                    stats_json(stats.part2.as_ref())
+                ),
+                Err(err) => write!(json, "\"error\": {}}}", json_string(err)),
+            }
+            .unwrap();
+            json.push_str(if i + 1 < self.days.len() { ",\n" } else { "\n" });
+        }
+        json.push_str("]}\n");
+        json
+    }
+}
+
+/// Quotes and escapes a string for JSON.
+fn json_string(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            c if (c as u32) < 0x20 => res.push_str(&format!("\\u{:04x}", c as u32)),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    #[test]
+    fn stats_from_samples() {
+        let ms = Duration::from_millis;
+
+        let stats = super::Stats::from_samples(&[ms(5)]);
+        assert_eq!((stats.min, stats.median, stats.p95), (ms(5), ms(5), ms(5)));
+
+        let stats = super::Stats::from_samples(&[ms(4), ms(1), ms(3), ms(2)]);
+        assert_eq!(
+            (stats.min, stats.median, stats.p95),
+            (ms(1), Duration::from_micros(2500), ms(4))
+        );
+
+        // With 20 samples, the 95th percentile is the 19th smallest one.
+        let samples: Vec<Duration> = (1..=20).rev().map(ms).collect();
+        let stats = super::Stats::from_samples(&samples);
+        assert_eq!(
+            (stats.min, stats.median, stats.p95),
+            (ms(1), Duration::from_micros(10500), ms(19))
+        );
+    }
+
+    #[test]
+    fn report_to_json() {
+        let ms = Duration::from_millis;
+        let stats = super::Stats {
+            min: ms(1),
+            median: ms(2),
+            p95: ms(3),
+        };
+        let report = super::Report {
+            warmup: 1,
+            iterations: 10,
+            days: vec![
+                super::DayReport {
+                    day: 1,
+                    title: "Sonar Sweep",
+                    input: "data/2021-12-01.txt".into(),
+                    stats: Ok(super::PhaseStats {
+                        parse: stats,
+                        part1: Some(stats),
+                        part2: None,
+                    }),
+                },
+                super::DayReport {
+                    day: 2,
+                    title: "Dive!",
+                    input: "x.txt".into(),
+                    stats: Err("bad \"input\"".to_string()),
+                },
+            ],
+        };
+
+        let stats_json = r#"{"min_ns": 1000000, "median_ns": 2000000, "p95_ns": 3000000}"#;
+        assert_eq!(
+            report.to_json(),
+            format!(
+                r#"{{"warmup": 1, "iterations": 10, "days": [
+  {{"day": 1, "title": "Sonar Sweep", "input": "data/2021-12-01.txt", "parse": {0}, "part1": {0}, "part2": null}},
+  {{"day": 2, "title": "Dive!", "input": "x.txt", "error": "bad \"input\""}}
+]}}
+"#,
+                stats_json
+            )
+        );
+    }
+}
@@ -0,0 +1,458 @@
+//! Runs the solvers for any day of Advent of Code 2021.
+
+use advent_of_code::answers::{self, ExpectedAnswers};
+use advent_of_code::bench;
+use advent_of_code::days;
+use advent_of_code::solver::{Answer, DynSolver};
+use advent_of_code::Error;
+
+const USAGE: &str = "\
+Usage:
+    aoc list
+    aoc run <day|all> [--part <1|2>] [--input <path>] [--check] [--<option>=<value>...]
+    aoc run <day|all> --bench [--iterations <n>] [--warmup <n>] [--json <path>] [...]
+
+Commands:
+    list    Lists the days for which a solver exists.
+    run     Runs the solver for a single day, or for all of them.
+
+Options for `run`:
+    --part <1|2>        Only run one part of the puzzle.
+    --input <path>      Read the puzzle input from this file, instead of the default
+                        2021-12-<day>.txt in the data dir. Only valid for a single day.
+    --check             Run each input listed in answers.toml in the data dir, and check
+                        the answers against the expected ones.
+    --bench             Time the parsing and each part separately, over several iterations,
+                        and report the min, median and 95th percentile of each.
+    --iterations <n>    With --bench, the number of timed iterations (default: 10).
+    --warmup <n>        With --bench, the number of untimed iterations to run first
+                        (default: 1).
+    --json <path>       With --bench, also write the timings as JSON to this file.
+    --<option>=<value>  Day-specific options, eg `aoc run 15 --log-images-to=/tmp`.";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    // Ok(false) when some days failed to run.
+    let result = match args.first().map(|s| s.as_str()) {
+        Some("list") if args.len() == 1 => {
+            list();
+            Ok(true)
+        }
+        Some("run") => parse_run_args(&args[1..]).and_then(|run_args| run(&run_args)),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            Ok(true)
+        }
+        _ => Err("Invalid command.".to_string()),
+    };
+
+    match result {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(err) => {
+            eprintln!("Error: {}\n\n{}", err, USAGE);
+            std::process::exit(2);
+        }
+    }
+}
+
+fn list() {
+    for day in days::ALL_DAYS {
+        let solver = days::solver_for_day(day).expect("BUG: missing solver");
+        println!("Day {:>2}: {}", day, solver.title());
+    }
+}
+
+/// Which days to run.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum DaySelection {
+    All,
+    Single(u8),
+}
+
+/// The parsed arguments of the `run` subcommand.
+#[derive(Debug)]
+struct RunArgs {
+    days: DaySelection,
+    /// Run only this part, or both if None.
+    part: Option<u8>,
+    input: Option<std::path::PathBuf>,
+    /// Check the answers against the registry, instead of printing them.
+    check: bool,
+    /// Benchmark the days, instead of printing the answers.
+    bench: bool,
+    /// With `bench`, the number of timed and warmup iterations, if not the default.
+    iterations: Option<usize>,
+    warmup: Option<usize>,
+    /// With `bench`, where to write the JSON report.
+    json: Option<std::path::PathBuf>,
+    /// Day-specific (name, value) options.
+    options: Vec<(String, String)>,
+}
+
+fn parse_run_args(args: &[String]) -> Result<RunArgs, String> {
+    let mut args = args.iter();
+
+    let days = match args.next().map(|s| s.as_str()) {
+        None => return Err("Missing day.".to_string()),
+        Some("all") => DaySelection::All,
+        Some(day) => {
+            let day = day
+                .parse::<u8>()
+                .map_err(|_| format!("Invalid day: {:?}", day))?;
+            if !days::ALL_DAYS.contains(&day) {
+                return Err(format!("No solver for day {}.", day));
+            }
+            DaySelection::Single(day)
+        }
+    };
+
+    let mut run_args = RunArgs {
+        days,
+        part: None,
+        input: None,
+        check: false,
+        bench: false,
+        iterations: None,
+        warmup: None,
+        json: None,
+        options: Vec::new(),
+    };
+
+    while let Some(arg) = args.next() {
+        let name = arg
+            .strip_prefix("--")
+            .ok_or_else(|| format!("Unexpected argument: {:?}", arg))?;
+        // Both `--name value` and `--name=value` are accepted.
+        let (name, inline_value) = match name.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (name, None),
+        };
+        let mut value = || -> Result<String, String> {
+            match &inline_value {
+                Some(value) => Ok(value.clone()),
+                None => args
+                    .next()
+                    .cloned()
+                    .ok_or_else(|| format!("Missing value for --{}", name)),
+            }
+        };
+
+        match name {
+            "part" => {
+                let part = value()?;
+                run_args.part = match part.as_str() {
+                    "1" => Some(1),
+                    "2" => Some(2),
+                    _ => return Err(format!("Invalid part: {:?}", part)),
+                };
+            }
+            "input" => run_args.input = Some(value()?.into()),
+            "check" if inline_value.is_none() => run_args.check = true,
+            "bench" if inline_value.is_none() => run_args.bench = true,
+            "iterations" | "warmup" => {
+                let n = value()?;
+                let n = n
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid number for --{}: {:?}", name, n))?;
+                if name == "iterations" {
+                    if n == 0 {
+                        return Err("--iterations must be at least 1.".to_string());
+                    }
+                    run_args.iterations = Some(n);
+                } else {
+                    run_args.warmup = Some(n);
+                }
+            }
+            "json" => run_args.json = Some(value()?.into()),
+            _ => {
+                // Day-specific options must always be of the form `--name=value`, since we can't
+                // tell whether they take a value or not.
+                let value = inline_value
+                    .ok_or_else(|| format!("Expected a value for --{}=<value>", name))?;
+                run_args.options.push((name.to_string(), value));
+            }
+        }
+    }
+
+    if run_args.check && run_args.input.is_some() {
+        return Err(
+            "--check uses the inputs from answers.toml, it can't be used with --input.".to_string(),
+        );
+    }
+    if run_args.bench && run_args.check {
+        return Err("--bench and --check can't be used together.".to_string());
+    }
+    let has_bench_options =
+        run_args.iterations.is_some() || run_args.warmup.is_some() || run_args.json.is_some();
+    if !run_args.bench && has_bench_options {
+        return Err("--iterations, --warmup and --json can only be used with --bench.".to_string());
+    }
+    if run_args.days == DaySelection::All {
+        if run_args.input.is_some() {
+            return Err("--input can only be used with a single day.".to_string());
+        }
+        if !run_args.options.is_empty() {
+            return Err("Day-specific options can only be used with a single day.".to_string());
+        }
+    }
+
+    Ok(run_args)
+}
+
+/// Runs the selected days. Days with a missing or malformed input are reported, without stopping
+/// the others from running.
+///
+/// Returns whether all days ran successfully (and passed the check, if enabled), or an error for
+/// invalid arguments.
+fn run(args: &RunArgs) -> Result<bool, String> {
+    let days: Vec<u8> = match args.days {
+        DaySelection::All => days::ALL_DAYS.collect(),
+        DaySelection::Single(day) => vec![day],
+    };
+
+    let registry = if args.check {
+        match answers::read_answers() {
+            Ok(registry) => Some(registry),
+            Err(err) => {
+                println!("Error: failed to read the expected answers: {}", err);
+                return Ok(false);
+            }
+        }
+    } else {
+        None
+    };
+    let mut stats = CheckStats::default();
+    let mut bench_reports = Vec::new();
+    let parts = match args.part {
+        Some(part) => vec![part],
+        None => vec![1, 2],
+    };
+
+    let mut all_ok = true;
+    for day in days {
+        let mut solver = days::solver_for_day(day).expect("BUG: missing solver");
+        for (name, value) in &args.options {
+            solver.configure(name, value)?;
+        }
+
+        if let Some(registry) = &registry {
+            all_ok &= check_day(solver.as_ref(), registry, args.part, &mut stats);
+            continue;
+        }
+
+        println!("Day {:>2}: {}", solver.day(), solver.title());
+        let input_path = match &args.input {
+            Some(path) => Ok(path.clone()),
+            None => advent_of_code::env::try_get_puzzle_input_path_for_day(day),
+        };
+
+        if args.bench {
+            let report = bench_day(
+                solver.as_ref(),
+                input_path,
+                &parts,
+                args.warmup.unwrap_or(1),
+                args.iterations.unwrap_or(10),
+            );
+            all_ok &= report.stats.is_ok();
+            bench_reports.push(report);
+            continue;
+        }
+
+        let result = input_path.map_err(|err| err.to_string()).and_then(|path| {
+            run_day(solver.as_ref(), &path, &parts, &mut |part, answer| {
+                print_answer(part, &answer)
+            })
+        });
+        if let Err(err) = result {
+            println!("  Error: {}", err);
+            all_ok = false;
+        }
+    }
+
+    if registry.is_some() {
+        println!(
+            "\nChecked {} answers: {} passed, {} failed.",
+            stats.passed + stats.failed,
+            stats.passed,
+            stats.failed
+        );
+    }
+
+    if let Some(json_path) = &args.json {
+        let report = bench::Report {
+            warmup: args.warmup.unwrap_or(1),
+            iterations: args.iterations.unwrap_or(10),
+            days: bench_reports,
+        };
+        if let Err(err) = std::fs::write(json_path, report.to_json()) {
+            println!("Error: {}", Error::io(json_path, err));
+            all_ok = false;
+        }
+    }
+
+    Ok(all_ok)
+}
+
+/// Reads and parses an input, then solves the given parts, handing each answer to `on_answer`.
+fn run_day(
+    solver: &dyn DynSolver,
+    input_path: &std::path::Path,
+    parts: &[u8],
+    on_answer: &mut dyn FnMut(u8, Answer),
+) -> Result<(), String> {
+    let text = std::fs::read_to_string(input_path)
+        .map_err(|err| Error::io(input_path, err).to_string())?;
+    // Parse errors don't know which file they come from.
+    let input = solver
+        .parse(&text)
+        .map_err(|err| format!("{}: {}", input_path.display(), err))?;
+
+    for &part in parts {
+        let answer = match part {
+            1 => solver.part1(input.as_ref()),
+            _ => solver.part2(input.as_ref()),
+        };
+        on_answer(part, answer);
+    }
+    Ok(())
+}
+
+/// Times a day's solver on its input, printing the stats of each phase.
+fn bench_day(
+    solver: &dyn DynSolver,
+    input_path: advent_of_code::error::Result<std::path::PathBuf>,
+    parts: &[u8],
+    warmup: usize,
+    iterations: usize,
+) -> bench::DayReport {
+    let (input, stats) = match input_path {
+        Ok(path) => {
+            let stats = std::fs::read_to_string(&path)
+                .map_err(|err| Error::io(&path, err).to_string())
+                .and_then(|text| {
+                    bench::bench_solver(solver, &text, parts, warmup, iterations)
+                        .map_err(|err| format!("{}: {}", path.display(), err))
+                });
+            (path, stats)
+        }
+        Err(err) => (std::path::PathBuf::new(), Err(err.to_string())),
+    };
+
+    let print_stats = |phase: &str, stats: &bench::Stats| {
+        println!(
+            "  {:<7} min {:>10.3?}  median {:>10.3?}  p95 {:>10.3?}",
+            phase, stats.min, stats.median, stats.p95
+        );
+    };
+    match &stats {
+        Ok(stats) => {
+            print_stats("Parse:", &stats.parse);
+            for (phase, part_stats) in [("Part 1:", &stats.part1), ("Part 2:", &stats.part2)] {
+                if let Some(part_stats) = part_stats {
+                    print_stats(phase, part_stats);
+                }
+            }
+        }
+        Err(err) => println!("  Error: {}", err),
+    }
+
+    bench::DayReport {
+        day: solver.day(),
+        title: solver.title(),
+        input,
+        stats,
+    }
+}
+
+/// The number of answers checked so far.
+#[derive(Default)]
+struct CheckStats {
+    passed: usize,
+    failed: usize,
+}
+
+/// Runs a day's solver on each of its inputs from the registry, and compares the answers with the
+/// expected ones. Returns whether they all matched.
+fn check_day(
+    solver: &dyn DynSolver,
+    registry: &[ExpectedAnswers],
+    part: Option<u8>,
+    stats: &mut CheckStats,
+) -> bool {
+    let mut all_ok = true;
+    let mut has_expected_answers = false;
+
+    for expected in registry.iter().filter(|e| e.day == solver.day()) {
+        // Only run the parts we know the answer to.
+        let parts: Vec<u8> = [1, 2]
+            .into_iter()
+            .filter(|&p| part.unwrap_or(p) == p && expected.part(p).is_some())
+            .collect();
+        if parts.is_empty() {
+            continue;
+        }
+        has_expected_answers = true;
+
+        println!(
+            "Day {:>2}: {} [{}]",
+            solver.day(),
+            solver.title(),
+            expected.input
+        );
+        let input_path = match advent_of_code::env::try_get_data_dir() {
+            Ok(dir) => dir.join(&expected.input),
+            Err(err) => {
+                println!("  Error: {}", err);
+                stats.failed += parts.len();
+                return false;
+            }
+        };
+
+        let mut num_checked = 0;
+        let result = run_day(solver, &input_path, &parts, &mut |part, answer| {
+            num_checked += 1;
+            let expected_answer = expected.part(part).expect("BUG: no expected answer");
+            match answers::diff_answers(expected_answer, &answer) {
+                None => {
+                    println!("  Part {}: ok", part);
+                    stats.passed += 1;
+                }
+                Some(diff) => {
+                    println!("  Part {}: FAILED, expected (-) vs actual (+):", part);
+                    for line in diff.lines() {
+                        println!("    {}", line);
+                    }
+                    stats.failed += 1;
+                    all_ok = false;
+                }
+            }
+        });
+        if let Err(err) = result {
+            println!("  Error: {}", err);
+            stats.failed += parts.len() - num_checked;
+            all_ok = false;
+        }
+    }
+
+    if !has_expected_answers {
+        println!("Day {:>2}: {}", solver.day(), solver.title());
+        println!("  No expected answers.");
+    }
+
+    all_ok
+}
+
+fn print_answer(part: u8, answer: &Answer) {
+    if answer.contains('\n') {
+        // Multi-line answers (eg text drawn with dots) are easier to read on their own lines.
+        println!("  Part {}:", part);
+        for line in answer.lines() {
+            println!("    {}", line);
+        }
+    } else {
+        println!("  Part {}: {}", part, answer);
+    }
+}
@@ -1,83 +1,119 @@
+/// The order in which the bits of a stream are laid out in its bytes.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BitOrder {
+    /// Most-significant bit first, both inside of individual bytes, and between bytes (ie this
+    /// simulates an arbitrarily long binary string where the first bit is the highest). Values are
+    /// read with their most-significant bit first.
+    #[default]
+    MsbFirst,
+    /// Least-significant bit first, inside of individual bytes, and values are read with their
+    /// least-significant bit first. This is the order of GIF's LZW codes, or of deflate.
+    LsbFirst,
+}
 
 /// A helper for reading bits from a stream of bytes.
 ///
-/// The bit order is most-significant bit first, both inside of individual bytes, and between
-/// bytes (ie this simulates an arbitrarily long binary string where the first bit is the
-/// highest).
+/// The bit order is most-significant bit first by default, see `BitOrder`.
 pub struct Bitstream<'a> {
     bytes: &'a [u8],
-    bits_left_in_next_byte: u8,
+    /// The position of the next bit to read, from the start of `bytes`.
+    pos: usize,
+    order: BitOrder,
 }
 
 impl<'a> Bitstream<'a> {
     pub fn new(bytes: &'a [u8]) -> Self {
+        Self::with_order(bytes, BitOrder::MsbFirst)
+    }
+
+    pub fn with_order(bytes: &'a [u8], order: BitOrder) -> Self {
         Self {
             bytes,
-            bits_left_in_next_byte: 8,
+            pos: 0,
+            order,
         }
     }
 
+    pub fn order(&self) -> BitOrder {
+        self.order
+    }
+
     pub fn is_empty(&self) -> bool {
-        self.bytes.is_empty()
+        self.num_remaining_bits() == 0
     }
 
     pub fn num_remaining_bits(&self) -> usize {
-        if self.bytes.is_empty() {
-            0
-        } else {
-            self.bits_left_in_next_byte as usize + (self.bytes.len() - 1) * 8
-        }
+        self.bytes.len() * 8 - self.pos
     }
 
-    /// Peaks at the next n bits, where n <= 16.
+    /// The number of bits that were read (or skipped) since the start of the stream.
+    pub fn bits_consumed(&self) -> usize {
+        self.pos
+    }
+
+    /// Moves to a bit position, counted from the start of the stream. Seeking to the end is
+    /// allowed, but not past it.
+    pub fn seek(&mut self, bit_pos: usize) {
+        assert!(
+            bit_pos <= self.bytes.len() * 8,
+            "Cannot seek past the end of the stream"
+        );
+        self.pos = bit_pos;
+    }
+
+    /// Peeks at the next n bits, where n <= 64.
     /// Returns None if there are not enough bits left.
-    pub fn peek_n_bits(&self, n: u8) -> Option<u16> {
-        assert!(n <= 16);
+    pub fn peek_bits_u64(&self, n: u8) -> Option<u64> {
+        assert!(n <= 64);
+        if n as usize > self.num_remaining_bits() {
+            return None;
+        }
 
-        let mut n = n;
-        let mut res = 0u16;
-
-        // We'll have to look at (at most) 3 bytes (eg when asked for 16 bits, and there's only 1
-        // left in the current byte).
-        for i in 0..3 {
-            let bits_left = if i == 0 { self.bits_left_in_next_byte } else { 8 };
-            // Read the byte in question, and shift it towards the most-significant bits if it's
-            // partial.
-            let this_byte = self.bytes.get(i)? << (8-bits_left);
-
-            if bits_left >= n {
-                // Read n bits, starting from the most significant bits.
-                res <<= n;
-                let this_chunk = this_byte >> (8-n);
-                res |= this_chunk as u16;
-                return Some(res);
+        let mut res = 0u64;
+        let mut pos = self.pos;
+        let mut num_read = 0;
+        // Read chunks of bits, up to the end of each byte.
+        while num_read < n {
+            let byte = self.bytes[pos / 8];
+            let offset = (pos % 8) as u8;
+            let chunk_len = u8::min(8 - offset, n - num_read);
+            match self.order {
+                BitOrder::MsbFirst => {
+                    let chunk = (byte << offset) >> (8 - chunk_len);
+                    res = (res << chunk_len) | chunk as u64;
+                }
+                BitOrder::LsbFirst => {
+                    let chunk = (byte >> offset) & low_bits_mask(chunk_len);
+                    res |= (chunk as u64) << num_read;
+                }
             }
-
-            // Read bits_left bits, starting from the most significant bits.
-            res <<= bits_left;
-            let this_chunk = this_byte >> (8-bits_left);
-            res |= this_chunk as u16;
-            n -= bits_left;
+            pos += chunk_len as usize;
+            num_read += chunk_len;
         }
+        Some(res)
+    }
 
-        panic!("BUG: should never be reached!");
+    /// Reads the next n bits, where n <= 64, and pops them off.
+    pub fn get_bits_u64(&mut self, n: u8) -> Option<u64> {
+        let res = self.peek_bits_u64(n)?;
+        self.pop_n_bits(n);
+        Some(res)
+    }
+
+    /// Peaks at the next n bits, where n <= 16.
+    /// Returns None if there are not enough bits left.
+    pub fn peek_n_bits(&self, n: u8) -> Option<u16> {
+        assert!(n <= 16);
+        self.peek_bits_u64(n).map(|bits| bits as u16)
     }
 
     /// Pops off the next n bits.
     pub fn pop_n_bits(&mut self, n: u8) {
-        let mut n = n;
-        while n > 0 {
-            if self.bits_left_in_next_byte <= n {
-                // Pop the whole byte.
-                n -= self.bits_left_in_next_byte;
-                self.bytes = &self.bytes[1..];
-                self.bits_left_in_next_byte = 8;
-            } else {
-                // Discard part of the byte.
-                self.bits_left_in_next_byte -= n;
-                break;
-            }
-        }
+        assert!(
+            n as usize <= self.num_remaining_bits(),
+            "Not enough bits left"
+        );
+        self.pos += n as usize;
     }
 
     /// Reads the next n bits, where n <= 16, and pops them off.
@@ -88,9 +124,88 @@ impl<'a> Bitstream<'a> {
     }
 }
 
+/// A helper for writing bits to a stream of bytes, the counterpart of `Bitstream`.
+///
+/// If the number of bits isn't a multiple of 8, the last byte is padded with zeroes.
+#[derive(Clone, Debug, Default)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    num_bits: usize,
+    order: BitOrder,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Self::with_order(BitOrder::MsbFirst)
+    }
+
+    pub fn with_order(order: BitOrder) -> Self {
+        Self {
+            bytes: Vec::new(),
+            num_bits: 0,
+            order,
+        }
+    }
+
+    pub fn bits_written(&self) -> usize {
+        self.num_bits
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Writes the n lowest bits of value, where n <= 64. The other bits must be zeroes.
+    pub fn put_bits_u64(&mut self, value: u64, n: u8) {
+        assert!(n <= 64);
+        assert!(
+            n == 64 || value >> n == 0,
+            "{} doesn't fit in {} bits",
+            value,
+            n
+        );
+
+        let mut num_left = n;
+        // Write chunks of bits, up to the end of each byte.
+        while num_left > 0 {
+            let offset = (self.num_bits % 8) as u8;
+            if offset == 0 {
+                self.bytes.push(0);
+            }
+            let chunk_len = u8::min(8 - offset, num_left);
+            let last_byte = self.bytes.last_mut().unwrap();
+            match self.order {
+                BitOrder::MsbFirst => {
+                    let chunk = (value >> (num_left - chunk_len)) as u8 & low_bits_mask(chunk_len);
+                    *last_byte |= chunk << (8 - offset - chunk_len);
+                }
+                BitOrder::LsbFirst => {
+                    let chunk = (value >> (n - num_left)) as u8 & low_bits_mask(chunk_len);
+                    *last_byte |= chunk << offset;
+                }
+            }
+            self.num_bits += chunk_len as usize;
+            num_left -= chunk_len;
+        }
+    }
+}
+
+/// A mask of the n lowest bits of a byte, where n <= 8.
+fn low_bits_mask(n: u8) -> u8 {
+    (((1u16 << n) - 1) & 0xFF) as u8
+}
+
 #[cfg(test)]
 mod tests {
+    use super::{BitOrder, BitWriter, Bitstream};
+
     #[test]
+    // The bit groupings follow the byte boundaries of the stream, rather than those of the result.
+    #[allow(clippy::unusual_byte_groupings)]
     fn test_bitstream() {
         let mut bits = super::Bitstream::new(&[0b1101_0010, 0b1111_1110, 0b0010_1000]);
 
@@ -123,4 +238,79 @@ mod tests {
         assert!(bits.is_empty());
         assert_eq!(bits.num_remaining_bits(), 0);
     }
+
+    #[test]
+    fn test_wide_reads_and_seeking() {
+        let bytes = [0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF, 0xF0];
+        let mut bits = Bitstream::new(&bytes);
+        assert_eq!(bits.peek_bits_u64(64), Some(0x0123_4567_89AB_CDEF));
+        assert_eq!(bits.get_bits_u64(4), Some(0x0));
+        assert_eq!(bits.get_bits_u64(64), Some(0x1234_5678_9ABC_DEFF));
+        assert_eq!(bits.bits_consumed(), 68);
+        assert_eq!(bits.get_bits_u64(5), None);
+        assert_eq!(bits.get_bits_u64(4), Some(0x0));
+        assert!(bits.is_empty());
+
+        bits.seek(36);
+        assert_eq!(bits.bits_consumed(), 36);
+        assert_eq!(bits.num_remaining_bits(), 36);
+        assert_eq!(bits.get_n_bits(12), Some(0x9AB));
+        bits.seek(0);
+        assert_eq!(bits.get_bits_u64(0), Some(0));
+        assert_eq!(bits.get_n_bits(12), Some(0x012));
+
+        let mut bits = Bitstream::with_order(&bytes, BitOrder::LsbFirst);
+        assert_eq!(bits.order(), BitOrder::LsbFirst);
+        assert_eq!(bits.peek_bits_u64(64), Some(0xEFCD_AB89_6745_2301));
+        assert_eq!(bits.get_bits_u64(1), Some(1));
+        assert_eq!(bits.get_bits_u64(3), Some(0));
+        assert_eq!(bits.get_bits_u64(8), Some(0x30));
+        assert_eq!(bits.get_bits_u64(60), Some(0xF0E_FCDA_B896_7452));
+    }
+
+    #[test]
+    fn test_bit_writer() {
+        let mut writer = BitWriter::new();
+        writer.put_bits_u64(0b110, 3);
+        writer.put_bits_u64(0b1_0010_1111, 9);
+        assert_eq!(writer.bits_written(), 12);
+        assert_eq!(writer.as_bytes(), [0b1101_0010, 0b1111_0000]);
+
+        let mut writer = BitWriter::with_order(BitOrder::LsbFirst);
+        writer.put_bits_u64(0b110, 3);
+        writer.put_bits_u64(0b1_0010_1111, 9);
+        assert_eq!(writer.into_bytes(), [0b0111_1110, 0b0000_1001]);
+
+        // Round trips, with pseudo-random widths and values.
+        for order in [BitOrder::MsbFirst, BitOrder::LsbFirst] {
+            let mut state = 12345u64;
+            let mut next = || {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1);
+                state
+            };
+            let values: Vec<(u64, u8)> = (0..1000)
+                .map(|_| {
+                    let n = (next() >> 58) as u8 + 1;
+                    (next() >> (64 - n), n)
+                })
+                .collect();
+
+            let mut writer = BitWriter::with_order(order);
+            for &(value, n) in &values {
+                writer.put_bits_u64(value, n);
+            }
+            let num_bits = writer.bits_written();
+            let bytes = writer.into_bytes();
+            assert_eq!(bytes.len(), num_bits.div_ceil(8));
+
+            let mut bits = Bitstream::with_order(&bytes, order);
+            for &(value, n) in &values {
+                assert_eq!(bits.get_bits_u64(n), Some(value));
+            }
+            assert_eq!(bits.bits_consumed(), num_bits);
+            assert!(bits.num_remaining_bits() < 8);
+        }
+    }
 }
@@ -1,5 +1,7 @@
+use crate::image::Image;
+
 /// A single RGB tuple, with 8-bit depth.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Rgb([u8; 3]);
 
 impl Rgb {
@@ -17,23 +19,234 @@ impl Rgb {
     }
 }
 
-/// A helper for converting values to colors using a linear mapping.
+/// A colormap, ie a list of colors going from the lowest values to the highest.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Colormap {
+    colors: &'static [Rgb],
+    reversed: bool,
+}
+
+impl Colormap {
+    pub const PLASMA: Self = Self::new(&PLASMA);
+    pub const VIRIDIS: Self = Self::new(&VIRIDIS);
+    pub const INFERNO: Self = Self::new(&INFERNO);
+    pub const MAGMA: Self = Self::new(&MAGMA);
+    pub const CIVIDIS: Self = Self::new(&CIVIDIS);
+    pub const GRAYSCALE: Self = Self::new(&GRAYSCALE);
+    /// A few distinct colors for categorical data, best used with `Colormap::color`.
+    pub const CATEGORICAL: Self = Self::new(&CATEGORICAL);
+
+    const NAMED: [(&'static str, Self); 7] = [
+        ("plasma", Self::PLASMA),
+        ("viridis", Self::VIRIDIS),
+        ("inferno", Self::INFERNO),
+        ("magma", Self::MAGMA),
+        ("cividis", Self::CIVIDIS),
+        ("grayscale", Self::GRAYSCALE),
+        ("categorical", Self::CATEGORICAL),
+    ];
+
+    /// A colormap with custom colors. Panics if there are none.
+    pub const fn new(colors: &'static [Rgb]) -> Self {
+        assert!(!colors.is_empty());
+        Self {
+            colors,
+            reversed: false,
+        }
+    }
+
+    /// Looks up a colormap by name, eg "viridis". A "_r" suffix gives the reversed colormap, eg
+    /// "viridis_r".
+    pub fn from_name(name: &str) -> Option<Self> {
+        let (name, reversed) = match name.strip_suffix("_r") {
+            Some(name) => (name, true),
+            None => (name, false),
+        };
+        let (_, colormap) = Self::NAMED.iter().find(|(n, _)| *n == name)?;
+        Some(if reversed {
+            colormap.reversed()
+        } else {
+            *colormap
+        })
+    }
+
+    /// The same colormap, going from the highest values to the lowest.
+    pub const fn reversed(self) -> Self {
+        Self {
+            colors: self.colors,
+            reversed: !self.reversed,
+        }
+    }
+
+    /// Maps a value in [0, 1] to the nearest color.
+    /// The mapping is saturating, ie out-of-bounds inputs will be clipped to the valid range.
+    pub fn sample(self, t: f32) -> Rgb {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let index = (t * (self.colors.len() - 1) as f32).round() as usize;
+        self.color(index)
+    }
+
+    /// The color at an index, wrapping around past the last one. Useful for categorical data.
+    pub fn color(self, index: usize) -> Rgb {
+        let index = index % self.colors.len();
+        if self.reversed {
+            self.colors[self.colors.len() - 1 - index]
+        } else {
+            self.colors[index]
+        }
+    }
+}
+
+/// A helper for converting values to colors using a linear (or logarithmic) mapping.
 ///
 /// For simplicity, the input values are f32.
 #[derive(Copy, Clone, Debug)]
 pub struct LinearColorScale {
     pub min: f32,
     pub max: f32,
+    pub colormap: Colormap,
+    /// If set, values are mapped to the colormap by `ln(1 + value - min)` rather than linearly,
+    /// which gives more contrast to the lower values.
+    pub log: bool,
 }
 
 impl LinearColorScale {
+    /// A linear scale over [min, max].
+    pub fn new(min: f32, max: f32, colormap: Colormap) -> Self {
+        Self {
+            min,
+            max,
+            colormap,
+            log: false,
+        }
+    }
+
     /// Maps an input value to a color.
     /// The mapping is saturating, ie out-of-bounds inputs will be clipped to the valid range.
     pub fn map(self, value: f32) -> Rgb {
         // Clip to the valid range.
         let value = value.clamp(self.min, self.max);
-        let index = (255.0 * (value - self.min) / (self.max - self.min)).round() as usize;
-        PLASMA[index]
+        let t = if self.log {
+            (value - self.min).ln_1p() / (self.max - self.min).ln_1p()
+        } else {
+            (value - self.min) / (self.max - self.min)
+        };
+        self.colormap.sample(t)
+    }
+}
+
+impl LinearColorScale {
+    /// Renders a colorbar `width` pixels wide: a strip with the colors from min to max, and the
+    /// min and max values written below its ends.
+    ///
+    /// The size of the text and strip grows with the width, to stay readable on large images.
+    pub fn colorbar(self, width: usize) -> Image<Rgb> {
+        const BACKGROUND: Rgb = Rgb([0, 0, 0]);
+        const FOREGROUND: Rgb = Rgb([255, 255, 255]);
+
+        let scale = (width / 200).max(1);
+        let margin = 2 * scale;
+        let strip_height = 6 * scale;
+        let tick_height = 2 * scale;
+        let label_row = margin + strip_height + tick_height + scale;
+        let mut bar = Image::filled(label_row + FONT_HEIGHT * scale + margin, width, BACKGROUND);
+        if width <= 2 * margin {
+            return bar;
+        }
+
+        // The strip, with a tick below each end.
+        let (start, end) = (margin, width - margin);
+        for col in start..end {
+            let t = (col - start) as f32 / (end - start - 1).max(1) as f32;
+            let color = self.map(self.min + t * (self.max - self.min));
+            for row in margin..margin + strip_height {
+                *bar.pixel_mut(row, col) = color;
+            }
+        }
+        for row in margin + strip_height..margin + strip_height + tick_height {
+            for col in (start..start + scale).chain(end - scale..end) {
+                *bar.pixel_mut(row, col) = FOREGROUND;
+            }
+        }
+
+        // The labels, aligned on the ticks.
+        let min_label = format_tick(self.min);
+        let max_label = format_tick(self.max);
+        let max_col = end.saturating_sub(text_width(&max_label, scale));
+        draw_text(&mut bar, label_row, start, &min_label, scale, FOREGROUND);
+        draw_text(&mut bar, label_row, max_col, &max_label, scale, FOREGROUND);
+        bar
+    }
+}
+
+/// Appends the colorbar of a color scale below an image, see `LinearColorScale::colorbar`.
+pub fn append_colorbar(image: &mut Image<Rgb>, scale: LinearColorScale) {
+    image.append_rows(&scale.colorbar(image.width));
+}
+
+/// Formats a value for a colorbar label, as compactly as possible.
+fn format_tick(value: f32) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e7 {
+        format!("{:.0}", value)
+    } else if value.abs() >= 0.01 && value.abs() < 1e7 {
+        let s = format!("{:.2}", value);
+        s.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        format!("{:.1e}", value)
+    }
+}
+
+const FONT_WIDTH: usize = 3;
+const FONT_HEIGHT: usize = 5;
+
+/// A tiny 3x5 font, for the characters used by numbers. Each row is 3 bits, the highest one being
+/// the leftmost pixel. Other characters are blank.
+fn glyph(c: char) -> [u8; FONT_HEIGHT] {
+    match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        'e' => [0b111, 0b100, 0b111, 0b100, 0b111],
+        _ => [0; FONT_HEIGHT],
+    }
+}
+
+/// The width in pixels of a text drawn by `draw_text`.
+fn text_width(text: &str, scale: usize) -> usize {
+    // One column of spacing between characters.
+    (text.chars().count() * (FONT_WIDTH + 1)).saturating_sub(1) * scale
+}
+
+/// Draws a text with its top-left corner at (row, col), each font pixel being `scale` pixels
+/// wide. The parts out of the image are clipped.
+fn draw_text(img: &mut Image<Rgb>, row: usize, col: usize, text: &str, scale: usize, color: Rgb) {
+    for (i, c) in text.chars().enumerate() {
+        let glyph_col = col + i * (FONT_WIDTH + 1) * scale;
+        for (glyph_r, bits) in glyph(c).into_iter().enumerate() {
+            for glyph_c in 0..FONT_WIDTH {
+                if bits & (1 << (FONT_WIDTH - 1 - glyph_c)) == 0 {
+                    continue;
+                }
+                for r in 0..scale {
+                    for c in 0..scale {
+                        let (r, c) = (row + glyph_r * scale + r, glyph_col + glyph_c * scale + c);
+                        if let Some(px) = img.get_mut(r, c) {
+                            *px = color;
+                        }
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -300,3 +513,264 @@ pub const PLASMA: [Rgb; 256] = [
     Rgb([240, 247, 36]),
     Rgb([240, 249, 33]),
 ];
+
+/// The viridis colormap, see `polynomial_colormap`.
+pub const VIRIDIS: [Rgb; 256] = polynomial_colormap(&[
+    [0.2777273272234177, 0.005407344544966578, 0.3340998053353061],
+    [0.1050930431085774, 1.404613529898575, 1.384590162594685],
+    [-0.3308618287255563, 0.214847559468213, 0.09509516302823659],
+    [-4.634230498983486, -5.799100973351585, -19.33244095627987],
+    [6.228269936347081, 14.17993336680509, 56.69055260068105],
+    [4.776384997670288, -13.74514537774601, -65.35303263337234],
+    [-5.435455855934631, 4.645852612178535, 26.3124352495832],
+]);
+
+/// The inferno colormap, see `polynomial_colormap`.
+pub const INFERNO: [Rgb; 256] = polynomial_colormap(&[
+    [
+        0.0002189403691192265,
+        0.001651004631001012,
+        -0.01948089843709184,
+    ],
+    [0.1065134194856116, 0.5639564367884091, 3.932712388889277],
+    [11.60249308247187, -3.972853965665698, -15.9423941062914],
+    [-41.70399613139459, 17.43639888205313, 44.35414519872813],
+    [77.162935699427, -33.40235894210092, -81.80730925738993],
+    [-71.31942824499214, 32.62606426397723, 73.20951985803202],
+    [25.13112622477341, -12.24266895238567, -23.07032500287172],
+]);
+
+/// The magma colormap, see `polynomial_colormap`.
+pub const MAGMA: [Rgb; 256] = polynomial_colormap(&[
+    [
+        -0.002136485053939582,
+        -0.000749655052795221,
+        -0.005386127855323933,
+    ],
+    [0.2516605407371642, 0.6775232436837668, 2.494026599312351],
+    [8.353717279216625, -3.577719514958484, 0.3144679030132573],
+    [-27.66873308576866, 14.26473078096533, -13.64921318813922],
+    [52.17613981234068, -27.94360607168351, 12.94416944238394],
+    [-50.76852536473588, 29.04658282127291, 4.23415299384598],
+    [18.65570506591883, -11.48977351997711, -5.601961508734096],
+]);
+
+/// The cividis colormap, linearly interpolated between 5 evenly spaced colors.
+///
+/// See https://doi.org/10.1371/journal.pone.0199239.
+pub const CIVIDIS: [Rgb; 256] = interpolated_colormap(&[
+    Rgb([0, 32, 77]),
+    Rgb([65, 77, 107]),
+    Rgb([124, 123, 120]),
+    Rgb([188, 175, 111]),
+    Rgb([255, 234, 70]),
+]);
+
+/// Black to white.
+pub const GRAYSCALE: [Rgb; 256] = interpolated_colormap(&[Rgb([0, 0, 0]), Rgb([255, 255, 255])]);
+
+/// The 10 colors of matplotlib's "tab10" palette.
+pub const CATEGORICAL: [Rgb; 10] = [
+    Rgb([31, 119, 180]),
+    Rgb([255, 127, 14]),
+    Rgb([44, 160, 44]),
+    Rgb([214, 39, 40]),
+    Rgb([148, 103, 189]),
+    Rgb([140, 86, 75]),
+    Rgb([227, 119, 194]),
+    Rgb([127, 127, 127]),
+    Rgb([188, 189, 34]),
+    Rgb([23, 190, 207]),
+];
+
+/// Builds a colormap from a degree 6 polynomial approximation: for each of r, g and b in [0, 1],
+/// the coefficients from the lowest degree to the highest.
+///
+/// The approximations of the matplotlib colormaps come from https://www.shadertoy.com/view/WlfXRN
+/// (CC0). They're within a few levels of the original tables.
+const fn polynomial_colormap(coefs: &[[f64; 3]; 7]) -> [Rgb; 256] {
+    let mut colors = [Rgb([0, 0, 0]); 256];
+    let mut i = 0;
+    while i < 256 {
+        let t = i as f64 / 255.0;
+        let mut channel = 0;
+        while channel < 3 {
+            // Horner's method.
+            let mut value = 0.0;
+            let mut degree = coefs.len();
+            while degree > 0 {
+                degree -= 1;
+                value = value * t + coefs[degree][channel];
+            }
+            // Float to int casts saturate, which clips to [0, 255].
+            colors[i].0[channel] = (value * 255.0 + 0.5) as u8;
+            channel += 1;
+        }
+        i += 1;
+    }
+    colors
+}
+
+/// Builds a colormap by linearly interpolating between evenly spaced colors.
+const fn interpolated_colormap(stops: &[Rgb]) -> [Rgb; 256] {
+    let mut colors = [Rgb([0, 0, 0]); 256];
+    let segments = stops.len() - 1;
+    let mut i = 0;
+    while i < 256 {
+        // Position in "number of segments", as a fraction i * segments / 255.
+        let pos = i * segments;
+        let (segment, frac) = if i == 255 {
+            (segments - 1, 255)
+        } else {
+            (pos / 255, pos % 255)
+        };
+        let (a, b) = (stops[segment].0, stops[segment + 1].0);
+        let mut channel = 0;
+        while channel < 3 {
+            let (a, b) = (a[channel] as usize, b[channel] as usize);
+            colors[i].0[channel] = ((a * (255 - frac) + b * frac + 127) / 255) as u8;
+            channel += 1;
+        }
+        i += 1;
+    }
+    colors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Colormap, LinearColorScale, Rgb};
+    use crate::image::Image;
+
+    #[test]
+    fn colormaps() {
+        let endpoints = |cm: Colormap| (cm.sample(0.0), cm.sample(1.0));
+        assert_eq!(
+            endpoints(Colormap::PLASMA),
+            (Rgb::new(13, 8, 135), Rgb::new(240, 249, 33))
+        );
+        assert_eq!(
+            endpoints(Colormap::GRAYSCALE),
+            (Rgb::new(0, 0, 0), Rgb::new(255, 255, 255))
+        );
+        assert_eq!(Colormap::GRAYSCALE.sample(0.5), Rgb::new(128, 128, 128));
+        assert_eq!(
+            endpoints(Colormap::CIVIDIS),
+            (Rgb::new(0, 32, 77), Rgb::new(255, 234, 70))
+        );
+        assert_eq!(Colormap::CIVIDIS.color(64), Rgb::new(65, 77, 107));
+
+        // The polynomial approximations are close to the original colormaps.
+        let close = |a: Rgb, b: Rgb| {
+            let diff = |x: u8, y: u8| (x as i32 - y as i32).abs();
+            diff(a.r(), b.r())
+                .max(diff(a.g(), b.g()))
+                .max(diff(a.b(), b.b()))
+                <= 6
+        };
+        for (cm, first, last) in [
+            (
+                Colormap::VIRIDIS,
+                Rgb::new(68, 1, 84),
+                Rgb::new(253, 231, 37),
+            ),
+            (
+                Colormap::INFERNO,
+                Rgb::new(0, 0, 4),
+                Rgb::new(252, 255, 164),
+            ),
+            (Colormap::MAGMA, Rgb::new(0, 0, 4), Rgb::new(252, 253, 191)),
+        ] {
+            let (a, b) = endpoints(cm);
+            assert!(close(a, first) && close(b, last), "{:?} {:?}", a, b);
+        }
+
+        let viridis_r = Colormap::from_name("viridis_r").unwrap();
+        assert_eq!(viridis_r, Colormap::VIRIDIS.reversed());
+        assert_eq!(viridis_r.sample(0.0), Colormap::VIRIDIS.sample(1.0));
+        assert_eq!(Colormap::from_name("jet"), None);
+
+        // Out of range values are clipped, categorical colors wrap around.
+        assert_eq!(Colormap::MAGMA.sample(-3.0), Colormap::MAGMA.sample(0.0));
+        assert_eq!(
+            Colormap::MAGMA.sample(f32::NAN),
+            Colormap::MAGMA.sample(0.0)
+        );
+        assert_eq!(
+            Colormap::CATEGORICAL.color(12),
+            Colormap::CATEGORICAL.color(2)
+        );
+    }
+
+    #[test]
+    fn color_scales() {
+        let mut scale = LinearColorScale::new(10.0, 20.0, Colormap::GRAYSCALE);
+        assert_eq!(scale.map(5.0), Rgb::new(0, 0, 0));
+        assert_eq!(scale.map(15.0), Rgb::new(128, 128, 128));
+        assert_eq!(scale.map(25.0), Rgb::new(255, 255, 255));
+
+        scale.colormap = scale.colormap.reversed();
+        assert_eq!(scale.map(12.0), Rgb::new(204, 204, 204));
+
+        // ln(1 + 2) / ln(1 + 10) = 0.458
+        scale.colormap = Colormap::GRAYSCALE;
+        scale.log = true;
+        assert_eq!(scale.map(12.0), Rgb::new(117, 117, 117));
+        assert_eq!(scale.map(20.0), Rgb::new(255, 255, 255));
+    }
+
+    #[test]
+    fn colorbar() {
+        let scale = LinearColorScale::new(-1.5, 2873.0, Colormap::VIRIDIS);
+        let bar = scale.colorbar(50);
+        // Margin, strip, tick, spacing, text, margin.
+        assert_eq!(bar.size_hw(), (2 + 6 + 2 + 1 + 5 + 2, 50));
+        assert_eq!(bar.pixel(2, 2), &scale.map(-1.5));
+        assert_eq!(bar.pixel(7, 47), &scale.map(2873.0));
+        assert_eq!(bar.pixel(1, 2), &Rgb::new(0, 0, 0));
+
+        let white = Rgb::new(255, 255, 255);
+        let text_rows = |img: &Image<Rgb>, cols: std::ops::Range<usize>| -> Vec<String> {
+            (11..16)
+                .map(|row| {
+                    cols.clone()
+                        .map(|col| {
+                            if img.pixel(row, col) == &white {
+                                '#'
+                            } else {
+                                ' '
+                            }
+                        })
+                        .collect()
+                })
+                .collect()
+        };
+        // "-1.5", left-aligned.
+        assert_eq!(
+            text_rows(&bar, 2..17),
+            [
+                "     #      ###",
+                "    ##      #  ",
+                "###  #      ###",
+                "     #        #",
+                "    ###  #  ###",
+            ]
+        );
+        // "2873", right-aligned.
+        assert_eq!(text_rows(&bar, 33..48)[0], "### ### ### ###");
+
+        let mut img = Image::filled(3, 50, Rgb::new(1, 2, 3));
+        super::append_colorbar(&mut img, scale);
+        assert_eq!(img.size_hw(), (3 + bar.height, 50));
+        assert_eq!(img.pixel(3 + 2, 2), &scale.map(-1.5));
+    }
+
+    #[test]
+    fn format_tick() {
+        assert_eq!(super::format_tick(0.0), "0");
+        assert_eq!(super::format_tick(-2873.0), "-2873");
+        assert_eq!(super::format_tick(0.5), "0.5");
+        assert_eq!(super::format_tick(1.0 / 3.0), "0.33");
+        assert_eq!(super::format_tick(1e-4), "1.0e-4");
+        assert_eq!(super::format_tick(3e9), "3.0e9");
+    }
+}
@@ -0,0 +1,57 @@
+use crate::error::{Error, Result};
+use crate::solver::{Answer, Solver};
+
+pub struct Day01;
+
+impl Solver for Day01 {
+    const DAY: u8 = 1;
+    const TITLE: &'static str = "Sonar Sweep";
+
+    type Input = Vec<i32>;
+
+    fn parse(&self, text: &str) -> Result<Self::Input> {
+        let depths: Vec<i32> = crate::iter::numbered_lines(text)
+            .map(|l| l.parse::<i32>(l.text))
+            .collect::<Result<_>>()?;
+        // Part 2 needs at least one full window.
+        if depths.len() < 3 {
+            return Err(Error::parse_at_offset(
+                text,
+                text.len(),
+                format!("Expected at least 3 depths, got {}", depths.len()),
+            ));
+        }
+        Ok(depths)
+    }
+
+    /// Each depth on its own.
+    fn part1(&self, depths: &Self::Input) -> Answer {
+        let mut iter = depths.iter().copied();
+        let mut prev_depth = iter.next().expect("Empty input");
+        let mut num_increases = 0;
+        for d in iter {
+            if d > prev_depth {
+                num_increases += 1;
+            }
+            prev_depth = d;
+        }
+
+        num_increases.to_string()
+    }
+
+    /// A sliding window.
+    fn part2(&self, depths: &Self::Input) -> Answer {
+        let mut iter = crate::iter::WindowIterator::<_, 3>::new(depths.iter().copied());
+        let mut prev_sum: i32 = iter.next().expect("Not enough inputs").iter().sum();
+        let mut num_increases = 0;
+        for win in iter {
+            let s = win.iter().sum();
+            if s > prev_sum {
+                num_increases += 1;
+            }
+            prev_sum = s;
+        }
+
+        num_increases.to_string()
+    }
+}
@@ -0,0 +1,81 @@
+use crate::error::Result;
+use crate::iter::Line;
+use crate::solver::{Answer, Solver};
+
+pub struct Day02;
+
+/// A single submarine command.
+#[derive(Copy, Clone, Debug)]
+pub enum Command {
+    Forward(i32),
+    Down(i32),
+    Up(i32),
+}
+
+impl Solver for Day02 {
+    const DAY: u8 = 2;
+    const TITLE: &'static str = "Dive!";
+
+    type Input = Vec<Command>;
+
+    fn parse(&self, text: &str) -> Result<Self::Input> {
+        crate::iter::numbered_lines(text)
+            .map(|l| parse_command(&l))
+            .collect()
+    }
+
+    fn part1(&self, commands: &Self::Input) -> Answer {
+        let mut pos = 0i32;
+        let mut depth = 0i32;
+        for cmd in commands {
+            match *cmd {
+                Command::Forward(x) => pos += x,
+                Command::Down(x) => depth += x,
+                Command::Up(x) => depth -= x,
+            }
+        }
+
+        (pos * depth).to_string()
+    }
+
+    fn part2(&self, commands: &Self::Input) -> Answer {
+        let mut pos = 0i32;
+        let mut aim = 0i32;
+        let mut depth = 0i32;
+        for cmd in commands {
+            match *cmd {
+                Command::Forward(x) => {
+                    pos += x;
+                    depth += aim * x;
+                }
+                Command::Down(x) => aim += x,
+                Command::Up(x) => aim -= x,
+            }
+        }
+
+        (pos * depth).to_string()
+    }
+}
+
+/// Parses the integer at the end of the line, after a prefix, eg "forward 123".
+/// Returns None if the prefix is missing.
+fn strip_prefix_and_parse_int(line: &Line, prefix: &str) -> Option<Result<i32>> {
+    let rest = line.text.strip_prefix(prefix)?;
+    Some(line.parse::<i32>(rest))
+}
+
+fn parse_command(line: &Line) -> Result<Command> {
+    const FORWARD: &str = "forward ";
+    const DOWN: &str = "down ";
+    const UP: &str = "up ";
+
+    if let Some(x) = strip_prefix_and_parse_int(line, FORWARD) {
+        Ok(Command::Forward(x?))
+    } else if let Some(x) = strip_prefix_and_parse_int(line, DOWN) {
+        Ok(Command::Down(x?))
+    } else if let Some(x) = strip_prefix_and_parse_int(line, UP) {
+        Ok(Command::Up(x?))
+    } else {
+        Err(line.error(format!("Invalid command: {}", line.text)))
+    }
+}
@@ -1,3 +1,6 @@
+use crate::error::{Error, Result};
+use crate::solver::{Answer, Solver};
+
 /// Counts the number of b'0' and b'1' at index `bit_index` in each line, and returns the most
 /// frequent one.
 fn compute_most_frequent_bit(lines: &[String], bit_index: usize) -> u32 {
@@ -36,18 +39,46 @@ fn u32_from_line(l: &str) -> u32 {
     res
 }
 
-fn main() {
-    let input_path = {
-        let mut p = advent_of_code::env::get_data_dir();
-        p.push("2021-12-03.txt");
-        p
-    };
-    let lines: Vec<_> = advent_of_code::iter::line_iter_from_file(&input_path).collect();
+pub struct Day03;
+
+impl Solver for Day03 {
+    const DAY: u8 = 3;
+    const TITLE: &'static str = "Binary Diagnostic";
+
+    type Input = Vec<String>;
+
+    /// Part 2 filters the numbers down to a single one, so they must be distinct.
+    fn parse(&self, text: &str) -> Result<Self::Input> {
+        let mut seen = std::collections::HashSet::new();
+        let lines: Vec<String> = crate::iter::numbered_lines(text)
+            .map(|l| {
+                if let Some(i) = l.text.find(|c| c != '0' && c != '1') {
+                    return Err(l.error_at(&l.text[i..], "Expected a binary digit"));
+                }
+                if l.text.len() != 12 {
+                    return Err(l.error(format!("Expected 12 bits, got {}", l.text.len())));
+                }
+                if !seen.insert(l.text) {
+                    return Err(l.error(format!("Duplicate number {}", l.text)));
+                }
+                Ok(l.text.to_string())
+            })
+            .collect::<Result<_>>()?;
+        if lines.is_empty() {
+            return Err(Error::parse_at_offset(
+                text,
+                text.len(),
+                "Expected at least 1 number",
+            ));
+        }
+        Ok(lines)
+    }
 
-    {
-        let mut one_counts: [usize; 12] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    /// Returns the power consumption, ie gamma * epsilon.
+    fn part1(&self, lines: &Self::Input) -> Answer {
+        let mut one_counts: [usize; 12] = [0; 12];
 
-        for l in &lines {
+        for l in lines {
             assert_eq!(l.len(), 12);
             for (i, b) in l.as_bytes().iter().enumerate() {
                 if *b == b'1' {
@@ -65,15 +96,11 @@ fn main() {
         }
 
         let epsilon = (!gamma) & 0xfff;
-        println!(
-            "gamma: {}, epsilon: {}, product: {}",
-            gamma,
-            epsilon,
-            gamma * epsilon
-        );
+        (gamma * epsilon).to_string()
     }
 
-    {
+    /// Returns the life support rating, ie oxygen rating * CO2 rating.
+    fn part2(&self, lines: &Self::Input) -> Answer {
         let mut oxygen_generator_lines = lines.clone();
         let oxygen_rating = {
             let mut i = 0;
@@ -91,7 +118,7 @@ fn main() {
             }
         };
 
-        let mut co2_scrubber_lines = lines;
+        let mut co2_scrubber_lines = lines.clone();
         let co2_rating = {
             let mut i = 0;
             loop {
@@ -107,11 +134,7 @@ fn main() {
                 i += 1;
             }
         };
-        println!(
-            "Oxygen: {}, CO2: {}, product: {}",
-            oxygen_rating,
-            co2_rating,
-            oxygen_rating * co2_rating
-        );
+
+        (oxygen_rating * co2_rating).to_string()
     }
 }
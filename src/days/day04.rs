@@ -1,4 +1,7 @@
-struct Bingo {
+use crate::error::{Error, Result};
+use crate::solver::{Answer, Solver};
+
+pub struct Bingo {
     /// The sequence of numbers that will be selected, with duplicates removed (just in case,
     /// unclear if actually needed).
     deduped_numbers: Vec<u8>,
@@ -13,6 +16,7 @@ enum BoardCell {
     Marked,
 }
 
+#[derive(Clone)]
 struct Board {
     /// Row-major matrix of numbers.
     matrix: [[BoardCell; 5]; 5],
@@ -32,6 +36,18 @@ enum MarkOutcome {
 }
 
 impl Board {
+    /// Whether all the numbers of a row or a column are drawn.
+    fn can_win(&self, drawn: &std::collections::HashSet<u8>) -> bool {
+        let is_drawn = |cell: &BoardCell| match cell {
+            BoardCell::Unmarked(x) => drawn.contains(x),
+            BoardCell::Marked => true,
+        };
+        (0..5).any(|i| {
+            (0..5).all(|j| is_drawn(&self.matrix[i][j]))
+                || (0..5).all(|j| is_drawn(&self.matrix[j][i]))
+        })
+    }
+
     fn mark(&mut self, n: u8) -> MarkOutcome {
         for (row_idx, row) in self.matrix.iter_mut().enumerate() {
             for (col_idx, col) in row.iter_mut().enumerate() {
@@ -66,15 +82,16 @@ impl Board {
     }
 }
 
-/// Parses the input lines, **assuming the empty lines have been removed**.
-fn parse_puzzle_input(mut lines: impl Iterator<Item = String>) -> Bingo {
+fn parse_puzzle_input(text: &str) -> Result<Bingo> {
+    let mut lines = crate::iter::numbered_lines(text);
+
     // First line is the drawn numbers, comma-separated.
-    let mut numbers: Vec<u8> = lines
-        .next()
-        .expect("Missing 1st line")
+    let first_line = lines.next_or_err("the drawn numbers")?;
+    let mut numbers: Vec<u8> = first_line
+        .text
         .split(',')
-        .map(|num_str| num_str.parse::<u8>().expect("Failed to parse u8"))
-        .collect();
+        .map(|num_str| first_line.parse::<u8>(num_str))
+        .collect::<Result<_>>()?;
 
     let deduped_numbers = {
         let mut seen = std::collections::HashSet::with_capacity(numbers.len());
@@ -86,21 +103,25 @@ fn parse_puzzle_input(mut lines: impl Iterator<Item = String>) -> Bingo {
     let mut boards = Vec::new();
     'board_loop: loop {
         let mut matrix = [[BoardCell::Marked; 5]; 5];
-        for matrix_row in &mut matrix {
-            let row = if let Some(r) = lines.next() {
-                r
+        for (row_idx, matrix_row) in matrix.iter_mut().enumerate() {
+            let row = if row_idx == 0 {
+                match lines.next() {
+                    Some(r) => r,
+                    None => break 'board_loop,
+                }
             } else {
-                break 'board_loop;
+                lines.next_or_err("a board row")?
             };
-            let row_nums = row.split(' ').filter_map(|maybe_num| {
-                if maybe_num.is_empty() {
-                    None
-                } else {
-                    Some(maybe_num.parse::<u8>().unwrap())
-                }
-            });
+            let row_nums: Vec<u8> = row
+                .text
+                .split_whitespace()
+                .map(|num_str| row.parse::<u8>(num_str))
+                .collect::<Result<_>>()?;
+            if row_nums.len() != 5 {
+                return Err(row.error(format!("Expected 5 numbers, got {}", row_nums.len())));
+            }
 
-            for (col_idx, n) in row_nums.enumerate() {
+            for (col_idx, n) in row_nums.into_iter().enumerate() {
                 matrix_row[col_idx] = BoardCell::Unmarked(n);
             }
         }
@@ -112,24 +133,36 @@ fn parse_puzzle_input(mut lines: impl Iterator<Item = String>) -> Bingo {
         });
     }
 
-    Bingo {
+    // The game must have a first and a last winner.
+    let drawn: std::collections::HashSet<u8> = deduped_numbers.iter().copied().collect();
+    let num_winnable = boards.iter().filter(|b| b.can_win(&drawn)).count();
+    if num_winnable < 2 {
+        return Err(Error::parse_at_offset(
+            text,
+            text.len(),
+            format!(
+                "Expected at least 2 boards which can win, got {}",
+                num_winnable
+            ),
+        ));
+    }
+
+    Ok(Bingo {
         deduped_numbers,
         boards,
-    }
+    })
 }
 
-fn main() {
-    let input_path = advent_of_code::env::get_puzzle_input_path("2021-12-04.txt");
-    let lines = advent_of_code::iter::line_iter_from_file(&input_path);
-
-    let mut bingo = parse_puzzle_input(lines);
-    let mut board_has_won = vec![false; bingo.boards.len()];
+/// Plays the full game, returning the scores of the first and last boards to win.
+fn play(bingo: &Bingo) -> (u32, u32) {
+    let mut boards = bingo.boards.clone();
+    let mut board_has_won = vec![false; boards.len()];
 
     let mut first_win_score = None;
     let mut last_win_score = None;
 
-    for n in bingo.deduped_numbers {
-        for (board, has_won) in bingo.boards.iter_mut().zip(&mut board_has_won) {
+    for &n in &bingo.deduped_numbers {
+        for (board, has_won) in boards.iter_mut().zip(&mut board_has_won) {
             if *has_won {
                 continue;
             }
@@ -147,6 +180,28 @@ fn main() {
         }
     }
 
-    println!("First winning board score: {}", first_win_score.unwrap());
-    println!("Last winning board score: {}", last_win_score.unwrap());
+    (first_win_score.unwrap(), last_win_score.unwrap())
+}
+
+pub struct Day04;
+
+impl Solver for Day04 {
+    const DAY: u8 = 4;
+    const TITLE: &'static str = "Giant Squid";
+
+    type Input = Bingo;
+
+    fn parse(&self, text: &str) -> Result<Self::Input> {
+        parse_puzzle_input(text)
+    }
+
+    /// Returns the score of the first winning board.
+    fn part1(&self, bingo: &Self::Input) -> Answer {
+        play(bingo).0.to_string()
+    }
+
+    /// Returns the score of the last winning board.
+    fn part2(&self, bingo: &Self::Input) -> Answer {
+        play(bingo).1.to_string()
+    }
 }
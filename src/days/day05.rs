@@ -0,0 +1,121 @@
+use crate::error::Result;
+use crate::image::Image;
+use crate::iter::Line as InputLine;
+use crate::solver::{Answer, Solver};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct Point {
+    x: u16,
+    y: u16,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Line {
+    a: Point,
+    b: Point,
+}
+
+impl Line {
+    fn is_axis_aligned(&self) -> bool {
+        self.a.x == self.b.x || self.a.y == self.b.y
+    }
+}
+
+/// Parses a point, eg "12,34".
+fn parse_point(l: &InputLine, text: &str) -> Result<Point> {
+    let (x, y) = l.split_once(text, ",")?;
+    Ok(Point {
+        x: l.parse::<u16>(x)?,
+        y: l.parse::<u16>(y)?,
+    })
+}
+
+fn parse_puzzle_input(text: crate::iter::Lines) -> Result<Vec<Line>> {
+    let mut lines = Vec::new();
+    for l in text {
+        let (a, b) = l.split_once(l.text, " -> ")?;
+        let line = Line {
+            a: parse_point(&l, a)?,
+            b: parse_point(&l, b)?,
+        };
+
+        // Drawing relies on lines being either axis-aligned, or at 45 degrees.
+        let dx = (line.b.x as i32 - line.a.x as i32).abs();
+        let dy = (line.b.y as i32 - line.a.y as i32).abs();
+        if !line.is_axis_aligned() && dx != dy {
+            return Err(l.error("Line is neither axis-aligned nor diagonal"));
+        }
+
+        lines.push(line);
+    }
+
+    Ok(lines)
+}
+
+fn get_max_xy(lines: &[Line]) -> Point {
+    let mut max_point = Point { x: 0, y: 0 };
+    for l in lines {
+        for p in [l.a, l.b] {
+            max_point.x = max_point.x.max(p.x);
+            max_point.y = max_point.y.max(p.y);
+        }
+    }
+    max_point
+}
+
+fn draw_line(line: &Line, img: &mut Image<u16>) {
+    let dx = (line.b.x as i16 - line.a.x as i16).signum();
+    let dy = (line.b.y as i16 - line.a.y as i16).signum();
+
+    let mut pt = line.a;
+    while pt != line.b {
+        *img.pixel_mut(pt.y as usize, pt.x as usize) += 1;
+        pt.x = (pt.x as i16 + dx).try_into().unwrap();
+        pt.y = (pt.y as i16 + dy).try_into().unwrap();
+    }
+    // Draw the final point.
+    *img.pixel_mut(pt.y as usize, pt.x as usize) += 1;
+}
+
+fn count_pixels_with_2_or_more_lines(img: &Image<u16>) -> usize {
+    img.data.iter().copied().filter(|&count| count >= 2).count()
+}
+
+/// Draws the lines onto an image, optionally skipping the diagonal ones, and returns the number of
+/// pixels where at least 2 lines overlap.
+fn count_overlaps(lines: &[Line], axis_aligned_only: bool) -> usize {
+    let max_point = get_max_xy(lines);
+    let mut img = Image::filled(max_point.y as usize + 1, max_point.x as usize + 1, 0);
+
+    for line in lines {
+        if axis_aligned_only && !line.is_axis_aligned() {
+            continue;
+        }
+        draw_line(line, &mut img);
+    }
+
+    count_pixels_with_2_or_more_lines(&img)
+}
+
+pub struct Day05;
+
+impl Solver for Day05 {
+    const DAY: u8 = 5;
+    const TITLE: &'static str = "Hydrothermal Venture";
+
+    type Input = Vec<Line>;
+
+    fn parse(&self, text: &str) -> Result<Self::Input> {
+        parse_puzzle_input(crate::iter::numbered_lines(text))
+    }
+
+    /// Consider only axis-aligned lines.
+    fn part1(&self, lines: &Self::Input) -> Answer {
+        count_overlaps(lines, true).to_string()
+    }
+
+    /// Consider all lines.
+    fn part2(&self, lines: &Self::Input) -> Answer {
+        count_overlaps(lines, false).to_string()
+    }
+}
@@ -0,0 +1,55 @@
+use crate::error::Result;
+use crate::solver::{Answer, Solver};
+
+pub struct Day06;
+
+impl Solver for Day06 {
+    const DAY: u8 = 6;
+    const TITLE: &'static str = "Lanternfish";
+
+    /// A histogram of how many fishes have d days left.
+    /// Min number of days is 0, max number is 8.
+    type Input = [usize; 9];
+
+    fn parse(&self, text: &str) -> Result<Self::Input> {
+        let input_line = crate::iter::numbered_lines(text).next_or_err("the fish timers")?;
+
+        let mut fish_day_hist = [0usize; 9];
+        for d in input_line.text.split(',') {
+            let fish = input_line.parse::<u8>(d)?;
+            if fish > 8 {
+                return Err(input_line.error_at(d, "Fish timers must be in [0, 8]"));
+            }
+            fish_day_hist[fish as usize] += 1;
+        }
+        Ok(fish_day_hist)
+    }
+
+    fn part1(&self, fish_day_hist: &Self::Input) -> Answer {
+        count_fish_after_n_days(*fish_day_hist, 80).to_string()
+    }
+
+    fn part2(&self, fish_day_hist: &Self::Input) -> Answer {
+        count_fish_after_n_days(*fish_day_hist, 256).to_string()
+    }
+}
+
+fn count_fish_after_n_days(mut fish_day_hist: [usize; 9], num_days: usize) -> usize {
+    for _day in 1..=num_days {
+        // All fish with 0 days will spawn new ones.
+        let fish_with_0_days = fish_day_hist[0];
+
+        // Reduce the number of days by one for all existing fish.
+        for d in 0..=7 {
+            fish_day_hist[d] = fish_day_hist[d + 1];
+        }
+
+        // Reset the counter for the 0-day fish.
+        fish_day_hist[6] += fish_with_0_days;
+
+        // Spawn new fish.
+        fish_day_hist[8] = fish_with_0_days;
+    }
+
+    fish_day_hist.iter().copied().sum()
+}
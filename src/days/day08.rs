@@ -1,3 +1,7 @@
+use crate::error::Result;
+use crate::iter::Line;
+use crate::solver::{Answer, Solver};
+
 /// A wire pattern is a set of active (on) wires, as a bitset with 7 bits (1 per wire).
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 struct WirePattern(u8);
@@ -16,7 +20,7 @@ impl WirePattern {
 
 /// A single line from the input: 10 unique wire patterns (1 per digit), and 4 patterns that we
 /// actually want to decode.
-struct NoteEntry {
+pub struct NoteEntry {
     unique_patterns: [WirePattern; 10],
     output_digits: [WirePattern; 4],
 }
@@ -85,61 +89,81 @@ fn decode_wire_to_segment_mapping(unique_patterns: &[WirePattern; 10]) -> Decode
     }
 }
 
-fn parse_note_entry(line: &str) -> NoteEntry {
-    let mut parts = line.split(' ');
-
-    let mut unique_patterns = [WirePattern(0); 10];
-    for upat in &mut unique_patterns {
-        let txt = parts.next().unwrap();
-        assert_ne!(txt, "|");
-        *upat = txt
-            .bytes()
-            .map(|b| 1 << (b - b'a'))
-            .fold(WirePattern(0), |acc, val: u8| WirePattern(acc.0 | val));
-    }
-
-    assert_eq!(parts.next().unwrap(), "|");
+/// Parses a wire pattern made of the letters a to g, eg "bdf".
+fn parse_wire_pattern(line: &Line, txt: &str) -> Result<WirePattern> {
+    txt.bytes()
+        .enumerate()
+        .try_fold(WirePattern(0), |acc, (i, b)| {
+            if !(b'a'..=b'g').contains(&b) {
+                return Err(line.error_at(&txt[i..], "Expected a wire in [a, g]"));
+            }
+            Ok(WirePattern(acc.0 | 1 << (b - b'a')))
+        })
+}
 
-    let mut output_digits = [WirePattern(0); 4];
-    for od in &mut output_digits {
-        let txt = parts.next().unwrap();
-        *od = txt
-            .bytes()
-            .map(|b| 1 << (b - b'a'))
-            .fold(WirePattern(0), |acc, val: u8| WirePattern(acc.0 | val));
+fn parse_note_entry(line: &Line) -> Result<NoteEntry> {
+    let (unique_txt, output_txt) = line.split_once(line.text, " | ")?;
+
+    // Parses exactly N patterns, separated by spaces.
+    fn parse_patterns<const N: usize>(line: &Line, txt: &str) -> Result<[WirePattern; N]> {
+        let patterns = txt
+            .split(' ')
+            .map(|pat| parse_wire_pattern(line, pat))
+            .collect::<Result<Vec<_>>>()?;
+        let num_patterns = patterns.len();
+        patterns.try_into().map_err(|_| {
+            line.error_at(
+                txt,
+                format!("Expected {} patterns, got {}", N, num_patterns),
+            )
+        })
     }
 
-    NoteEntry {
-        unique_patterns,
-        output_digits,
-    }
+    Ok(NoteEntry {
+        unique_patterns: parse_patterns(line, unique_txt)?,
+        output_digits: parse_patterns(line, output_txt)?,
+    })
 }
 
-fn main() {
-    let input_path = advent_of_code::env::get_puzzle_input_path("2021-12-08.txt");
-    let lines = advent_of_code::iter::line_iter_from_file(&input_path);
+pub struct Day08;
 
-    let mut count_of_1478 = 0;
-    let mut sum_of_decoded_numbers = 0;
-    for (line_idx, line) in lines.enumerate() {
-        let entry = parse_note_entry(&line);
+impl Solver for Day08 {
+    const DAY: u8 = 8;
+    const TITLE: &'static str = "Seven Segment Search";
 
-        for od in entry.output_digits {
-            let num_wires = od.0.count_ones();
-            if [2, 3, 4, 7].contains(&num_wires) {
-                count_of_1478 += 1;
+    type Input = Vec<NoteEntry>;
+
+    fn parse(&self, text: &str) -> Result<Self::Input> {
+        crate::iter::numbered_lines(text)
+            .map(|line| parse_note_entry(&line))
+            .collect()
+    }
+
+    /// Returns the number of 1/4/7/8 digits in the output digits.
+    fn part1(&self, entries: &Self::Input) -> Answer {
+        let mut count_of_1478 = 0;
+        for entry in entries {
+            for od in entry.output_digits {
+                let num_wires = od.0.count_ones();
+                if [2, 3, 4, 7].contains(&num_wires) {
+                    count_of_1478 += 1;
+                }
             }
         }
+        count_of_1478.to_string()
+    }
 
-        let decoded_wire_pats = decode_wire_to_segment_mapping(&entry.unique_patterns);
-        let mut num: u32 = 0;
-        for od in entry.output_digits {
-            num = 10 * num + decoded_wire_pats.decode(od).unwrap() as u32;
+    /// Returns the sum of the decoded numbers.
+    fn part2(&self, entries: &Self::Input) -> Answer {
+        let mut sum_of_decoded_numbers = 0;
+        for entry in entries {
+            let decoded_wire_pats = decode_wire_to_segment_mapping(&entry.unique_patterns);
+            let mut num: u32 = 0;
+            for od in entry.output_digits {
+                num = 10 * num + decoded_wire_pats.decode(od).unwrap() as u32;
+            }
+            sum_of_decoded_numbers += num;
         }
-        sum_of_decoded_numbers += num;
-        println!("Line {}, decoded number: {}", line_idx, num);
+        sum_of_decoded_numbers.to_string()
     }
-
-    println!("Part 1: num of 1/4/7/8 in output digits: {}", count_of_1478);
-    println!("Part 2: sum of decoded numbers: {}", sum_of_decoded_numbers);
 }
@@ -0,0 +1,327 @@
+use crate::animation::Format;
+use crate::colormap::{Colormap, Rgb};
+use crate::image::Image;
+use crate::recorder::{ImageRecorder, Recorder};
+use crate::solver::{Answer, Solver};
+
+#[derive(Default)]
+pub struct Day09 {
+    /// If set, an image of the basins is saved to this path, in PPM format.
+    save_basins_to: Option<std::path::PathBuf>,
+    /// If set, an animation of the basins being merged is saved to this path.
+    record_to: Option<(std::path::PathBuf, Format)>,
+}
+
+impl Solver for Day09 {
+    const DAY: u8 = 9;
+    const TITLE: &'static str = "Smoke Basin";
+
+    type Input = Image<u8>;
+
+    /// Supported options:
+    /// - `save-basins-to=<path>`: save an image of the basins found in part 2, with one color per
+    ///   basin and the 9s in black.
+    /// - `record-to=<path>`: save an animation of the basins being merged in part 2, one row per
+    ///   frame, as a GIF or an APNG depending on the extension (`.gif` or `.png`).
+    fn configure(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "save-basins-to" => {
+                self.save_basins_to = Some(value.into());
+                Ok(())
+            }
+            "record-to" => {
+                let path = std::path::PathBuf::from(value);
+                let format = Format::from_path(&path)
+                    .ok_or_else(|| format!("Expected a .gif or .png path, got {:?}", value))?;
+                self.record_to = Some((path, format));
+                Ok(())
+            }
+            _ => Err(format!("Day {} has no option named {:?}", Self::DAY, name)),
+        }
+    }
+
+    fn parse(&self, text: &str) -> crate::error::Result<Self::Input> {
+        parse_input_lines_as_image(crate::iter::numbered_lines(text))
+    }
+
+    /// Look for pixels smaller than all their neighbors.
+    fn part1(&self, img: &Self::Input) -> Answer {
+        let mut sum_of_low_point_risks = 0u32;
+
+        for row in 0..img.height {
+            for col in 0..img.width {
+                let this_pixel = *img.pixel(row, col);
+
+                // 9s are the maximum height; therefore they cannot ever be low points.
+                if this_pixel == 9 {
+                    continue;
+                }
+
+                let is_low_point = img
+                    .neighbors8(row, col)
+                    .all(|(neigh_row, neigh_col)| *img.pixel(neigh_row, neigh_col) > this_pixel);
+                if is_low_point {
+                    sum_of_low_point_risks += this_pixel as u32 + 1;
+                }
+            }
+        }
+
+        sum_of_low_point_risks.to_string()
+    }
+
+    /// We're looking for basins, ie connected components of pixels where the smoke can "flow
+    /// down" towards a low point.
+    ///
+    /// It appears that the image was generated such that basins are always separated by
+    /// a wall of 9s. This means that the only crests (pixels at the top between 2 basins)
+    /// are those 9s, which according to the instructions should not be counted.
+    /// This means that 2 adjacent pixels belong to the same basin iif they are not 9s.
+    ///
+    /// We'll compute the connected components and their sizes in a single pass using a
+    /// union find / disjoint set data structure.
+    fn part2(&self, img: &Self::Input) -> Answer {
+        let mut recorder = self.record_to.as_ref().map(|_| {
+            let mut recorder = ImageRecorder::new(basin_color, 600);
+            recorder.scale = 6;
+            recorder
+        });
+
+        let mut union_find = find_basins(img, &mut recorder);
+
+        if let (Some(recorder), Some((path, format))) = (recorder, &self.record_to) {
+            let frame_delay = std::time::Duration::from_millis(50);
+            let num_frames = recorder
+                .save(path, *format, frame_delay)
+                .expect("Failed to save animation");
+            println!("Saved an animation of {} frames to {:?}!", num_frames, path);
+        }
+
+        if let Some(path) = &self.save_basins_to {
+            save_basins_image(img, &mut union_find, path);
+        }
+
+        // Iterate over all the sets, find the roots, and get their tree sizes, and keep only the
+        // top 3.
+        let mut largest_basins = [0u32; 3];
+        for set in union_find.sets {
+            if set.parent.is_some() {
+                // Skip internal tree nodes.
+                continue;
+            }
+            let s = set.size_if_root;
+            if s >= largest_basins[0] {
+                largest_basins[2] = largest_basins[1];
+                largest_basins[1] = largest_basins[0];
+                largest_basins[0] = s;
+            } else if s >= largest_basins[1] {
+                largest_basins[2] = largest_basins[1];
+                largest_basins[1] = s;
+            } else if s >= largest_basins[2] {
+                largest_basins[2] = s;
+            }
+        }
+
+        largest_basins.into_iter().product::<u32>().to_string()
+    }
+}
+
+/// Computes the basins, as a union find where pixel (row, col) is the set row * width + col.
+///
+/// After each row, records the root of each pixel's basin so far, or None for the 9s and the
+/// pixels which weren't reached yet.
+fn find_basins(img: &Image<u8>, recorder: &mut impl Recorder<Option<SetId>>) -> UnionFind {
+    // We'll initially create one set per pixel: each pixel is its own standalone basin.
+    let mut union_find = UnionFind::with_size(img.height * img.width);
+    let pixel_id = |row: usize, col: usize| -> SetId { (row * img.width + col) as SetId };
+
+    for row in 0..img.height {
+        for col in 0..img.width {
+            if *img.pixel(row, col) == 9 {
+                continue;
+            }
+
+            // Merge this pixel's basin with its neighbors to the left and up (future loop
+            // iterations will take care of the neighbors to the right and down).
+            if row > 0 && *img.pixel(row - 1, col) != 9 {
+                union_find.merge(pixel_id(row, col), pixel_id(row - 1, col));
+            }
+            if col > 0 && *img.pixel(row, col - 1) != 9 {
+                union_find.merge(pixel_id(row, col), pixel_id(row, col - 1));
+            }
+        }
+
+        if recorder.is_recording() {
+            let roots = (0..img.len())
+                .map(|i| {
+                    let reached = i < (row + 1) * img.width && img.data[i] != 9;
+                    reached.then(|| union_find.get_root_and_compress_path(i as SetId))
+                })
+                .collect();
+            recorder.record(&Image {
+                height: img.height,
+                width: img.width,
+                data: roots,
+            });
+        }
+    }
+
+    union_find
+}
+
+/// Basins are colored after their root, so that they keep their color when smaller ones merge
+/// into them.
+fn basin_color(root: &Option<SetId>) -> Rgb {
+    match root {
+        Some(root) => Colormap::CATEGORICAL.color(*root as usize),
+        None => Rgb::new(0, 0, 0),
+    }
+}
+
+/// Saves an image with one categorical color per basin, and the 9s in black.
+fn save_basins_image(img: &Image<u8>, basins: &mut UnionFind, path: &std::path::Path) {
+    // Number the basins in the order in which they're first seen.
+    let mut basin_numbers = std::collections::HashMap::new();
+    let mut colors = Vec::with_capacity(img.len());
+    for (i, &height) in img.data.iter().enumerate() {
+        colors.push(if height == 9 {
+            Rgb::new(0, 0, 0)
+        } else {
+            let root = basins.get_root_and_compress_path(i as SetId);
+            let num_basins = basin_numbers.len();
+            Colormap::CATEGORICAL.color(*basin_numbers.entry(root).or_insert(num_basins))
+        });
+    }
+
+    let basins_img = Image {
+        height: img.height,
+        width: img.width,
+        data: colors,
+    };
+    crate::netpbm::save_image_as_ppm(&basins_img, path).expect("Failed to save image");
+}
+
+fn parse_input_lines_as_image(lines: crate::iter::Lines) -> crate::error::Result<Image<u8>> {
+    let mut width = 0;
+    let mut height = 0;
+    let mut data = Vec::new();
+
+    for line in lines {
+        height += 1;
+        if width == 0 {
+            width = line.text.len();
+        }
+        if line.text.len() != width {
+            return Err(line.error(format!(
+                "Expected {} columns, got {}",
+                width,
+                line.text.len()
+            )));
+        }
+
+        data.extend(line.parse_digits()?);
+    }
+
+    assert_eq!(data.len(), height * width);
+    Ok(Image {
+        height,
+        width,
+        data,
+    })
+}
+
+/// A data structure for keeping track of various sets / subsets, as trees.
+/// https://en.wikipedia.org/wiki/Union_find
+///
+/// Each item is created as a single-element `Set`. Sets can be merged by making them part of the
+/// same tree. This tree structure is defined using the `parent` attribute of each `Set`. Within a
+/// tree, only the root has no parent.
+struct UnionFind {
+    /// The various disjoint sets of which we're keeping track.
+    sets: Vec<Set>,
+}
+
+/// An index representing a set in the UnionFind's array of sets.
+type SetId = u32;
+
+/// Information about a set.
+#[derive(Clone, Debug)]
+struct Set {
+    /// The parent set that contains this one, or None if this set is a root.
+    parent: Option<SetId>,
+    /// When this set is a root (no parent), contains the total size of this set and its children.
+    /// For non-root sets, ignore this value, it is not representative of anything.
+    size_if_root: u32,
+}
+
+impl UnionFind {
+    fn with_size(size: usize) -> Self {
+        UnionFind {
+            sets: vec![
+                Set {
+                    parent: None,
+                    size_if_root: 1
+                };
+                size
+            ],
+        }
+    }
+
+    /// Returns the corresponding set.
+    fn get(&self, s: SetId) -> &Set {
+        &self.sets[s as usize]
+    }
+
+    /// Returns the corresponding set for mutation.
+    fn get_mut(&mut self, s: SetId) -> &mut Set {
+        &mut self.sets[s as usize]
+    }
+
+    /// Merges 2 sets.
+    fn merge(&mut self, a: SetId, b: SetId) {
+        let root_a = self.get_root_and_compress_path(a);
+        let root_b = self.get_root_and_compress_path(b);
+
+        if root_a == root_b {
+            // Already part of the same set.
+            return;
+        }
+
+        // To merge the 2 trees, make root_a the parent of root_b.
+        // To achieve an excellent runtime complexity, we need to make sure that the size / depth
+        // of the resulting tree doesn't grow too much. This is achieved by simply picking as new
+        // root the set with the largest size. So if root_b is actually the largest, swap a and b.
+        let (new_root, new_child) =
+            if self.get(root_a).size_if_root >= self.get(root_b).size_if_root {
+                (root_a, root_b)
+            } else {
+                (root_b, root_a)
+            };
+
+        let additional_items = self.get(new_child).size_if_root;
+        self.get_mut(new_root).size_if_root += additional_items;
+        self.get_mut(new_child).parent = Some(new_root);
+    }
+
+    /// Returns the root set for `s`.
+    ///
+    /// Performs path-compression: all sets on the way from `s` to the root are re-parented to have
+    /// the root as immediate parent (this makes the tree very flat, and helps achieve the
+    /// ridiculously good complexity bounds that union-find data structures have).
+    fn get_root_and_compress_path(&mut self, s: SetId) -> SetId {
+        // First, go up to the root.
+        let mut current = s;
+        while let Some(parent) = self.get(current).parent {
+            current = parent;
+        }
+        let root = current;
+
+        // Next, re-parent all the nodes on the way, starting from `s` again.
+        current = s;
+        while let Some(parent) = self.get(current).parent {
+            self.get_mut(current).parent = Some(root);
+            current = parent;
+        }
+
+        root
+    }
+}
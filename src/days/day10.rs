@@ -0,0 +1,131 @@
+use crate::error::{Error, Result};
+use crate::iter::Line;
+use crate::solver::{Answer, Solver};
+
+pub struct Day10;
+
+impl Solver for Day10 {
+    const DAY: u8 = 10;
+    const TITLE: &'static str = "Syntax Scoring";
+
+    type Input = Vec<ParseResult>;
+
+    fn parse(&self, text: &str) -> Result<Self::Input> {
+        let lines: Vec<ParseResult> = crate::iter::numbered_lines(text)
+            .map(|line| parse_line(&line))
+            .collect::<Result<_>>()?;
+        // Part 2 needs a middle score.
+        let num_incomplete = lines
+            .iter()
+            .filter(|l| matches!(l, ParseResult::Incomplete(_)))
+            .count();
+        if num_incomplete % 2 == 0 {
+            return Err(Error::parse_at_offset(
+                text,
+                text.len(),
+                format!(
+                    "Expected an odd number of incomplete lines, got {}",
+                    num_incomplete
+                ),
+            ));
+        }
+        Ok(lines)
+    }
+
+    /// Returns the total syntax error score.
+    fn part1(&self, lines: &Self::Input) -> Answer {
+        let mut total_syntax_error_score = 0u32;
+        for line in lines {
+            if let ParseResult::Corrupted(c) = line {
+                total_syntax_error_score += error_score_from_closer(*c);
+            }
+        }
+        total_syntax_error_score.to_string()
+    }
+
+    /// Returns the middle auto-complete score.
+    fn part2(&self, lines: &Self::Input) -> Answer {
+        let mut completion_scores = Vec::new();
+        for line in lines {
+            if let ParseResult::Incomplete(openers) = line {
+                completion_scores.push(compute_autocomplete_score(openers));
+            }
+        }
+
+        assert!(completion_scores.len() % 2 == 1);
+        let middle_idx = completion_scores.len() / 2;
+        completion_scores.select_nth_unstable(middle_idx);
+        completion_scores[middle_idx].to_string()
+    }
+}
+
+fn parse_line(line: &Line) -> Result<ParseResult> {
+    // A stack of opening delimiters.
+    let mut opening_chars = Vec::new();
+
+    for (i, c) in line.text.char_indices() {
+        match c {
+            '(' | '[' | '{' | '<' => opening_chars.push(c),
+            ')' | ']' | '}' | '>' => {
+                if let Some(opener) = opening_chars.pop() {
+                    if opener != opener_from_closer(c) {
+                        return Ok(ParseResult::Corrupted(c));
+                    }
+                } else {
+                    // We read a closing delimiter, but there was no opener.
+                    return Ok(ParseResult::Corrupted(c));
+                }
+            }
+            _ => return Err(line.error_at(&line.text[i..], format!("Invalid char: {:?}", c))),
+        }
+    }
+
+    if opening_chars.is_empty() {
+        Ok(ParseResult::Ok)
+    } else {
+        Ok(ParseResult::Incomplete(opening_chars))
+    }
+}
+
+pub enum ParseResult {
+    Ok,
+    /// Corrupted line. The char is the first unexpected closing character.
+    Corrupted(char),
+    /// Incomplete line. Vec contains the sequence of opening chars that need closing.
+    Incomplete(Vec<char>),
+}
+
+fn opener_from_closer(closer: char) -> char {
+    match closer {
+        ')' => '(',
+        ']' => '[',
+        '}' => '{',
+        '>' => '<',
+        _ => panic!("Invalid char: {}", closer),
+    }
+}
+
+fn error_score_from_closer(closer: char) -> u32 {
+    match closer {
+        ')' => 3,
+        ']' => 57,
+        '}' => 1197,
+        '>' => 25137,
+        _ => panic!("Invalid char: {}", closer),
+    }
+}
+
+fn compute_autocomplete_score(openers: &[char]) -> u64 {
+    let mut score = 0;
+    for c in openers.iter().rev() {
+        score *= 5;
+        score += match c {
+            '(' => 1,
+            '[' => 2,
+            '{' => 3,
+            '<' => 4,
+            _ => panic!("Invalid char: {}", c),
+        };
+    }
+    score
+}
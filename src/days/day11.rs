@@ -0,0 +1,199 @@
+use crate::animation::Format;
+use crate::colormap::{Colormap, Rgb};
+use crate::image::Image;
+use crate::recorder::{ImageRecorder, Recorder};
+use crate::solver::{Answer, Solver};
+use crate::terminal::{frame_delay_from_fps, LiveView};
+
+#[derive(Default)]
+pub struct Day11 {
+    /// If set, an animation of the part 2 simulation is saved to this path.
+    record_to: Option<(std::path::PathBuf, Format)>,
+    /// If set, the part 2 simulation is drawn in the terminal, with this delay between steps.
+    watch: Option<std::time::Duration>,
+}
+
+impl Solver for Day11 {
+    const DAY: u8 = 11;
+    const TITLE: &'static str = "Dumbo Octopus";
+
+    type Input = Image<ChargeLevel>;
+
+    /// Supported options:
+    /// - `record-to=<path>`: save an animation of the part 2 simulation, as a GIF or an APNG
+    ///   depending on the extension (`.gif` or `.png`). Flashing octopuses are white.
+    /// - `watch=<fps>`: draw the part 2 simulation in the terminal, at this many steps per second.
+    fn configure(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "record-to" => {
+                let path = std::path::PathBuf::from(value);
+                let format = Format::from_path(&path)
+                    .ok_or_else(|| format!("Expected a .gif or .png path, got {:?}", value))?;
+                self.record_to = Some((path, format));
+                Ok(())
+            }
+            "watch" => {
+                self.watch = Some(frame_delay_from_fps(value)?);
+                Ok(())
+            }
+            _ => Err(format!("Day {} has no option named {:?}", Self::DAY, name)),
+        }
+    }
+
+    fn parse(&self, text: &str) -> crate::error::Result<Self::Input> {
+        parse_input_image(crate::iter::numbered_lines(text))
+    }
+
+    /// Returns the number of flashes after 100 steps.
+    fn part1(&self, img: &Self::Input) -> Answer {
+        simulate_n_steps(img.clone(), 100).to_string()
+    }
+
+    /// Returns the first step during which all octopuses flash simultaneously.
+    fn part2(&self, img: &Self::Input) -> Answer {
+        let recorder = self.record_to.as_ref().map(|_| {
+            let mut recorder = ImageRecorder::new(charge_level_color, 600);
+            recorder.scale = 20;
+            recorder
+        });
+
+        let watcher = self
+            .watch
+            .map(|delay| LiveView::new(std::io::stdout(), charge_level_color, delay));
+
+        let mut recorders = (recorder, watcher);
+        let num_steps = find_synchronized_flashing_step(img.clone(), &mut recorders);
+        let (recorder, _) = recorders;
+
+        if let (Some(recorder), Some((path, format))) = (recorder, &self.record_to) {
+            let frame_delay = std::time::Duration::from_millis(100);
+            let num_frames = recorder
+                .save(path, *format, frame_delay)
+                .expect("Failed to save animation");
+            println!("Saved an animation of {} frames to {:?}!", num_frames, path);
+        }
+
+        num_steps.to_string()
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct ChargeLevel(u8);
+
+impl ChargeLevel {
+    fn increment(&mut self) -> Effect {
+        self.0 = self.0.saturating_add(1);
+        if self.0 == 10 {
+            Effect::Flash
+        } else {
+            Effect::None
+        }
+    }
+
+    fn reset_if_flashed(&mut self) {
+        if self.0 > 9 {
+            self.0 = 0;
+        }
+    }
+}
+
+/// Flashing octopuses are white, the others get brighter as they charge.
+fn charge_level_color(charge_level: &ChargeLevel) -> Rgb {
+    match charge_level.0 {
+        0 => Rgb::new(255, 255, 255),
+        level => Colormap::INFERNO.sample(level as f32 / 12.0),
+    }
+}
+
+enum Effect {
+    Flash,
+    None,
+}
+
+/// Returns the number of flashes after n steps have occurred.
+fn simulate_n_steps(mut img: Image<ChargeLevel>, n: usize) -> usize {
+    let mut scratch_buffer = Vec::new();
+    (0..n).map(|_| step(&mut img, &mut scratch_buffer)).sum()
+}
+
+/// Returns the number of steps before all octopuses flash simultaneously.
+fn find_synchronized_flashing_step(
+    mut img: Image<ChargeLevel>,
+    recorder: &mut impl Recorder<ChargeLevel>,
+) -> usize {
+    let mut scratch_buffer = Vec::new();
+    for n in 1.. {
+        let flashes = step(&mut img, &mut scratch_buffer);
+        recorder.record(&img);
+        if flashes == img.len() {
+            return n;
+        }
+    }
+    unreachable!()
+}
+
+/// Simulates a single step, returning the number of flashes.
+///
+/// `scratch_buffer` is an implementation detail; passing the same Vec when calling step again and
+/// again saves on allocations (the buffer is cleared internally).
+fn step(img: &mut Image<ChargeLevel>, scratch_buffer: &mut Vec<(usize, usize)>) -> usize {
+    let to_process = scratch_buffer;
+    to_process.clear();
+
+    // First pass: all octopuses charge by 1 on their own.
+    for row in 0..img.height {
+        for col in 0..img.width {
+            match img.pixel_mut(row, col).increment() {
+                Effect::None => {}
+                Effect::Flash => to_process.push((row, col)),
+            }
+        }
+    }
+
+    // Now, propagate all flashing effects.
+    let mut total_flashes = 0usize;
+    while let Some((row, col)) = to_process.pop() {
+        total_flashes += 1;
+
+        for (nrow, ncol) in img.neighbors8(row, col) {
+            match img.pixel_mut(nrow, ncol).increment() {
+                Effect::None => {}
+                Effect::Flash => to_process.push((nrow, ncol)),
+            }
+        }
+    }
+
+    // Finally, reset all the octopuses that flashed.
+    for charge_level in &mut img.data {
+        charge_level.reset_if_flashed();
+    }
+
+    total_flashes
+}
+
+fn parse_input_image(lines: crate::iter::Lines) -> crate::error::Result<Image<ChargeLevel>> {
+    let mut height = 0;
+    let mut width = 0;
+    let mut data = Vec::new();
+    for line in lines {
+        height += 1;
+        if width == 0 {
+            width = line.text.len();
+        }
+        if width != line.text.len() {
+            return Err(line.error(format!(
+                "Expected {} columns, got {}",
+                width,
+                line.text.len()
+            )));
+        }
+
+        data.extend(line.parse_digits()?.into_iter().map(ChargeLevel));
+    }
+
+    Ok(Image {
+        height,
+        width,
+        data,
+    })
+}
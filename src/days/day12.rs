@@ -1,22 +1,27 @@
-fn main() {
-    for test_file in [
-        "2021-12-12.sample_01.txt",
-        "2021-12-12.sample_02.txt",
-        "2021-12-12.sample_03.txt",
-        "2021-12-12.txt",
-    ] {
-        println!("---------------- {} ----------------", test_file);
-        let input_path = advent_of_code::env::get_puzzle_input_path(test_file);
-        let lines = advent_of_code::iter::line_iter_from_file(&input_path);
-        let graph = parse_cave_graph(lines);
-
-        let num_paths_no_revisits =
-            count_all_paths_from_start_to_end(&graph, false /* no revisits */);
-        println!("Part 1: number of paths: {}", num_paths_no_revisits);
-
-        let num_paths_with_revisits =
-            count_all_paths_from_start_to_end(&graph, true /* allow 1 revisit */);
-        println!("Part 2: number of paths: {}", num_paths_with_revisits);
+use crate::error::Result;
+use crate::solver::{Answer, Solver};
+
+pub struct Day12;
+
+impl Solver for Day12 {
+    const DAY: u8 = 12;
+    const TITLE: &'static str = "Passage Pathing";
+
+    type Input = CaveGraph;
+
+    fn parse(&self, text: &str) -> Result<Self::Input> {
+        parse_cave_graph(crate::iter::numbered_lines(text))
+    }
+
+    /// Returns the number of paths that visit small caves at most once.
+    fn part1(&self, graph: &Self::Input) -> Answer {
+        count_all_paths_from_start_to_end(graph, false /* no revisits */).to_string()
+    }
+
+    /// Returns the number of paths that visit a single small cave twice, and the others at most
+    /// once.
+    fn part2(&self, graph: &Self::Input) -> Answer {
+        count_all_paths_from_start_to_end(graph, true /* allow 1 revisit */).to_string()
     }
 }
 
@@ -33,7 +38,7 @@ struct CaveId(usize);
 
 /// Representation of the graph of caves. Keeps track of which caves are connect to which,
 /// and their sizes.
-struct CaveGraph {
+pub struct CaveGraph {
     /// For each cave, its size.
     sizes: Vec<CaveSize>,
     /// For each cave, the list of its neighbors.
@@ -96,7 +101,7 @@ fn count_all_paths_from_start_to_end(graph: &CaveGraph, allow_revisits: bool) ->
     count_paths_depth_first(graph, START, allow_revisits, &mut times_visited)
 }
 
-fn parse_cave_graph(lines: impl Iterator<Item = String>) -> CaveGraph {
+fn parse_cave_graph(lines: crate::iter::Lines) -> Result<CaveGraph> {
     let mut name_to_id = std::collections::HashMap::new();
 
     // Create the start and end caves.
@@ -106,9 +111,13 @@ fn parse_cave_graph(lines: impl Iterator<Item = String>) -> CaveGraph {
     let mut neighbors = vec![Vec::new(), Vec::new()];
 
     for line in lines {
-        let mut parts = line.split('-');
-        let names = [parts.next().unwrap(), parts.next().unwrap()];
-        assert_eq!(None, parts.next());
+        let (a, b) = line.split_once(line.text, "-")?;
+        let names = [a, b];
+        for name in names {
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphabetic()) {
+                return Err(line.error_at(name, format!("Invalid cave name: {:?}", name)));
+            }
+        }
 
         // Assign IDs if needed, taking care to resize `sizes` and `neighbors` appropriately.
         let mut ids = [CaveId(0), CaveId(0)];
@@ -126,14 +135,14 @@ fn parse_cave_graph(lines: impl Iterator<Item = String>) -> CaveGraph {
         neighbors[ids[1].0].push(ids[0]);
 
         if sizes[ids[0].0] == CaveSize::Large && sizes[ids[1].0] == CaveSize::Large {
-            panic!(
+            return Err(line.error(format!(
                 "Invalid graph, it contains infinitely many paths (2 connected large caves): {} and {}",
                 names[0], names[1]
-            );
+            )));
         }
     }
 
-    CaveGraph { sizes, neighbors }
+    Ok(CaveGraph { sizes, neighbors })
 }
 
 fn determine_cave_size(name: &str) -> CaveSize {
@@ -0,0 +1,216 @@
+use crate::error::{Error, Result};
+use crate::image::SparseGrid;
+use crate::solver::{Answer, Solver};
+
+pub struct Day13;
+
+impl Solver for Day13 {
+    const DAY: u8 = 13;
+    const TITLE: &'static str = "Transparent Origami";
+
+    type Input = (Dots, Vec<Fold>);
+
+    fn parse(&self, text: &str) -> Result<Self::Input> {
+        parse_puzzle_input(text)
+    }
+
+    /// Returns the number of dots after the first fold.
+    fn part1(&self, (dots, instructions): &Self::Input) -> Answer {
+        fold_paper(dots, instructions[0]).len().to_string()
+    }
+
+    /// Returns the letters formed by the dots after all the folds, or the dots drawn as text if
+    /// they aren't letters.
+    fn part2(&self, (dots, instructions): &Self::Input) -> Answer {
+        let mut final_dots = dots.clone();
+        for fold in instructions {
+            final_dots = fold_paper(&final_dots, *fold);
+        }
+        recognize_letters(&final_dots).unwrap_or_else(|| draw_dots(&final_dots))
+    }
+}
+
+/// The dots on the paper, indexed by (row, col), ie (y, x).
+pub type Dots = SparseGrid<bool>;
+
+/// A fold instruction.
+#[derive(Copy, Clone, Debug)]
+pub enum Fold {
+    AlongX(isize),
+    AlongY(isize),
+}
+
+fn fold_paper(dots: &Dots, fold: Fold) -> Dots {
+    let mut result = SparseGrid::new(false);
+
+    for (row, col, _) in dots.iter() {
+        match fold {
+            Fold::AlongX(fold) if col > fold => result.insert(row, fold - (col - fold), true),
+            Fold::AlongY(fold) if row > fold => result.insert(fold - (row - fold), col, true),
+            _ => result.insert(row, col, true),
+        };
+    }
+
+    result
+}
+
+/// Draws the dots as a multi-line string, with '#' for dots.
+fn draw_dots(dots: &Dots) -> String {
+    let img = dots.to_image();
+
+    // Start from (0, 0) rather than from the bounding box, and leave out trailing spaces.
+    (0..img.rows().end)
+        .map(|row| {
+            let line: String = (0..img.cols().end)
+                .map(|col| match img.get(row, col) {
+                    Some(true) => '#',
+                    _ => ' ',
+                })
+                .collect();
+            line.trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The capital letters drawn by AoC puzzles, 4 dots wide and 6 dots tall.
+#[rustfmt::skip]
+const LETTERS: [(char, [&str; 6]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// Reads the dots as a line of `LETTERS`, starting from (0, 0) with 1 empty column between
+/// letters. Returns `None` if some of the dots aren't part of a known letter.
+fn recognize_letters(dots: &Dots) -> Option<String> {
+    let (rows, cols) = dots.bbox();
+    if dots.is_empty() || rows.start < 0 || rows.end > 6 || cols.start < 0 {
+        return None;
+    }
+
+    let num_letters = (cols.end + 4) / 5;
+    let text: String = (0..num_letters)
+        .map(|i| {
+            let is_match = |glyph: &[&str; 6]| {
+                glyph.iter().zip(0..).all(|(line, row)| {
+                    (line.chars().zip(5 * i..)).all(|(c, col)| (c == '#') == *dots.get(row, col))
+                })
+            };
+            LETTERS
+                .iter()
+                .find(|(_, glyph)| is_match(glyph))
+                .map(|&(letter, _)| letter)
+        })
+        .collect::<Option<_>>()?;
+
+    // Dots between the letters wouldn't have been looked at.
+    let num_letter_dots: usize = text
+        .chars()
+        .flat_map(|letter| LETTERS.iter().find(|&&(l, _)| l == letter))
+        .map(|(_, glyph)| glyph.concat().matches('#').count())
+        .sum();
+    (num_letter_dots == dots.len()).then_some(text)
+}
+
+/// Parses the initial pattern of dots, and the list of fold instructions.
+fn parse_puzzle_input(text: &str) -> Result<(Dots, Vec<Fold>)> {
+    const PREFIX: &str = "fold along ";
+
+    // The dots come first, then the fold instructions.
+    let mut dots = SparseGrid::new(false);
+    let mut instructions = Vec::new();
+    for line in crate::iter::numbered_lines(text) {
+        if let Some(rest) = line.text.strip_prefix(PREFIX) {
+            let (axis, val) = line.split_once(rest, "=")?;
+            let val = line.parse::<u16>(val)? as isize;
+            match axis {
+                "x" => instructions.push(Fold::AlongX(val)),
+                "y" => instructions.push(Fold::AlongY(val)),
+                _ => return Err(line.error_at(axis, format!("Invalid fold axis: {:?}", axis))),
+            }
+        } else if instructions.is_empty() {
+            let (x, y) = line.split_once(line.text, ",")?;
+            let x = line.parse::<u16>(x)?;
+            let y = line.parse::<u16>(y)?;
+            dots.insert(y as isize, x as isize, true);
+        } else {
+            return Err(line.error(format!("Expected {:?}", PREFIX)));
+        }
+    }
+
+    if instructions.is_empty() {
+        return Err(Error::parse_at_offset(
+            text,
+            text.len(),
+            "Missing fold instructions",
+        ));
+    }
+
+    Ok((dots, instructions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Places the dots of the letters on a line, the way AoC draws them.
+    fn draw_letters(letters: &str) -> Dots {
+        let mut dots = SparseGrid::new(false);
+        for (i, letter) in letters.chars().enumerate() {
+            let (_, glyph) = LETTERS.iter().find(|&&(l, _)| l == letter).unwrap();
+            for (row, line) in glyph.iter().enumerate() {
+                for (col, c) in line.chars().enumerate() {
+                    if c == '#' {
+                        dots.insert(row as isize, (5 * i + col) as isize, true);
+                    }
+                }
+            }
+        }
+        dots
+    }
+
+    #[test]
+    fn test_recognize_letters() {
+        let alphabet: String = LETTERS.iter().map(|&(letter, _)| letter).collect();
+        assert_eq!(recognize_letters(&draw_letters(&alphabet)), Some(alphabet));
+        assert_eq!(
+            recognize_letters(&draw_letters("LJ")),
+            Some("LJ".to_string())
+        );
+
+        // A dot between letters, or below them.
+        let mut dots = draw_letters("CPJBERUL");
+        dots.insert(2, 9, true);
+        assert_eq!(recognize_letters(&dots), None);
+        let mut dots = draw_letters("HI");
+        dots.insert(6, 0, true);
+        assert_eq!(recognize_letters(&dots), None);
+
+        // The sample's square isn't a letter.
+        let (dots, folds) = parse_puzzle_input(
+            "6,10\n0,14\n9,10\n0,3\n10,4\n4,11\n6,0\n6,12\n4,1\n0,13\n10,12\n3,4\n3,0\n\
+             8,4\n1,10\n2,14\n8,10\n9,0\n\nfold along y=7\nfold along x=5\n",
+        )
+        .unwrap();
+        let dots = folds
+            .iter()
+            .fold(dots, |dots, &fold| fold_paper(&dots, fold));
+        assert_eq!(recognize_letters(&dots), None);
+        assert_eq!(draw_dots(&dots), "#####\n#   #\n#   #\n#   #\n#####");
+    }
+}
@@ -1,45 +1,50 @@
+use crate::error::Result;
+use crate::solver::{Answer, Solver};
 use std::collections::HashMap;
 
-fn main() {
-    for test_file in ["2021-12-14.sample.txt", "2021-12-14.txt"] {
-        println!("--------------- {} ---------------", test_file);
-        let input_path = advent_of_code::env::get_puzzle_input_path(test_file);
-        let (template, rules) =
-            parse_puzzle_input(advent_of_code::iter::line_iter_from_file(&input_path));
+pub struct Day14;
 
+impl Solver for Day14 {
+    const DAY: u8 = 14;
+    const TITLE: &'static str = "Extended Polymerization";
+
+    /// The polymer template, and the pair insertion rules.
+    type Input = (Vec<Element>, HashMap<Pair, Element>);
+
+    fn parse(&self, text: &str) -> Result<Self::Input> {
+        parse_puzzle_input(crate::iter::numbered_lines(text))
+    }
+
+    /// Applies the rules 10 times to the explicit sequence of elements, and returns the difference
+    /// between the most and least common element counts.
+    fn part1(&self, (template, rules): &Self::Input) -> Answer {
         let mut result = template.clone();
         let mut input = Vec::new();
         for _step in 0..10 {
             std::mem::swap(&mut input, &mut result);
             result.clear();
-            apply_rules(&input, &rules, &mut result);
+            apply_rules(&input, rules, &mut result);
         }
 
         let (min_count, max_count) = find_min_max_element_counts(&result);
-        println!(
-            "Part 1: min count = {}, max count = {}, difference: {}",
-            min_count,
-            max_count,
-            max_count - min_count
-        );
+        (max_count - min_count).to_string()
+    }
 
-        let mut result = count_digrams(&template);
+    /// Same as part 1, but applying the rules 40 times, which requires only keeping track of the
+    /// digram counts.
+    fn part2(&self, (template, rules): &Self::Input) -> Answer {
+        let mut result = count_digrams(template);
         for _step in 0..40 {
-            result = apply_rules_to_digrams(&result, &rules);
+            result = apply_rules_to_digrams(&result, rules);
         }
 
-        let (min_count, max_count) = find_min_max_element_counts_in_digrams(&template, &result);
-        println!(
-            "Part 2: min count = {}, max count = {}, difference: {}",
-            min_count,
-            max_count,
-            max_count - min_count
-        );
+        let (min_count, max_count) = find_min_max_element_counts_in_digrams(template, &result);
+        (max_count - min_count).to_string()
     }
 }
 
-type Element = u8;
-type Pair = [Element; 2];
+pub type Element = u8;
+pub type Pair = [Element; 2];
 
 fn apply_rules(input: &[Element], rules: &HashMap<Pair, Element>, result: &mut Vec<Element>) {
     result.clear();
@@ -115,7 +120,7 @@ fn find_min_max_element_counts_in_digrams(original_template: &[Element], digrams
     // element as the last element of the original template string!
     histogram[*original_template.last().unwrap() as usize] += 1;
 
-    let mut min = std::usize::MAX;
+    let mut min = usize::MAX;
     let mut max = 0;
     for count in histogram {
         if count == 0 {
@@ -129,18 +134,30 @@ fn find_min_max_element_counts_in_digrams(original_template: &[Element], digrams
 }
 
 fn parse_puzzle_input(
-    mut lines: impl Iterator<Item = String>,
-) -> (Vec<Element>, HashMap<Pair, Element>) {
-    let template: Vec<Element> = lines.next().unwrap().as_bytes().into();
+    mut lines: crate::iter::Lines,
+) -> Result<(Vec<Element>, HashMap<Pair, Element>)> {
+    let template: Vec<Element> = lines
+        .next_or_err("the polymer template")?
+        .text
+        .as_bytes()
+        .into();
 
     let mut rules = HashMap::new();
     for line in lines {
-        let bytes = line.as_bytes();
-        assert_eq!(bytes.len(), 7);
-        assert_eq!(&bytes[2..6], b" -> ");
+        // Rules look like "AB -> C".
+        let (pair, element) = line.split_once(line.text, " -> ")?;
+        if pair.len() != 2 {
+            return Err(line.error_at(pair, "Expected a pair of elements"));
+        }
+        if element.len() != 1 {
+            return Err(line.error_at(element, "Expected a single element"));
+        }
 
-        rules.insert([bytes[0], bytes[1]], bytes[6]);
+        rules.insert(
+            [pair.as_bytes()[0], pair.as_bytes()[1]],
+            element.as_bytes()[0],
+        );
     }
 
-    (template, rules)
+    Ok((template, rules))
 }
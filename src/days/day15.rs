@@ -0,0 +1,426 @@
+use crate::animation::{save_animation, Format};
+use crate::colormap::{Colormap, LinearColorScale, Rgb};
+use crate::image::Image;
+use crate::netpbm::{PamImage, TupleType};
+use crate::solver::{Answer, Solver};
+
+#[derive(Default)]
+pub struct Day15 {
+    /// If set, images showing the progress of the part 2 search are saved to this dir.
+    log_images_to: Option<std::path::PathBuf>,
+    /// The format of those images.
+    log_images_format: LogImagesFormat,
+    /// The colormap of those images, plasma by default.
+    log_images_colormap: Option<Colormap>,
+    /// Map the costs to colors logarithmically rather than linearly.
+    log_images_log_scale: bool,
+    /// If set, non-zero pixels of this mask are turned into obstacles for part 2.
+    obstacle_mask: Option<Image<u8>>,
+}
+
+impl Solver for Day15 {
+    const DAY: u8 = 15;
+    const TITLE: &'static str = "Chiton";
+
+    type Input = Image<usize>;
+
+    /// Supported options:
+    /// - `log-images-to=<dir>`: save images of the part 2 search progress to this dir.
+    /// - `log-images-format=<ppm|pam|gif|apng>`: the format of those images. With PAM, the pixels
+    ///   which haven't been explored yet are transparent. GIF and APNG save a single animation.
+    /// - `log-images-colormap=<name>`: the colormap of those images, eg `viridis`, or `viridis_r`
+    ///   for the reversed one.
+    /// - `log-images-scale=<linear|log>`: how the costs are mapped to colors.
+    /// - `apply-obstacle-mask=<path>`: read a PGM-format mask, where non-zero pixels become
+    ///   obstacles for part 2. Use `-` to read it from stdin.
+    fn configure(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "log-images-to" => {
+                self.log_images_to = Some(value.into());
+                Ok(())
+            }
+            "log-images-format" => {
+                self.log_images_format = match value {
+                    "ppm" => LogImagesFormat::Ppm,
+                    "pam" => LogImagesFormat::Pam,
+                    "gif" => LogImagesFormat::Gif,
+                    "apng" => LogImagesFormat::Apng,
+                    _ => return Err(format!("Invalid image format {:?}", value)),
+                };
+                Ok(())
+            }
+            "log-images-colormap" => {
+                let colormap = Colormap::from_name(value)
+                    .ok_or_else(|| format!("Unknown colormap {:?}", value))?;
+                self.log_images_colormap = Some(colormap);
+                Ok(())
+            }
+            "log-images-scale" => {
+                self.log_images_log_scale = match value {
+                    "linear" => false,
+                    "log" => true,
+                    _ => return Err(format!("Invalid color scale {:?}", value)),
+                };
+                Ok(())
+            }
+            "apply-obstacle-mask" => {
+                let mask = if value == "-" {
+                    crate::netpbm::read_pgm_from(std::io::stdin().lock())
+                } else {
+                    crate::netpbm::try_read_pgm_image(std::path::Path::new(value))
+                };
+                let mask =
+                    mask.map_err(|err| format!("Failed to read PGM-format mask: {}", err))?;
+                self.obstacle_mask = Some(mask);
+                Ok(())
+            }
+            _ => Err(format!("Day {} has no option named {:?}", Self::DAY, name)),
+        }
+    }
+
+    fn parse(&self, text: &str) -> crate::error::Result<Self::Input> {
+        let image = parse_input_image(crate::iter::numbered_lines(text))?;
+        if image.is_empty() {
+            return Err(crate::error::Error::parse_at_offset(
+                text,
+                text.len(),
+                "Expected at least 1 row of risk levels",
+            ));
+        }
+        Ok(image)
+    }
+
+    fn part1(&self, image: &Self::Input) -> Answer {
+        find_optimal_path(image, None).to_string()
+    }
+
+    fn part2(&self, image: &Self::Input) -> Answer {
+        let mut tiled_image = expand_tile_into_full_image(image);
+
+        if let Some(mask) = &self.obstacle_mask {
+            assert_eq!(mask.height, tiled_image.height);
+            assert_eq!(mask.width, tiled_image.width);
+            assert_eq!(mask.data.len(), tiled_image.data.len());
+            let very_high_cost = mask.height * mask.width * 10;
+
+            // Use an infinity-like cost for non-zero pixels from the mask.
+            for (m, p) in mask.data.iter().copied().zip(tiled_image.data.iter_mut()) {
+                if m >= 1 {
+                    *p = very_high_cost;
+                }
+            }
+        }
+
+        let mut log = self
+            .log_images_to
+            .as_ref()
+            .map(|_| ExplorationLog::new(tiled_image.height, tiled_image.width));
+
+        let optimal_path_cost = find_optimal_path(&tiled_image, log.as_mut());
+
+        if let (Some(log), Some(logdir)) = (log, &self.log_images_to) {
+            let colormap = self.log_images_colormap.unwrap_or(Colormap::PLASMA);
+            let mut cs = LinearColorScale::new(0.0, log.max_cost() as f32, colormap);
+            cs.log = self.log_images_log_scale;
+            generate_viz_images(&log, logdir, "2021-12-15", self.log_images_format, cs);
+        }
+
+        optimal_path_cost.to_string()
+    }
+}
+
+/// Search for a minimal cost path between the top-left corner and the bottom right corner.
+///
+/// Uses an implementation of A* (https://en.wikipedia.org/wiki/A*_search_algorithm).
+///
+/// Optionally records the order in which pixels are visited (as well as their cost) to a log.
+fn find_optimal_path(image: &Image<usize>, mut log: Option<&mut ExplorationLog>) -> usize {
+    // A heuristic that estimates the distance to the bottom right.
+    // For A* to find the optimal path first, this heuristic must never over-estimate the
+    // distance (it must be an admissible heuristic).
+    // To do so, we simply assume that all costs till the end are 1.
+    let estimated_dist_to_end =
+        |row: usize, col: usize| -> usize { (image.height - 1 - row) + (image.width - 1 - col) };
+
+    // Using the heuristic "distance to the end", compute the heuristic "total cost".
+    let estimated_total_cost = |cost_so_far: usize, row: usize, col: usize| -> usize {
+        let est_remaining_cost = estimated_dist_to_end(row, col);
+        cost_so_far + est_remaining_cost
+    };
+
+    // The image of visited pixels.
+    let mut visited = Image {
+        height: image.height,
+        width: image.width,
+        data: vec![false; image.height * image.width],
+    };
+
+    // The open set of points, ie the pixels we haven't visited and could visit in the next step,
+    // starting from a previously visited pixel.
+    // This is a max heap, meaning max-cost first. To flip this we'll use Reverse.
+    use std::cmp::Reverse;
+    let mut to_visit = std::collections::BinaryHeap::new();
+    to_visit.push(Reverse(VisitCandidate {
+        row: 0,
+        col: 0,
+        cost_so_far: 0,
+        estimated_total_cost: estimated_total_cost(0, 0, 0),
+    }));
+
+    // Scratch buffer used for iterating over the valid neighbors. Cleared on every iteration.
+    let mut neighbors = Vec::with_capacity(4);
+
+    while let Some(cand) = to_visit.pop() {
+        let cand = cand.0; // Peel the Reverse.
+
+        // Our distance heuristic is 'consistent', so the first time we visit a pixel is the
+        // optimal cost already.
+        if *visited.pixel(cand.row, cand.col) {
+            continue;
+        }
+
+        if let Some(log) = &mut log {
+            log.explore(cand.row, cand.col, cand.cost_so_far);
+        }
+
+        if cand.row == image.height - 1 && cand.col == image.width - 1 {
+            return cand.cost_so_far;
+        }
+        *visited.pixel_mut(cand.row, cand.col) = true;
+
+        // Add all 4 neighbors to the open set.
+        neighbors.clear();
+        if 0 < cand.row && !visited.pixel(cand.row - 1, cand.col) {
+            neighbors.push((cand.row - 1, cand.col));
+        }
+        if cand.row + 1 < image.height && !visited.pixel(cand.row + 1, cand.col) {
+            neighbors.push((cand.row + 1, cand.col));
+        }
+        if 0 < cand.col && !visited.pixel(cand.row, cand.col - 1) {
+            neighbors.push((cand.row, cand.col - 1));
+        }
+        if cand.col + 1 < image.width && !visited.pixel(cand.row, cand.col + 1) {
+            neighbors.push((cand.row, cand.col + 1));
+        }
+
+        for &(r, c) in &neighbors {
+            let cost_so_far = cand.cost_so_far + *image.pixel(r, c);
+            to_visit.push(Reverse(VisitCandidate {
+                row: r,
+                col: c,
+                cost_so_far,
+                estimated_total_cost: estimated_total_cost(cost_so_far, r, c),
+            }));
+        }
+    }
+
+    panic!("BUG: should be unreachable");
+}
+
+#[derive(Clone, Debug, Eq)]
+struct VisitCandidate {
+    row: usize,
+    col: usize,
+    cost_so_far: usize,
+    estimated_total_cost: usize,
+}
+
+impl std::cmp::Ord for VisitCandidate {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.estimated_total_cost.cmp(&other.estimated_total_cost)
+    }
+}
+
+impl std::cmp::PartialOrd for VisitCandidate {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::cmp::PartialEq for VisitCandidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost_so_far == other.cost_so_far
+    }
+}
+
+fn parse_input_image(lines: crate::iter::Lines) -> crate::error::Result<Image<usize>> {
+    let mut height = 0;
+    let mut width = 0;
+    let mut data = Vec::new();
+
+    for line in lines {
+        height += 1;
+        if width == 0 {
+            width = line.text.len();
+        }
+        if width != line.text.len() {
+            return Err(line.error(format!(
+                "Expected {} columns, got {}",
+                width,
+                line.text.len()
+            )));
+        }
+
+        data.extend(line.parse_digits()?.into_iter().map(|d| d as usize));
+    }
+
+    Ok(Image {
+        height,
+        width,
+        data,
+    })
+}
+
+fn expand_tile_into_full_image(tile: &Image<usize>) -> Image<usize> {
+    let mut img = Image {
+        height: tile.height * 5,
+        width: tile.width * 5,
+        data: vec![0usize; tile.height * 5 * tile.width * 5],
+    };
+
+    for row_in_img in 0..img.height {
+        for col_in_img in 0..img.width {
+            let row_in_tile = row_in_img % tile.height;
+            let col_in_tile = col_in_img % tile.width;
+
+            let tile_index_rows = row_in_img / tile.height;
+            let tile_index_cols = col_in_img / tile.width;
+            let extra_risk = tile_index_rows + tile_index_cols;
+
+            // This is a little more awkward than just "%10" because there is no risk level 0.
+            // So we remap the risk to [0,8], compute the extra risk %9, and then remap back to
+            // [1,9].
+            *img.pixel_mut(row_in_img, col_in_img) =
+                1 + (*tile.pixel(row_in_tile, col_in_tile) - 1 + extra_risk) % 9;
+        }
+    }
+
+    img
+}
+
+struct ExplorationLog {
+    image_height: usize,
+    image_width: usize,
+    /// The visited pixels, as (row, col, cost) tuples.
+    visits: Vec<(usize, usize, usize)>,
+}
+
+impl ExplorationLog {
+    fn new(image_height: usize, image_width: usize) -> Self {
+        Self {
+            image_height,
+            image_width,
+            visits: Vec::new(),
+        }
+    }
+
+    fn explore(&mut self, row: usize, col: usize, cost: usize) {
+        self.visits.push((row, col, cost));
+    }
+
+    fn max_cost(&self) -> usize {
+        self.visits
+            .iter()
+            .map(|&(_, _, cost)| cost)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// The format of the images showing the part 2 search progress.
+#[derive(Copy, Clone, Debug, Default)]
+enum LogImagesFormat {
+    /// One PPM image per frame.
+    #[default]
+    Ppm,
+    /// One PAM image per frame, with an alpha channel.
+    Pam,
+    /// A single animated GIF.
+    Gif,
+    /// A single animated PNG.
+    Apng,
+}
+
+fn generate_viz_images(
+    log: &ExplorationLog,
+    dir: &std::path::Path,
+    name_prefix: &str,
+    format: LogImagesFormat,
+    cs: LinearColorScale,
+) {
+    // There are (likely) way too many steps to save an image for each.
+    // Instead, we'll aim for a N second clip at M fps.
+    let target_len_s = 20;
+    let fps = 30;
+    let frames = viz_frames(log, cs, target_len_s * fps);
+    let to_rgb = |frame: Image<[u8; 4]>| frame.map(|px| Rgb::new(px[0], px[1], px[2]));
+
+    let animation_format = match format {
+        LogImagesFormat::Ppm | LogImagesFormat::Pam => {
+            let mut num_saved_images = 0;
+            for (i, frame) in frames.enumerate() {
+                let mut path = std::path::PathBuf::new();
+                path.push(dir);
+                if let LogImagesFormat::Pam = format {
+                    path.push(format!("{}.step_{:05}.pam", name_prefix, i));
+                    let pam = PamImage::from_image(&frame, Some(TupleType::RgbAlpha));
+                    crate::netpbm::save_pam(&pam, &path).expect("Failed to save image");
+                } else {
+                    path.push(format!("{}.step_{:05}.ppm", name_prefix, i));
+                    crate::netpbm::save_image_as_ppm(&to_rgb(frame), &path)
+                        .expect("Failed to save image");
+                }
+                num_saved_images += 1;
+            }
+            println!("Saved {} images!", num_saved_images);
+            return;
+        }
+        LogImagesFormat::Gif => Format::Gif,
+        LogImagesFormat::Apng => Format::Apng,
+    };
+
+    let path = dir.join(format!("{}.{}", name_prefix, animation_format.extension()));
+    let frame_delay = std::time::Duration::from_secs(1) / fps as u32;
+    let num_frames = save_animation(&path, animation_format, frames.map(to_rgb), frame_delay)
+        .expect("Failed to save animation");
+    println!("Saved an animation of {} frames to {:?}!", num_frames, path);
+}
+
+/// Lazily generates about `target_num_frames` RGBA frames of the search progress, each with a
+/// colorbar below it. The pixels which haven't been explored yet are transparent.
+fn viz_frames(
+    log: &ExplorationLog,
+    cs: LinearColorScale,
+    target_num_frames: usize,
+) -> impl Iterator<Item = Image<[u8; 4]>> + '_ {
+    // The output image that we'll progressively modify, as RGBA.
+    // Starts out as all black, and transparent.
+    let mut img = Image {
+        height: log.image_height,
+        width: log.image_width,
+        data: vec![[0u8; 4]; log.image_height * log.image_width],
+    };
+
+    // A colorbar appended below each frame, showing the costs. It's always opaque.
+    let colorbar = cs
+        .colorbar(log.image_width)
+        .map(|c| [c.r(), c.g(), c.b(), 255]);
+
+    // We'll save a frame every x:
+    let save_step = usize::max(1, log.visits.len() / target_num_frames);
+
+    let mut visits = log.visits.iter().enumerate();
+    std::iter::from_fn(move || {
+        for (step, (row, col, cost)) in visits.by_ref() {
+            let color = cs.map(*cost as f32);
+            *img.pixel_mut(*row, *col) = [color.r(), color.g(), color.b(), 255];
+
+            if step % save_step == 0 || step + 1 == log.visits.len() {
+                let mut frame = img.clone();
+                frame.append_rows(&colorbar);
+                return Some(frame);
+            }
+        }
+        None
+    })
+}
@@ -0,0 +1,756 @@
+use crate::bits::{BitWriter, Bitstream};
+use crate::error::Error;
+use crate::iter::Line;
+use crate::solver::{Answer, Solver};
+
+#[derive(Default)]
+pub struct Day16 {
+    /// If set, the packet is printed in part 2, in this form.
+    print_packet: Option<PacketForm>,
+}
+
+/// The ways to print a packet, for debugging.
+#[derive(Clone, Copy, Debug)]
+enum PacketForm {
+    /// An outline of the packet tree, see `Display for Packet`.
+    Outline,
+    /// An infix expression, see `infix_expression`.
+    Infix,
+    /// An infix expression, with the values of all sub-expressions.
+    InfixWithValues,
+}
+
+impl Solver for Day16 {
+    const DAY: u8 = 16;
+    const TITLE: &'static str = "Packet Decoder";
+
+    type Input = Packet;
+
+    /// Supported options:
+    /// - `print-packet=<outline|infix|infix-with-values>`: print the packet in part 2, as an
+    ///   outline with the versions, type ids and bit offsets of all packets, or as an infix
+    ///   expression, optionally with the values of its sub-expressions.
+    fn configure(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "print-packet" => {
+                self.print_packet = Some(match value {
+                    "outline" => PacketForm::Outline,
+                    "infix" => PacketForm::Infix,
+                    "infix-with-values" => PacketForm::InfixWithValues,
+                    _ => return Err(format!("Invalid packet form {:?}", value)),
+                });
+                Ok(())
+            }
+            _ => Err(format!("Day {} has no option named {:?}", Self::DAY, name)),
+        }
+    }
+
+    fn parse(&self, text: &str) -> crate::error::Result<Self::Input> {
+        let line = crate::iter::numbered_lines(text).next_or_err("a hex message")?;
+        let msg = parse_puzzle_input(&line)?;
+        parse_message(&msg)
+    }
+
+    /// Returns the sum of the versions of all packets.
+    fn part1(&self, packet: &Self::Input) -> Answer {
+        let mut to_visit = vec![packet];
+        let mut sum_of_versions = 0u64;
+        while let Some(packet) = to_visit.pop() {
+            sum_of_versions += packet.version as u64;
+
+            if let Payload::Op { packets, .. } = &packet.payload {
+                to_visit.extend(packets);
+            }
+        }
+
+        sum_of_versions.to_string()
+    }
+
+    /// Returns the value of the top-level packet's expression.
+    fn part2(&self, packet: &Self::Input) -> Answer {
+        match self.print_packet {
+            None => {}
+            Some(PacketForm::Outline) => print!("{}", packet),
+            Some(PacketForm::Infix) => println!("{}", infix_expression(packet, false)),
+            Some(PacketForm::InfixWithValues) => println!("{}", infix_expression(packet, true)),
+        }
+        eval(packet).to_string()
+    }
+}
+
+struct Message {
+    bytes: Vec<u8>,
+    /// The line of the puzzle input the message was read from, for error reporting.
+    line_number: usize,
+}
+
+impl Message {
+    fn as_bits(&self) -> Bitstream<'_> {
+        Bitstream::new(&self.bytes)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Packet {
+    version: u8,
+    payload: Payload,
+    /// Where the packet starts in its message, in bits. 0 for packets which weren't parsed.
+    bit_offset: usize,
+}
+
+/// Packets are equal if their contents are, wherever they are in their messages.
+impl PartialEq for Packet {
+    fn eq(&self, other: &Self) -> bool {
+        self.version == other.version && self.payload == other.payload
+    }
+}
+
+impl Eq for Packet {}
+
+impl Packet {
+    fn type_id(&self) -> u8 {
+        match &self.payload {
+            Payload::Literal(_) => 4,
+            Payload::Op { id, .. } => id.type_id(),
+        }
+    }
+
+    fn write_outline(&self, f: &mut std::fmt::Formatter<'_>, depth: usize) -> std::fmt::Result {
+        write!(
+            f,
+            "{:>6}  {:indent$}v{} type {}: ",
+            self.bit_offset,
+            "",
+            self.version,
+            self.type_id(),
+            indent = 2 * depth
+        )?;
+        match &self.payload {
+            Payload::Literal(lit) => writeln!(f, "literal {}", lit),
+            Payload::Op { id, packets } => {
+                writeln!(f, "{} of {} packets", id.name(), packets.len())?;
+                packets
+                    .iter()
+                    .try_for_each(|packet| packet.write_outline(f, depth + 1))
+            }
+        }
+    }
+}
+
+/// An outline of the packet tree, with one line per packet: its bit offset, then indented by
+/// depth, its version, type id and contents. Eg:
+///
+/// ```text
+///      0  v7 type 3: max of 2 packets
+///     18    v2 type 4: literal 1
+///     29    v4 type 4: literal 2
+/// ```
+impl std::fmt::Display for Packet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_outline(f, 0)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Payload {
+    Literal(u64),
+    Op { id: OpId, packets: Vec<Packet> },
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum OpId {
+    Sum,
+    Product,
+    Min,
+    Max,
+    Greater,
+    Less,
+    Equal,
+}
+
+impl OpId {
+    fn from_type_id(type_id: u8) -> Option<Self> {
+        use OpId::*;
+        match type_id {
+            0 => Some(Sum),
+            1 => Some(Product),
+            2 => Some(Min),
+            3 => Some(Max),
+            5 => Some(Greater),
+            6 => Some(Less),
+            7 => Some(Equal),
+            _ => None,
+        }
+    }
+
+    fn type_id(self) -> u8 {
+        use OpId::*;
+        match self {
+            Sum => 0,
+            Product => 1,
+            Min => 2,
+            Max => 3,
+            Greater => 5,
+            Less => 6,
+            Equal => 7,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        use OpId::*;
+        match self {
+            Sum => "sum",
+            Product => "product",
+            Min => "min",
+            Max => "max",
+            Greater => "greater",
+            Less => "less",
+            Equal => "equal",
+        }
+    }
+
+    fn is_comparison(self) -> bool {
+        matches!(self, OpId::Greater | OpId::Less | OpId::Equal)
+    }
+}
+
+/// The result of decoding (part of) a message. On error, the bitstream is left where the error was
+/// found.
+type DecodeResult<T> = Result<T, String>;
+
+fn parse_message(msg: &Message) -> crate::error::Result<Packet> {
+    let mut bits = msg.as_bits();
+
+    let decode = |bits: &mut Bitstream<'_>| -> DecodeResult<Packet> {
+        let packet = parse_packet(bits)?;
+
+        // There may be some bits left at the end. They must all be zeroes.
+        if bits.num_remaining_bits() >= 8 {
+            return Err("Trailing data after the outermost packet".to_string());
+        }
+        let remaining_data = bits
+            .peek_n_bits(bits.num_remaining_bits() as u8)
+            .unwrap_or(0);
+        if remaining_data != 0 {
+            return Err("Non-zero padding after the outermost packet".to_string());
+        }
+
+        Ok(packet)
+    };
+
+    decode(&mut bits).map_err(|err| {
+        let bit_offset = bits.bits_consumed();
+        // Each hex char of the input holds 4 bits.
+        Error::parse(
+            msg.line_number,
+            bit_offset / 4 + 1,
+            format!("{} (at bit {})", err, bit_offset),
+        )
+    })
+}
+
+/// Reads the next n bits, failing if the message ended early.
+fn read_bits(bits: &mut Bitstream<'_>, n: u8) -> DecodeResult<u16> {
+    bits.get_n_bits(n)
+        .ok_or_else(|| "Unexpected end of message".to_string())
+}
+
+fn parse_packet(bits: &mut Bitstream<'_>) -> DecodeResult<Packet> {
+    let bit_offset = bits.bits_consumed();
+    let version = read_bits(bits, 3)? as u8;
+    let type_id = read_bits(bits, 3)? as u8;
+
+    let payload = match type_id {
+        4 => Payload::Literal(parse_literal(bits)?),
+        0..=3 | 5..=7 => {
+            let id = OpId::from_type_id(type_id).unwrap();
+            let packets = parse_op_packets(bits)?;
+
+            // Evaluation needs at least 1 operand, and exactly 2 for comparisons.
+            if packets.is_empty() || (id.is_comparison() && packets.len() != 2) {
+                return Err(format!(
+                    "Invalid number of sub-packets for {:?}: {}",
+                    id,
+                    packets.len()
+                ));
+            }
+
+            Payload::Op { id, packets }
+        }
+        _ => unreachable!("3-bit type id"),
+    };
+
+    Ok(Packet {
+        version,
+        payload,
+        bit_offset,
+    })
+}
+
+fn parse_literal(bits: &mut Bitstream<'_>) -> DecodeResult<u64> {
+    let mut lit = 0u64;
+    loop {
+        if lit.leading_zeros() < 4 {
+            return Err("Literal value overflows 64 bits".to_string());
+        }
+        let has_more_groups = read_bits(bits, 1)? == 1;
+        lit = (lit << 4) | read_bits(bits, 4)? as u64;
+        if !has_more_groups {
+            break;
+        }
+    }
+
+    Ok(lit)
+}
+
+fn parse_op_packets(bits: &mut Bitstream<'_>) -> DecodeResult<Vec<Packet>> {
+    let length_type_id = read_bits(bits, 1)?;
+
+    let mut subpackets = Vec::new();
+
+    if length_type_id == 0 {
+        let len_of_subpackets_in_bits = read_bits(bits, 15)? as usize;
+        let bits_in_stream = bits.num_remaining_bits();
+
+        // How many trailing bits are not part of this op's subpackets?
+        let num_not_our_business_bits = bits_in_stream
+            .checked_sub(len_of_subpackets_in_bits)
+            .ok_or_else(|| "Sub-packets length exceeds the end of the message".to_string())?;
+
+        while bits.num_remaining_bits() > num_not_our_business_bits {
+            subpackets.push(parse_packet(bits)?);
+        }
+        if bits.num_remaining_bits() != num_not_our_business_bits {
+            return Err("Sub-packets overflow their declared length".to_string());
+        }
+    } else {
+        let num_subpackets = read_bits(bits, 11)?;
+        for _ in 0..num_subpackets {
+            subpackets.push(parse_packet(bits)?);
+        }
+    }
+
+    Ok(subpackets)
+}
+
+/// How an operator packet declares the extent of its sub-packets.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LengthType {
+    /// Length type 0: the total number of bits of the sub-packets, in 15 bits.
+    TotalBits,
+    /// Length type 1: the number of sub-packets, in 11 bits.
+    NumPackets,
+}
+
+/// Encodes a packet as a hex transmission, which `parse_message` decodes back to the same packet.
+///
+/// Operator packets use the `preferred` length type, or the other one if their sub-packets don't
+/// fit in it.
+pub fn encode_message(packet: &Packet, preferred: LengthType) -> Result<String, String> {
+    let mut bits = BitWriter::new();
+    encode_packet(packet, preferred, &mut bits)?;
+    Ok(bits
+        .as_bytes()
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect())
+}
+
+fn encode_packet(
+    packet: &Packet,
+    preferred: LengthType,
+    bits: &mut BitWriter,
+) -> Result<(), String> {
+    if packet.version > 7 {
+        return Err(format!("Version {} doesn't fit in 3 bits", packet.version));
+    }
+    bits.put_bits_u64(packet.version as u64, 3);
+
+    match &packet.payload {
+        Payload::Literal(lit) => {
+            bits.put_bits_u64(4, 3);
+
+            // Groups of 4 bits, most-significant first, each prefixed with whether more follow.
+            let num_groups = u32::max(1, (u64::BITS - lit.leading_zeros()).div_ceil(4));
+            for i in (0..num_groups).rev() {
+                bits.put_bits_u64((i > 0) as u64, 1);
+                bits.put_bits_u64((lit >> (4 * i)) & 0xF, 4);
+            }
+        }
+        Payload::Op { id, packets } => {
+            if packets.is_empty() || (id.is_comparison() && packets.len() != 2) {
+                return Err(format!(
+                    "Invalid number of sub-packets for {:?}: {}",
+                    id,
+                    packets.len()
+                ));
+            }
+            bits.put_bits_u64(id.type_id() as u64, 3);
+
+            let mut sub_bits = BitWriter::new();
+            for sub_packet in packets {
+                encode_packet(sub_packet, preferred, &mut sub_bits)?;
+            }
+            let fits_in_total_bits = sub_bits.bits_written() < 1 << 15;
+            let fits_in_num_packets = packets.len() < 1 << 11;
+            let length_type = match preferred {
+                LengthType::TotalBits if fits_in_total_bits => LengthType::TotalBits,
+                LengthType::NumPackets if fits_in_num_packets => LengthType::NumPackets,
+                _ if fits_in_total_bits => LengthType::TotalBits,
+                _ if fits_in_num_packets => LengthType::NumPackets,
+                _ => {
+                    return Err(format!(
+                        "Too many sub-packets to encode: {} packets, of {} bits",
+                        packets.len(),
+                        sub_bits.bits_written()
+                    ))
+                }
+            };
+            match length_type {
+                LengthType::TotalBits => {
+                    bits.put_bits_u64(0, 1);
+                    bits.put_bits_u64(sub_bits.bits_written() as u64, 15);
+                }
+                LengthType::NumPackets => {
+                    bits.put_bits_u64(1, 1);
+                    bits.put_bits_u64(packets.len() as u64, 11);
+                }
+            }
+
+            // Copy over the sub-packets' bits.
+            let mut sub_bits_reader = Bitstream::new(sub_bits.as_bytes());
+            let mut num_left = sub_bits.bits_written();
+            while num_left > 0 {
+                let n = usize::min(num_left, 64) as u8;
+                bits.put_bits_u64(sub_bits_reader.get_bits_u64(n).unwrap(), n);
+                num_left -= n as usize;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn eval(packet: &Packet) -> u64 {
+    match &packet.payload {
+        Payload::Literal(x) => *x,
+        Payload::Op { id, packets } => apply_op(*id, packets.iter().map(eval)),
+    }
+}
+
+/// Applies an operator to the values of its sub-packets.
+fn apply_op(id: OpId, mut evaled_subs: impl Iterator<Item = u64>) -> u64 {
+    use OpId::*;
+    match id {
+        Sum => evaled_subs.sum::<u64>(),
+        Product => evaled_subs.product(),
+        Min => evaled_subs.min().unwrap(),
+        Max => evaled_subs.max().unwrap(),
+        Greater => {
+            let a = evaled_subs.next().unwrap();
+            let b = evaled_subs.next().unwrap();
+            (a > b) as u64
+        }
+        Less => {
+            let a = evaled_subs.next().unwrap();
+            let b = evaled_subs.next().unwrap();
+            (a < b) as u64
+        }
+        Equal => {
+            let a = evaled_subs.next().unwrap();
+            let b = evaled_subs.next().unwrap();
+            (a == b) as u64
+        }
+    }
+}
+
+/// The precedences of infix expressions, from the loosest to the tightest.
+const PRECEDENCE_COMPARISON: u8 = 0;
+const PRECEDENCE_SUM: u8 = 1;
+const PRECEDENCE_PRODUCT: u8 = 2;
+const PRECEDENCE_ATOM: u8 = 3;
+
+/// Writes the packet as an infix expression, eg `min(3, 5 * (2 + 7)) > 4`.
+///
+/// With `with_values`, each operator's sub-expression is shown in brackets along with its value,
+/// and the value of the whole expression comes last, eg
+/// `[min(3, [5 * [2 + 7 = 9] = 45]) = 3] > 4 = 0`.
+pub fn infix_expression(packet: &Packet, with_values: bool) -> String {
+    let (expr, value, _) = infix(packet, with_values);
+    match packet.payload {
+        Payload::Op { .. } if with_values => format!("{} = {}", expr, value),
+        _ => expr,
+    }
+}
+
+/// Returns the infix expression of a packet, its value, and its precedence.
+fn infix(packet: &Packet, with_values: bool) -> (String, u64, u8) {
+    let (id, packets) = match &packet.payload {
+        Payload::Literal(lit) => return (lit.to_string(), *lit, PRECEDENCE_ATOM),
+        Payload::Op { id, packets } => (*id, packets),
+    };
+
+    let (symbol, precedence) = match id {
+        OpId::Sum if packets.len() > 1 => (" + ", PRECEDENCE_SUM),
+        OpId::Product if packets.len() > 1 => (" * ", PRECEDENCE_PRODUCT),
+        OpId::Greater => (" > ", PRECEDENCE_COMPARISON),
+        OpId::Less => (" < ", PRECEDENCE_COMPARISON),
+        OpId::Equal => (" == ", PRECEDENCE_COMPARISON),
+        // Written as function calls, eg `min(a, b)`.
+        _ => (", ", PRECEDENCE_ATOM),
+    };
+
+    let mut values = Vec::with_capacity(packets.len());
+    let operands: Vec<String> = packets
+        .iter()
+        .map(|sub_packet| {
+            let (expr, value, sub_precedence) = infix(sub_packet, with_values);
+            values.push(value);
+            if with_values && matches!(sub_packet.payload, Payload::Op { .. }) {
+                format!("[{} = {}]", expr, value)
+            } else if precedence != PRECEDENCE_ATOM
+                && (sub_precedence < precedence
+                    || (id.is_comparison() && sub_precedence == precedence))
+            {
+                format!("({})", expr)
+            } else {
+                expr
+            }
+        })
+        .collect();
+
+    let expr = if precedence == PRECEDENCE_ATOM {
+        format!("{}({})", id.name(), operands.join(symbol))
+    } else {
+        operands.join(symbol)
+    };
+    (expr, apply_op(id, values.into_iter()), precedence)
+}
+
+fn parse_puzzle_input(line: &Line) -> crate::error::Result<Message> {
+    let txt = line.text.trim_end();
+    let mut bytes = Vec::new();
+
+    let hex_digit = |i: usize| {
+        binary_from_ascii_hex_char(txt.as_bytes()[i])
+            .ok_or_else(|| line.error_at(&txt[i..], "Expected a hex digit"))
+    };
+    for i in (0..txt.len()).step_by(2) {
+        // The last chunk may be a single hex digit.
+        let mut b = hex_digit(i)? << 4;
+        if i + 1 < txt.len() {
+            b |= hex_digit(i + 1)?;
+        }
+
+        bytes.push(b);
+    }
+    Ok(Message {
+        bytes,
+        line_number: line.number,
+    })
+}
+
+fn binary_from_ascii_hex_char(hex_char_ascii: u8) -> Option<u8> {
+    match hex_char_ascii {
+        b'0'..=b'9' => Some(hex_char_ascii - b'0'),
+        b'a'..=b'f' => Some(10 + (hex_char_ascii - b'a')),
+        b'A'..=b'F' => Some(10 + (hex_char_ascii - b'A')),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(hex: &str) -> Packet {
+        let line = crate::iter::numbered_lines(hex).next().unwrap();
+        parse_message(&parse_puzzle_input(&line).unwrap()).unwrap()
+    }
+
+    fn literal(version: u8, lit: u64) -> Packet {
+        Packet {
+            version,
+            payload: Payload::Literal(lit),
+            bit_offset: 0,
+        }
+    }
+
+    fn op(version: u8, id: OpId, packets: Vec<Packet>) -> Packet {
+        Packet {
+            version,
+            payload: Payload::Op { id, packets },
+            bit_offset: 0,
+        }
+    }
+
+    #[test]
+    fn test_encode_samples() {
+        // The examples of the puzzle's description.
+        let lit = literal(6, 2021);
+        assert_eq!(
+            encode_message(&lit, LengthType::TotalBits).unwrap(),
+            "D2FE28"
+        );
+        assert_eq!(decode("D2FE28"), lit);
+
+        let less = op(1, OpId::Less, vec![literal(6, 10), literal(2, 20)]);
+        assert_eq!(
+            encode_message(&less, LengthType::TotalBits).unwrap(),
+            "38006F45291200"
+        );
+        assert_eq!(decode("38006F45291200"), less);
+
+        let max = op(
+            7,
+            OpId::Max,
+            vec![literal(2, 1), literal(4, 2), literal(1, 3)],
+        );
+        assert_eq!(
+            encode_message(&max, LengthType::NumPackets).unwrap(),
+            "EE00D40C823060"
+        );
+        assert_eq!(decode("EE00D40C823060"), max);
+    }
+
+    #[test]
+    fn test_outline() {
+        let packet = decode("EE00D40C823060");
+        assert_eq!(
+            packet.to_string(),
+            "     0  v7 type 3: max of 3 packets\n\
+             \x20   18    v2 type 4: literal 1\n\
+             \x20   29    v4 type 4: literal 2\n\
+             \x20   40    v1 type 4: literal 3\n"
+        );
+
+        let packet = decode("9C0141080250320F1802104A08");
+        assert_eq!(
+            packet.to_string(),
+            "     0  v4 type 7: equal of 2 packets\n\
+             \x20   22    v2 type 0: sum of 2 packets\n\
+             \x20   40      v2 type 4: literal 1\n\
+             \x20   51      v4 type 4: literal 3\n\
+             \x20   62    v6 type 1: product of 2 packets\n\
+             \x20   80      v0 type 4: literal 2\n\
+             \x20   91      v2 type 4: literal 2\n"
+        );
+    }
+
+    #[test]
+    fn test_infix_expression() {
+        let packet = decode("9C0141080250320F1802104A08");
+        assert_eq!(infix_expression(&packet, false), "1 + 3 == 2 * 2");
+        assert_eq!(
+            infix_expression(&packet, true),
+            "[1 + 3 = 4] == [2 * 2 = 4] = 1"
+        );
+
+        // The example of `infix_expression`'s doc.
+        let (l0, l2, l3, l4, l5, l7) = (
+            literal(0, 0),
+            literal(0, 2),
+            literal(0, 3),
+            literal(0, 4),
+            literal(0, 5),
+            literal(0, 7),
+        );
+        let sum = op(0, OpId::Sum, vec![l2, l7]);
+        let product = op(0, OpId::Product, vec![l5, sum]);
+        let min = op(0, OpId::Min, vec![l3.clone(), product]);
+        let packet = op(0, OpId::Greater, vec![min.clone(), l4.clone()]);
+        assert_eq!(infix_expression(&packet, false), "min(3, 5 * (2 + 7)) > 4");
+        assert_eq!(
+            infix_expression(&packet, true),
+            "[min(3, [5 * [2 + 7 = 9] = 45]) = 3] > 4 = 0"
+        );
+        assert_eq!(eval(&packet), 0);
+
+        // Comparisons and single operands.
+        let nested = op(0, OpId::Less, vec![packet, op(0, OpId::Sum, vec![l4])]);
+        assert_eq!(
+            infix_expression(&nested, false),
+            "(min(3, 5 * (2 + 7)) > 4) < sum(4)"
+        );
+        let sums = op(
+            0,
+            OpId::Product,
+            vec![op(0, OpId::Sum, vec![l0, l3.clone()]), l3],
+        );
+        assert_eq!(infix_expression(&sums, false), "(0 + 3) * 3");
+        assert_eq!(infix_expression(&literal(0, 42), true), "42");
+        assert_eq!(
+            infix_expression(&min, true),
+            "min(3, [5 * [2 + 7 = 9] = 45]) = 3"
+        );
+    }
+
+    #[test]
+    fn test_encode_errors() {
+        assert!(encode_message(&literal(8, 0), LengthType::TotalBits).is_err());
+        let empty_sum = op(0, OpId::Sum, vec![]);
+        assert!(encode_message(&empty_sum, LengthType::TotalBits).is_err());
+        let bad_comparison = op(0, OpId::Equal, vec![literal(0, 1)]);
+        assert!(encode_message(&bad_comparison, LengthType::TotalBits).is_err());
+
+        // Too many sub-packets for length type 1, and too many bits for length type 0.
+        let huge_sum = op(0, OpId::Sum, vec![literal(0, u64::MAX); 2048]);
+        assert!(encode_message(&huge_sum, LengthType::NumPackets).is_err());
+
+        // Either length type is used when the other can't be.
+        let many = op(0, OpId::Sum, vec![literal(0, 1); 2048]);
+        let hex = encode_message(&many, LengthType::NumPackets).unwrap();
+        assert_eq!(decode(&hex), many);
+        let long = op(0, OpId::Product, vec![literal(0, u64::MAX); 400]);
+        let hex = encode_message(&long, LengthType::TotalBits).unwrap();
+        assert_eq!(decode(&hex), long);
+    }
+
+    /// A pseudo-random packet, with sub-packets down to the given depth.
+    fn random_packet(next: &mut impl FnMut() -> u64, depth: u32) -> Packet {
+        let version = (next() % 8) as u8;
+        if depth == 0 || next().is_multiple_of(3) {
+            // Literals of any number of bits.
+            return literal(version, next() >> (next() % 64));
+        }
+        let id = [
+            OpId::Sum,
+            OpId::Product,
+            OpId::Min,
+            OpId::Max,
+            OpId::Greater,
+            OpId::Less,
+            OpId::Equal,
+        ][(next() % 7) as usize];
+        let num_packets = match id {
+            OpId::Greater | OpId::Less | OpId::Equal => 2,
+            _ => 1 + next() % 4,
+        };
+        let packets = (0..num_packets)
+            .map(|_| random_packet(next, depth - 1))
+            .collect();
+        op(version, id, packets)
+    }
+
+    #[test]
+    fn test_random_round_trips() {
+        let mut state = 2021u64;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            state >> 11
+        };
+
+        for i in 0..500 {
+            let packet = random_packet(&mut next, 5);
+            let preferred = if i % 2 == 0 {
+                LengthType::TotalBits
+            } else {
+                LengthType::NumPackets
+            };
+            let hex = encode_message(&packet, preferred).unwrap();
+            assert_eq!(decode(&hex), packet, "{}", hex);
+        }
+    }
+}
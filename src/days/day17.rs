@@ -1,17 +1,34 @@
-fn main() {
-    for test_file in ["2021-12-17.sample.txt", "2021-12-17.txt"] {
-        println!("-------------------- {} --------------------", test_file);
-        let path = advent_of_code::env::get_puzzle_input_path(&test_file);
-        let text = std::fs::read_to_string(&path).unwrap();
-        let target = parse_puzzle_input(&text);
-        let res = search_for_highest_reaching_launch(&target);
-        println!("Part 1: highest y-coord: {}", res.y_peak);
-        println!("Part 2: num feasible shots: {}", res.num_feasible_shots);
+use crate::error::Result;
+use crate::iter::Line;
+use crate::solver::{Answer, Solver};
+
+pub struct Day17;
+
+impl Solver for Day17 {
+    const DAY: u8 = 17;
+    const TITLE: &'static str = "Trick Shot";
+
+    type Input = Target;
+
+    fn parse(&self, text: &str) -> Result<Self::Input> {
+        parse_puzzle_input(text)
+    }
+
+    /// Returns the highest y-coordinate that can be reached.
+    fn part1(&self, target: &Self::Input) -> Answer {
+        search_for_highest_reaching_launch(target).y_peak.to_string()
+    }
+
+    /// Returns the number of initial velocities that reach the target.
+    fn part2(&self, target: &Self::Input) -> Answer {
+        search_for_highest_reaching_launch(target)
+            .num_feasible_shots
+            .to_string()
     }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
-struct Target {
+pub struct Target {
     x_range: std::ops::Range<i64>,
     y_range: std::ops::Range<i64>,
 }
@@ -52,7 +69,6 @@ fn search_for_highest_reaching_launch(target: &Target) -> LaunchResult {
         vx * (vx + 1) / 2
     }
     fn x(vx: i64, s: i64) -> i64 {
-        let s = s as i64;
         if s >= vx {
             x_end(vx)
         } else {
@@ -140,56 +156,35 @@ fn search_for_highest_reaching_launch(target: &Target) -> LaunchResult {
         }
     }
 
-    assert!(feasible_shots.len() > 0);
+    assert!(!feasible_shots.is_empty());
     LaunchResult {
         y_peak: max_y_peak,
         num_feasible_shots: feasible_shots.len(),
     }
 }
 
-fn parse_puzzle_input(text: &str) -> Target {
-    // Parses a number at the begining of the string, and returns the number and what's left of the
-    // string.
-    fn parse_number(s: &mut &str) -> i64 {
-        let mut num_chars = 0;
-        let mut num = 0;
-        let sign = if *s.as_bytes().first().unwrap() == b'-' {
-            *s = &(*s)[1..];
-            -1
-        } else {
-            1
-        };
-        for c in s.chars() {
-            if c.is_ascii_digit() {
-                num_chars += 1;
-                num = num * 10 + (c as u32 - '0' as u32) as i64;
-            } else {
-                break;
-            }
-        }
-        *s = &(*s)[num_chars..];
-        sign * num
+fn parse_puzzle_input(text: &str) -> Result<Target> {
+    // Parses an inclusive range, eg "-10..-5", into an exclusive one.
+    fn parse_range<'a>(line: &Line<'a>, range: &'a str) -> Result<std::ops::Range<i64>> {
+        let (min, max) = line.split_once(range, "..")?;
+        Ok(line.parse::<i64>(min)?..line.parse::<i64>(max)? + 1)
     }
 
-    let mut text = text.strip_prefix("target area: x=").unwrap();
-
-    let xmin = parse_number(&mut text);
-    text = text.strip_prefix("..").unwrap();
-    let xmax = parse_number(&mut text) + 1;
+    let line = crate::iter::numbered_lines(text).next_or_err("the target area")?;
+    let rest = line.strip_prefix(line.text.trim_end(), "target area: x=")?;
+    let (x_range, y_range) = line.split_once(rest, ", y=")?;
 
-    text = text.strip_prefix(", y=").unwrap();
+    let target = Target {
+        x_range: parse_range(&line, x_range)?,
+        y_range: parse_range(&line, y_range)?,
+    };
 
-    let ymin = parse_number(&mut text);
-    text = text.strip_prefix("..").unwrap();
-    let ymax = parse_number(&mut text) + 1;
-
-    text = text.trim_end();
-    assert!(text.is_empty());
-
-    Target {
-        x_range: xmin..xmax,
-        y_range: ymin..ymax,
+    // The search relies on the target being ahead of and below the launch point.
+    if target.x_range.start <= 0 || target.y_range.end >= 0 {
+        return Err(line.error("Target area must be at x > 0 and y < 0"));
     }
+
+    Ok(target)
 }
 
 #[cfg(test)]
@@ -197,7 +192,7 @@ mod tests {
     #[test]
     fn test_parse_puzzle_inpu() {
         let input_string = "target area: x=20..30, y=-10..-5\n";
-        let target = super::parse_puzzle_input(&input_string);
+        let target = super::parse_puzzle_input(input_string).unwrap();
         assert_eq!(
             target,
             super::Target {
@@ -1,34 +1,47 @@
-fn main() {
-    let test_file = advent_of_code::env::get_puzzle_input_path("2021-12-18.txt");
-    let test_lines = std::fs::read_to_string(&test_file).unwrap();
-    let numbers = parse_number_list(&test_lines);
-    let summed = sum(&numbers);
-    println!("Part 1: magnitude of the sum = {}", magnitude(&summed));
-
-    let mut max_magnitude: Option<u64> = None;
-    for (i, a) in numbers.iter().enumerate() {
-        for b in &numbers[i + 1..] {
-            let magnitude_ab = magnitude(&add(a, b));
-            let magnitude_ba = magnitude(&add(b, a));
-            let max = u64::max(magnitude_ab, magnitude_ba);
-
-            if let Some(m) = max_magnitude {
-                max_magnitude = Some(m.max(max));
-            } else {
-                max_magnitude = Some(max);
+use crate::iter::Line;
+use crate::solver::{Answer, Solver};
+
+pub struct Day18;
+
+impl Solver for Day18 {
+    const DAY: u8 = 18;
+    const TITLE: &'static str = "Snailfish";
+
+    type Input = Vec<Number>;
+
+    fn parse(&self, text: &str) -> crate::error::Result<Self::Input> {
+        parse_number_list(text)
+    }
+
+    /// Returns the magnitude of the sum of all numbers.
+    fn part1(&self, numbers: &Self::Input) -> Answer {
+        magnitude(&sum(numbers)).to_string()
+    }
+
+    /// Returns the largest magnitude of the sum of any 2 different numbers.
+    fn part2(&self, numbers: &Self::Input) -> Answer {
+        let mut max_magnitude: Option<u64> = None;
+        for (i, a) in numbers.iter().enumerate() {
+            for b in &numbers[i + 1..] {
+                let magnitude_ab = magnitude(&add(a, b));
+                let magnitude_ba = magnitude(&add(b, a));
+                let max = u64::max(magnitude_ab, magnitude_ba);
+
+                if let Some(m) = max_magnitude {
+                    max_magnitude = Some(m.max(max));
+                } else {
+                    max_magnitude = Some(max);
+                }
             }
         }
-    }
 
-    println!(
-        "Part 2: max magnitude of any 2 additions = {}",
-        max_magnitude.unwrap()
-    );
+        max_magnitude.unwrap().to_string()
+    }
 }
 
 /// A snailfish number, represented as a binary tree.
 #[derive(Clone, Debug, Eq, PartialEq)]
-struct Number {
+pub struct Number {
     nodes: Vec<Node>,
 }
 
@@ -116,12 +129,17 @@ impl std::fmt::Display for Number {
     }
 }
 
-fn parse_number(mut number_str: &str) -> Number {
-    number_str = number_str.trim();
+fn parse_number(line: &Line) -> crate::error::Result<Number> {
+    let mut number_str = line.text.trim();
 
     let mut nodes = Vec::new();
 
-    fn parse_node(nodes: &mut Vec<Node>, s: &mut &str, parent: Option<NodeIdx>) -> NodeIdx {
+    fn parse_node(
+        line: &Line,
+        nodes: &mut Vec<Node>,
+        s: &mut &str,
+        parent: Option<NodeIdx>,
+    ) -> crate::error::Result<NodeIdx> {
         // Make a new node.
         let idx = nodes.len();
         // For now, assume it's a scalar.
@@ -130,42 +148,66 @@ fn parse_number(mut number_str: &str) -> Number {
             val: 0,
         });
 
-        let first = s.chars().next().unwrap();
-        if first.is_ascii_digit() {
-            nodes[idx] = Node::Scalar {
-                parent,
-                val: first.to_digit(10).unwrap() as u64,
-            };
-            *s = &(*s)[1..];
-        } else {
-            *s = s.strip_prefix('[').unwrap();
-            let left = parse_node(nodes, s, Some(idx));
-            *s = s.strip_prefix(',').unwrap();
-            let right = parse_node(nodes, s, Some(idx));
-            *s = s.strip_prefix(']').unwrap();
-            nodes[idx] = Node::Pair {
-                parent,
-                left,
-                right,
-            };
+        // Removes the expected char from the start of `s`.
+        let strip_char = |s: &mut &str, c: char| -> crate::error::Result<()> {
+            *s = s
+                .strip_prefix(c)
+                .ok_or_else(|| line.error_at(s, format!("Expected {:?}", c)))?;
+            Ok(())
+        };
+
+        match s.chars().next() {
+            Some(first) if first.is_ascii_digit() => {
+                nodes[idx] = Node::Scalar {
+                    parent,
+                    val: first.to_digit(10).unwrap() as u64,
+                };
+                *s = &(*s)[1..];
+            }
+            _ => {
+                strip_char(s, '[')?;
+                let left = parse_node(line, nodes, s, Some(idx))?;
+                strip_char(s, ',')?;
+                let right = parse_node(line, nodes, s, Some(idx))?;
+                strip_char(s, ']')?;
+                nodes[idx] = Node::Pair {
+                    parent,
+                    left,
+                    right,
+                };
+            }
         }
 
-        idx
+        Ok(idx)
     }
 
-    let root = parse_node(&mut nodes, &mut number_str, None);
+    let root = parse_node(line, &mut nodes, &mut number_str, None)?;
     assert_eq!(root, ROOT);
-    assert!(number_str.is_empty());
+    if !number_str.is_empty() {
+        return Err(line.error_at(number_str, "Unexpected trailing characters"));
+    }
 
-    Number { nodes }
+    Ok(Number { nodes })
 }
 
-fn parse_number_list(numbers: &str) -> Vec<Number> {
-    numbers.lines().map(parse_number).collect()
+/// Parses at least 2 numbers, so that part 2 has a pair to add.
+fn parse_number_list(numbers: &str) -> crate::error::Result<Vec<Number>> {
+    let list: Vec<Number> = crate::iter::numbered_lines(numbers)
+        .filter(|l| !l.text.trim().is_empty())
+        .map(|l| parse_number(&l))
+        .collect::<crate::error::Result<_>>()?;
+    if list.len() < 2 {
+        return Err(crate::error::Error::parse_at_offset(
+            numbers,
+            numbers.len(),
+            format!("Expected at least 2 numbers, got {}", list.len()),
+        ));
+    }
+    Ok(list)
 }
 
 fn sum(numbers: &[Number]) -> Number {
-    let mut result = match numbers.get(0) {
+    let mut result = match numbers.first() {
         None => return Number::new_scalar(0),
         Some(n) => n.clone(),
     };
@@ -260,7 +302,7 @@ fn explode_pair(num: &mut Number, idx: NodeIdx) {
         v.reverse();
         v
     };
-    assert!(path_to_idx.len() >= 1);
+    assert!(!path_to_idx.is_empty());
     assert_eq!(*path_to_idx.first().unwrap(), ROOT);
 
     // Add left_val to the next scalar on the left.
@@ -413,7 +455,7 @@ fn reduce(mut num: Number) -> Number {
 fn magnitude(num: &Number) -> u64 {
     fn node_magn(num: &Number, idx: NodeIdx) -> u64 {
         match num.node(idx) {
-            Node::Scalar { val, .. } => *val as u64,
+            Node::Scalar { val, .. } => *val,
             Node::Pair { left, right, .. } => {
                 3 * node_magn(num, *left) + 2 * node_magn(num, *right)
             }
@@ -425,11 +467,20 @@ fn magnitude(num: &Number) -> u64 {
 
 #[cfg(test)]
 mod tests {
+    /// Parses a single number, which must be valid.
+    fn parse_number(number_str: &str) -> super::Number {
+        let line = crate::iter::Line {
+            number: 1,
+            text: number_str,
+        };
+        super::parse_number(&line).unwrap()
+    }
+
     #[test]
     fn test_parse() {
         use super::{Node, Number};
         assert_eq!(
-            super::parse_number("7"),
+            parse_number("7"),
             Number {
                 nodes: vec![Node::Scalar {
                     parent: None,
@@ -438,7 +489,7 @@ mod tests {
             }
         );
         assert_eq!(
-            super::parse_number("[3,5]"),
+            parse_number("[3,5]"),
             Number {
                 nodes: vec![
                     Node::Pair {
@@ -458,7 +509,7 @@ mod tests {
             }
         );
         assert_eq!(
-            super::parse_number("[[2,3],5]"),
+            parse_number("[[2,3],5]"),
             Number {
                 nodes: vec![
                     Node::Pair {
@@ -503,7 +554,7 @@ mod tests {
             "[[[[4,2],2],6],[8,7]]",
         ] {
             // Test the round-trip: str -> Number -> str.
-            assert_eq!(input, format!("{}", super::parse_number(input)));
+            assert_eq!(input, format!("{}", parse_number(input)));
         }
     }
 
@@ -627,7 +678,7 @@ mod tests {
             (input_04, result_04),
             (input_05, result_05),
         ] {
-            let numbers = super::parse_number_list(input);
+            let numbers = super::parse_number_list(input).unwrap();
             let summed = super::sum(&numbers);
             let summed_string = summed.to_string();
             assert_eq!(expected, summed_string);
@@ -653,7 +704,7 @@ mod tests {
         ];
 
         for (number_string, expected_magnitude) in number_and_magnitude {
-            let number = super::parse_number(number_string);
+            let number = parse_number(number_string);
             let magnitude = super::magnitude(&number);
             assert_eq!(expected_magnitude, magnitude);
         }
@@ -1,48 +1,27 @@
-fn main() {
-    for test_file in ["2021-12-19.sample.txt", "2021-12-19.txt"] {
-        println!("------------------ {} ------------------", test_file);
-        let test_path = advent_of_code::env::get_puzzle_input_path(test_file);
-        let test_contents = std::fs::read_to_string(&test_path).unwrap();
-        let mut reports = parse_puzzle_input(&test_contents);
-
-        // The set of beacons, with coordinates in the reference frame (the frame of the first
-        // scanner we'll process).
-        let mut beacons_in_ref_frame: std::collections::HashSet<Position> = Default::default();
-
-        // Scanner reports that have been transformed to the reference frame, but have yet to be
-        // matched against `unmatched` reports.
-        let mut scanner_positions = vec![Position::new(0, 0, 0)];
-        let mut processed = vec![reports.pop().unwrap()];
-        let mut unmatched = reports;
-
-        while let Some(report) = processed.pop() {
-            // Add the beacons from this report to the set.
-            beacons_in_ref_frame.extend(report.beacons.iter().cloned());
-
-            // Using `report` as reference, try to match other reports.
-            unmatched.retain(|other| {
-                if let Some((transform, transformed_other, count)) =
-                    find_rotation_and_beacon_matches(&report, other)
-                {
-                    assert!(count >= 12);
-                    scanner_positions.push(Position(transform.translation.0));
-                    // Now that we've warped `other` into the canonical frame, use it in a future
-                    // iteration to find more matches.
-                    processed.push(transformed_other);
-                    false
-                } else {
-                    // No matches, so keep this report here in `unmatched`.
-                    true
-                }
-            });
-        }
+use crate::error::{Error, Result};
+use crate::solver::{Answer, Solver};
 
-        // We should have succeeded in finding all coordinate frames and all beacons.
-        assert!(unmatched.is_empty());
-        println!(
-            "Part 1: number of unique beacons: {}",
-            beacons_in_ref_frame.len()
-        );
+pub struct Day19;
+
+impl Solver for Day19 {
+    const DAY: u8 = 19;
+    const TITLE: &'static str = "Beacon Scanner";
+
+    type Input = Vec<ScannerReport>;
+
+    fn parse(&self, text: &str) -> Result<Self::Input> {
+        parse_puzzle_input(text)
+    }
+
+    /// Returns the number of unique beacons.
+    fn part1(&self, reports: &Self::Input) -> Answer {
+        let (beacons_in_ref_frame, _scanner_positions) = locate_beacons_and_scanners(reports);
+        beacons_in_ref_frame.len().to_string()
+    }
+
+    /// Returns the largest Manhattan distance between any 2 scanners.
+    fn part2(&self, reports: &Self::Input) -> Answer {
+        let (_beacons_in_ref_frame, scanner_positions) = locate_beacons_and_scanners(reports);
 
         // Iterate over all pairs of scanner positions, and find the largest one.
         let mut largest_dist = 0;
@@ -59,12 +38,57 @@ fn main() {
             }
         }
 
-        println!("Part 2: largest Manhattan distance: {}", largest_dist);
+        largest_dist.to_string()
+    }
+}
+
+/// Matches all the scanner reports together, and returns the set of unique beacons as well as the
+/// position of each scanner, in the reference frame (the frame of the first scanner we process).
+fn locate_beacons_and_scanners(
+    reports: &[ScannerReport],
+) -> (std::collections::HashSet<Position>, Vec<Position>) {
+    let mut reports = reports.to_vec();
+
+    // The set of beacons, with coordinates in the reference frame (the frame of the first
+    // scanner we'll process).
+    let mut beacons_in_ref_frame: std::collections::HashSet<Position> = Default::default();
+
+    // Scanner reports that have been transformed to the reference frame, but have yet to be
+    // matched against `unmatched` reports.
+    let mut scanner_positions = vec![Position::new(0, 0, 0)];
+    let mut processed = vec![reports.pop().unwrap()];
+    let mut unmatched = reports;
+
+    while let Some(report) = processed.pop() {
+        // Add the beacons from this report to the set.
+        beacons_in_ref_frame.extend(report.beacons.iter().cloned());
+
+        // Using `report` as reference, try to match other reports.
+        unmatched.retain(|other| {
+            if let Some((transform, transformed_other, count)) =
+                find_rotation_and_beacon_matches(&report, other)
+            {
+                assert!(count >= 12);
+                scanner_positions.push(Position(transform.translation.0));
+                // Now that we've warped `other` into the canonical frame, use it in a future
+                // iteration to find more matches.
+                processed.push(transformed_other);
+                false
+            } else {
+                // No matches, so keep this report here in `unmatched`.
+                true
+            }
+        });
     }
+
+    // We should have succeeded in finding all coordinate frames and all beacons.
+    assert!(unmatched.is_empty());
+
+    (beacons_in_ref_frame, scanner_positions)
 }
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
-struct Position([i32; 3]);
+pub struct Position([i32; 3]);
 
 impl Position {
     fn new(x: i32, y: i32, z: i32) -> Self {
@@ -84,10 +108,8 @@ impl Position {
     }
 }
 
-impl Position {}
-
 #[derive(Clone, Debug, Eq, PartialEq)]
-struct ScannerReport {
+pub struct ScannerReport {
     beacons: Vec<Position>,
 }
 
@@ -259,36 +281,59 @@ fn find_rotation_and_beacon_matches(
     }
 }
 
-fn parse_puzzle_input(text: &str) -> Vec<ScannerReport> {
+fn parse_puzzle_input(text: &str) -> Result<Vec<ScannerReport>> {
     let mut reports = Vec::new();
 
-    for line in text.lines() {
-        let line = line.trim();
+    for l in crate::iter::numbered_lines(text) {
+        let line = l.text.trim();
 
         if line.is_empty() {
             continue;
         }
 
         if let Some(rest) = line.strip_prefix("--- scanner ") {
-            let scanner_idx = rest.strip_suffix(" ---").unwrap().parse::<usize>().unwrap();
-            assert_eq!(scanner_idx, reports.len());
+            let scanner_idx = match rest.strip_suffix(" ---") {
+                Some(idx) => l.parse::<usize>(idx)?,
+                None => return Err(l.error_at(rest, "Expected \" ---\"")),
+            };
+            if scanner_idx != reports.len() {
+                return Err(l.error_at(
+                    rest,
+                    format!("Expected scanner {}, got {}", reports.len(), scanner_idx),
+                ));
+            }
             reports.push(ScannerReport {
                 beacons: Vec::new(),
             });
         } else {
+            let report: &mut ScannerReport = reports
+                .last_mut()
+                .ok_or_else(|| l.error_at(line, "Expected a scanner header"))?;
+
             let mut parts = line.split(',');
-            let relpos = Position([
-                parts.next().unwrap().parse::<i32>().unwrap(),
-                parts.next().unwrap().parse::<i32>().unwrap(),
-                parts.next().unwrap().parse::<i32>().unwrap(),
-            ]);
-            assert_eq!(parts.next(), None);
-
-            reports.last_mut().unwrap().beacons.push(relpos);
+            let mut coords = [0; 3];
+            for coord in &mut coords {
+                let part = parts
+                    .next()
+                    .ok_or_else(|| l.error_at(line, "Expected 3 coordinates"))?;
+                *coord = l.parse::<i32>(part)?;
+            }
+            if let Some(extra) = parts.next() {
+                return Err(l.error_at(extra, "Expected 3 coordinates"));
+            }
+
+            report.beacons.push(Position(coords));
         }
     }
 
-    reports
+    if reports.is_empty() {
+        return Err(Error::parse_at_offset(
+            text,
+            text.len(),
+            "Expected at least 1 scanner report",
+        ));
+    }
+    Ok(reports)
 }
 
 #[cfg(test)]
@@ -314,7 +359,7 @@ mod tests {
                     beacons: vec![Position::new(77, 888, 9999)],
                 },
             ],
-            super::parse_puzzle_input(input)
+            super::parse_puzzle_input(input).unwrap()
         );
     }
 
@@ -322,7 +367,7 @@ mod tests {
     fn rotations_are_valid() {
         for rot in Rotation::all_rotations() {
             // All 3 axes show up.
-            let mut axes = rot.src_axes.clone();
+            let mut axes = rot.src_axes;
             axes.sort();
             assert_eq!(axes, [0, 1, 2]);
 
@@ -0,0 +1,217 @@
+use crate::animation::Format;
+use crate::colormap::Rgb;
+use crate::image::{Image, OffsetImage, SparseGrid};
+use crate::iter::Line;
+use crate::recorder::{ImageRecorder, Recorder};
+use crate::solver::{Answer, Solver};
+use crate::terminal::{frame_delay_from_fps, LiveView};
+
+#[derive(Default)]
+pub struct Day20 {
+    /// If set, an animation of the part 2 enhancements is saved to this path.
+    record_to: Option<(std::path::PathBuf, Format)>,
+    /// If set, the part 2 enhancements are drawn in the terminal, with this delay between steps.
+    watch: Option<std::time::Duration>,
+}
+
+impl Solver for Day20 {
+    const DAY: u8 = 20;
+    const TITLE: &'static str = "Trench Map";
+
+    type Input = (ImageEnhancer, InfiniteImage);
+
+    /// Supported options:
+    /// - `record-to=<path>`: save an animation of the part 2 enhancements, as a GIF or an APNG
+    ///   depending on the extension (`.gif` or `.png`). Lit pixels are white.
+    /// - `watch=<fps>`: draw the part 2 enhancements in the terminal, at this many steps per
+    ///   second.
+    fn configure(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "record-to" => {
+                let path = std::path::PathBuf::from(value);
+                let format = Format::from_path(&path)
+                    .ok_or_else(|| format!("Expected a .gif or .png path, got {:?}", value))?;
+                self.record_to = Some((path, format));
+                Ok(())
+            }
+            "watch" => {
+                self.watch = Some(frame_delay_from_fps(value)?);
+                Ok(())
+            }
+            _ => Err(format!("Day {} has no option named {:?}", Self::DAY, name)),
+        }
+    }
+
+    fn parse(&self, text: &str) -> crate::error::Result<Self::Input> {
+        parse_puzzle_input(text)
+    }
+
+    /// Returns the number of lit pixels after 2 enhancement iterations.
+    fn part1(&self, (enhancer, inf_img): &Self::Input) -> Answer {
+        count_lit_pixels_after(enhancer, inf_img, 2, &mut ()).to_string()
+    }
+
+    /// Returns the number of lit pixels after 50 enhancement iterations.
+    fn part2(&self, (enhancer, inf_img): &Self::Input) -> Answer {
+        let recorder = self.record_to.as_ref().map(|_| {
+            let to_rgb = |&px: &u8| Rgb::new(255 * px, 255 * px, 255 * px);
+            let mut recorder = ImageRecorder::new(to_rgb, 600);
+            recorder.scale = 3;
+            recorder
+        });
+
+        let watcher = self.watch.map(|delay| {
+            let to_rgb = |&px: &u8| Rgb::new(255 * px, 255 * px, 255 * px);
+            LiveView::new(std::io::stdout(), to_rgb, delay)
+        });
+
+        let mut recorders = (recorder, watcher);
+        let num_lit_pixels = count_lit_pixels_after(enhancer, inf_img, 50, &mut recorders);
+        let (recorder, _) = recorders;
+
+        if let (Some(recorder), Some((path, format))) = (recorder, &self.record_to) {
+            let frame_delay = std::time::Duration::from_millis(200);
+            let num_frames = recorder
+                .save(path, *format, frame_delay)
+                .expect("Failed to save animation");
+            println!("Saved an animation of {} frames to {:?}!", num_frames, path);
+        }
+
+        num_lit_pixels.to_string()
+    }
+}
+
+fn count_lit_pixels_after(
+    enhancer: &ImageEnhancer,
+    inf_img: &InfiniteImage,
+    num_iterations: usize,
+    recorder: &mut impl Recorder<u8>,
+) -> usize {
+    // The image grows by 1 pixel on all sides with each iteration. Snapshots show the final area.
+    let margin = num_iterations as isize;
+    let (rows, cols) = inf_img.bbox();
+    let rows = (rows.start - margin)..(rows.end + margin);
+    let cols = (cols.start - margin)..(cols.end + margin);
+
+    let mut inf_img = inf_img.clone();
+    for _ in 0..num_iterations {
+        inf_img = enhancer.do_the_thing(&inf_img);
+
+        if recorder.is_recording() {
+            recorder.record(&Image {
+                height: rows.len(),
+                width: cols.len(),
+                data: rows
+                    .clone()
+                    .flat_map(|row| cols.clone().map(move |col| (row, col)))
+                    .map(|(row, col)| *inf_img.get(row, col))
+                    .collect(),
+            });
+        }
+    }
+    // If the background is lit, then there's infinity many lit pixels!
+    assert_eq!(*inf_img.background(), 0);
+    inf_img.iter().filter(|&(_, _, &p)| p == 1).count()
+}
+
+pub struct ImageEnhancer {
+    // A size 512 array mapping every u9 to 0 or 1.
+    map: Vec<u8>,
+}
+
+/// An infinite image, where only the pixels that differ from the background are stored.
+pub type InfiniteImage = SparseGrid<u8>;
+
+impl ImageEnhancer {
+    fn map(&self, u9: u16) -> u8 {
+        self.map[u9 as usize]
+    }
+
+    fn do_the_thing(&self, img: &InfiniteImage) -> InfiniteImage {
+        // Work on a dense crop of the image. The output crop is expanded by 1 pixel on all sides
+        // compared to the original.
+        let pad_with = *img.background();
+        let img = img.to_image();
+        let mut out_img = img.grow(1, pad_with);
+
+        for out_r in out_img.rows() {
+            for out_c in out_img.cols() {
+                let mut u9 = 0u16;
+
+                // Iterate over the neighborhood.
+                for nr in (out_r - 1)..=(out_r + 1) {
+                    for nc in (out_c - 1)..=(out_c + 1) {
+                        // Pixels out of the image's bounds are padding.
+                        let val = img.get(nr, nc).copied().unwrap_or(pad_with) as u16;
+
+                        assert!(val <= 1);
+                        u9 = (u9 << 1) | val;
+                    }
+                }
+
+                let new_px = self.map(u9);
+                assert!(new_px <= 1);
+                *out_img.pixel_mut(out_r, out_c) = new_px;
+            }
+        }
+
+        let new_pad_with = if pad_with == 0 {
+            self.map(0)
+        } else {
+            self.map(0b1_1111_1111)
+        };
+        SparseGrid::from_image(&out_img, new_pad_with)
+    }
+}
+
+/// Parses a line of '.' (0) and '#' (1) pixels.
+fn parse_pixels(line: &Line, pixels: &str) -> crate::error::Result<Vec<u8>> {
+    pixels
+        .char_indices()
+        .map(|(i, c)| match c {
+            '.' => Ok(0u8),
+            '#' => Ok(1),
+            _ => Err(line.error_at(&pixels[i..], format!("Invalid char: {:?}", c))),
+        })
+        .collect()
+}
+
+fn parse_puzzle_input(text: &str) -> crate::error::Result<(ImageEnhancer, InfiniteImage)> {
+    let mut lines = crate::iter::numbered_lines(text);
+
+    let first_line = lines.next_or_err("the image enhancement algorithm")?;
+    let enhancer_map = parse_pixels(&first_line, first_line.text.trim())?;
+    if enhancer_map.len() != 512 {
+        return Err(first_line.error(format!("Expected 512 pixels, got {}", enhancer_map.len())));
+    }
+    let enhancer = ImageEnhancer { map: enhancer_map };
+
+    let mut height = 0;
+    let mut width = 0;
+    let mut pixels = Vec::new();
+    for l in lines {
+        let line = l.text.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        height += 1;
+        if width == 0 {
+            width = line.len();
+        }
+        if width != line.len() {
+            return Err(l.error(format!("Expected {} columns, got {}", width, line.len())));
+        }
+        pixels.extend(parse_pixels(&l, line)?);
+    }
+    assert_eq!(height * width, pixels.len());
+
+    let img = Image {
+        height,
+        width,
+        data: pixels,
+    };
+    let inf_img = SparseGrid::from_image(&OffsetImage::new(img, (0, 0)), 0);
+
+    Ok((enhancer, inf_img))
+}
@@ -1,8 +1,21 @@
-fn main() {
-    for test_file in ["2021-12-21.sample.txt", "2021-12-21.txt"] {
-        println!("------------------ {} ------------------", test_file);
-        let test_path = advent_of_code::env::get_puzzle_input_path(test_file);
-        let (p1, p2) = parse_puzzle_input(&std::fs::read_to_string(&test_path).unwrap());
+use crate::error::Result;
+use crate::solver::{Answer, Solver};
+
+pub struct Day21;
+
+impl Solver for Day21 {
+    const DAY: u8 = 21;
+    const TITLE: &'static str = "Dirac Dice";
+
+    /// The starting positions of players 1 and 2.
+    type Input = (u32, u32);
+
+    fn parse(&self, text: &str) -> Result<Self::Input> {
+        parse_puzzle_input(text)
+    }
+
+    /// Returns the product of the losing player's score and the number of die rolls.
+    fn part1(&self, &(p1, p2): &Self::Input) -> Answer {
         let dd = DeterministicDie {
             sides: 100,
             next_side_idx: 0,
@@ -10,20 +23,13 @@ fn main() {
 
         let out = play(p1, p2, dd);
         let losing_player_score = out.p1_score.min(out.p2_score);
-        println!(
-            "Part 1: losing score = {}, num rolls = {}, product = {}",
-            losing_player_score,
-            out.num_rolls,
-            losing_player_score * out.num_rolls
-        );
+        (losing_player_score * out.num_rolls).to_string()
+    }
 
+    /// Returns the number of universes in which the player that wins the most wins.
+    fn part2(&self, &(p1, p2): &Self::Input) -> Answer {
         let quantum_out = quantum_play(p1, p2);
-        println!(
-            "Part 2: p1 wins in {} universes, p2 wins in {} universes. Most wins: {}",
-            quantum_out.p1_wins,
-            quantum_out.p2_wins,
-            quantum_out.p1_wins.max(quantum_out.p2_wins)
-        );
+        quantum_out.p1_wins.max(quantum_out.p2_wins).to_string()
     }
 }
 
@@ -182,23 +188,25 @@ fn quantum_play(p1_start: u32, p2_start: u32) -> QuantumOutcome {
     outcome
 }
 
-fn parse_puzzle_input(text: &str) -> (u32, u32) {
-    let mut lines = text.lines();
-    let p1 = lines
-        .next()
-        .unwrap()
-        .strip_prefix("Player 1 starting position: ")
-        .unwrap()
-        .parse::<u32>()
-        .unwrap();
-    let p2 = lines
-        .next()
-        .unwrap()
-        .strip_prefix("Player 2 starting position: ")
-        .unwrap()
-        .parse::<u32>()
-        .unwrap();
-    assert_eq!(lines.next(), None);
-    // We subtract 1 because we use 0-indexed positions.
-    (p1 - 1, p2 - 1)
+fn parse_puzzle_input(text: &str) -> Result<(u32, u32)> {
+    let mut lines = crate::iter::numbered_lines(text);
+
+    let mut parse_position = |player: u32| -> Result<u32> {
+        let line = lines.next_or_err(&format!("player {}'s starting position", player))?;
+        let prefix = format!("Player {} starting position: ", player);
+        let pos_str = line.strip_prefix(line.text.trim_end(), &prefix)?;
+        let pos = line.parse::<u32>(pos_str)?;
+        if !(1..=10).contains(&pos) {
+            return Err(line.error_at(pos_str, "Position must be in [1, 10]"));
+        }
+        // We subtract 1 because we use 0-indexed positions.
+        Ok(pos - 1)
+    };
+    let p1 = parse_position(1)?;
+    let p2 = parse_position(2)?;
+
+    if let Some(line) = lines.find(|l| !l.text.trim().is_empty()) {
+        return Err(line.error("Unexpected trailing line"));
+    }
+    Ok((p1, p2))
 }
@@ -1,27 +1,66 @@
+use crate::image::Volume;
+use crate::iter::Line;
+use crate::solver::{Answer, Solver};
 use std::ops::Range;
 
-fn main() {
-    for test_file in [
-        "2021-12-22.sample_1.txt",
-        "2021-12-22.sample_2.txt",
-        "2021-12-22.txt",
-    ] {
-        println!("------------------ {} ------------------", test_file);
-        let test_path = advent_of_code::env::get_puzzle_input_path(test_file);
-        let steps = parse_puzzle_input(&std::fs::read_to_string(&test_path).unwrap());
-
-        let mut grid = ReactorGridImage::new(State::Off);
-        for step in &steps {
-            grid.set_cuboid_to(&step.xrange, &step.yrange, &step.zrange, step.set_to);
+#[derive(Default)]
+pub struct Day22 {
+    /// If set, the z-slices of the part 1 reactor grid are saved to this dir.
+    log_z_slices_to: Option<std::path::PathBuf>,
+}
+
+impl Solver for Day22 {
+    const DAY: u8 = 22;
+    const TITLE: &'static str = "Reactor Reboot";
+
+    type Input = Vec<Step>;
+
+    /// Supported options:
+    /// - `log-z-slices-to=<dir>`: save each z-slice of the part 1 reactor grid as a PGM image,
+    ///   with the cubes that are on in white.
+    fn configure(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "log-z-slices-to" => {
+                self.log_z_slices_to = Some(value.into());
+                Ok(())
+            }
+            _ => Err(format!("Day {} has no option named {:?}", Self::DAY, name)),
+        }
+    }
+
+    fn parse(&self, text: &str) -> crate::error::Result<Self::Input> {
+        parse_puzzle_input(text)
+    }
+
+    /// Returns the number of cubes that are on in [-50,50]x[-50,50]x[-50,50].
+    fn part1(&self, steps: &Self::Input) -> Answer {
+        let mut grid = Volume::filled([-50..51, -50..51, -50..51], State::Off);
+        for step in steps {
+            let cuboid = [&step.xrange, &step.yrange, &step.zrange]
+                .map(|r| r.start as isize..r.end as isize);
+            grid.fill_cuboid(cuboid, step.set_to);
         }
-        let num_on = grid.data.iter().filter(|&&s| s == State::On).count();
-        println!(
-            "Part 1: number of on cubes in [-50,50]x[-50,50]x[-50,50]: {}",
-            num_on
-        );
 
+        if let Some(dir) = &self.log_z_slices_to {
+            for z in grid.bounds[2].clone() {
+                let path = dir.join(format!("2021-12-22.z_{:+03}.pgm", z));
+                let to_gray = |s: &State| if *s == State::On { 255 } else { 0 };
+                crate::netpbm::save_z_slice_as_pgm(&grid, z, to_gray, &path)
+                    .expect("Failed to save image");
+            }
+        }
+
+        grid.data
+            .iter()
+            .filter(|&&s| s == State::On)
+            .count()
+            .to_string()
+    }
+
+    /// Returns the number of cubes that are on in the full space.
+    fn part2(&self, steps: &Self::Input) -> Answer {
         let mut cuboid_grid = ReactorGridCuboids::new();
-        for step in &steps {
+        for step in steps {
             let area = Cuboid {
                 xrange: step.xrange.clone(),
                 yrange: step.yrange.clone(),
@@ -33,10 +72,7 @@ fn main() {
                 cuboid_grid.turn_off(&area);
             }
         }
-        println!(
-            "Part 2: number of on cubes in the full space: {}",
-            cuboid_grid.count_on()
-        );
+        cuboid_grid.count_on().to_string()
     }
 }
 
@@ -47,53 +83,13 @@ enum State {
 }
 
 #[derive(Clone, Debug)]
-struct Step {
+pub struct Step {
     set_to: State,
     xrange: Range<i32>,
     yrange: Range<i32>,
     zrange: Range<i32>,
 }
 
-/// A dense representation of the reactor grid, limited to the range [-50, 50]^3.
-struct ReactorGridImage {
-    data: Vec<State>,
-}
-
-impl ReactorGridImage {
-    fn new(initial_state: State) -> Self {
-        Self {
-            data: vec![initial_state; 101 * 101 * 101],
-        }
-    }
-
-    fn set_cuboid_to(
-        &mut self,
-        xrange: &Range<i32>,
-        yrange: &Range<i32>,
-        zrange: &Range<i32>,
-        state: State,
-    ) {
-        let xrange = clip_range(xrange, -50, 51);
-        let yrange = clip_range(yrange, -50, 51);
-        let zrange = clip_range(zrange, -50, 51);
-
-        for x in xrange.clone() {
-            let x = (x + 50) as usize;
-            for y in yrange.clone() {
-                let y = (y + 50) as usize;
-                let zstart = (zrange.start + 50) as usize;
-                let zend = (zrange.end + 50) as usize;
-
-                let istart = (x * 101 + y) * 101 + zstart;
-                let iend = istart + (zend - zstart);
-                for px in &mut self.data[istart..iend] {
-                    *px = state;
-                }
-            }
-        }
-    }
-}
-
 #[derive(Clone, Debug)]
 struct Cuboid {
     xrange: Range<i32>,
@@ -268,36 +264,35 @@ fn clip_range(r: &Range<i32>, min: i32, max: i32) -> Range<i32> {
     }
 }
 
-fn parse_puzzle_input(text: &str) -> Vec<Step> {
-    fn parse_inclusive_range(s: &mut &str) -> Range<i32> {
-        let end = s.find(',').unwrap_or(s.len());
-        let mut parts = (*s)[..end].split("..");
+fn parse_puzzle_input(text: &str) -> crate::error::Result<Vec<Step>> {
+    // Parses an inclusive range, eg "-10..12", into an exclusive one.
+    fn parse_inclusive_range<'a>(line: &Line<'a>, s: &'a str) -> crate::error::Result<Range<i32>> {
+        let (start, end) = line.split_once(s, "..")?;
         let r = Range {
-            start: parts.next().unwrap().parse::<i32>().unwrap(),
-            end: parts.next().unwrap().parse::<i32>().unwrap() + 1,
+            start: line.parse::<i32>(start)?,
+            end: line.parse::<i32>(end)? + 1,
         };
-        assert_eq!(parts.next(), None);
-        *s = &(*s)[end..];
-        r
+        if r.is_empty() {
+            return Err(line.error_at(s, "Empty range"));
+        }
+        Ok(r)
     }
 
     let mut steps = Vec::new();
 
-    for line in text.lines() {
-        let (set_to, mut range_str) = if let Some(rest) = line.strip_prefix("on ") {
-            (State::On, rest)
-        } else if let Some(rest) = line.strip_prefix("off ") {
-            (State::Off, rest)
-        } else {
-            panic!("Invalid line: {}", line)
+    for line in crate::iter::numbered_lines(text) {
+        let (set_to, range_str) = match line.text.trim_end().split_once(' ') {
+            Some(("on", rest)) => (State::On, rest),
+            Some(("off", rest)) => (State::Off, rest),
+            _ => return Err(line.error("Expected \"on\" or \"off\"")),
         };
 
-        range_str = range_str.strip_prefix("x=").unwrap();
-        let xrange = parse_inclusive_range(&mut range_str);
-        range_str = range_str.strip_prefix(",y=").unwrap();
-        let yrange = parse_inclusive_range(&mut range_str);
-        range_str = range_str.strip_prefix(",z=").unwrap();
-        let zrange = parse_inclusive_range(&mut range_str);
+        let range_str = line.strip_prefix(range_str, "x=")?;
+        let (x_str, range_str) = line.split_once(range_str, ",y=")?;
+        let (y_str, z_str) = line.split_once(range_str, ",z=")?;
+        let xrange = parse_inclusive_range(&line, x_str)?;
+        let yrange = parse_inclusive_range(&line, y_str)?;
+        let zrange = parse_inclusive_range(&line, z_str)?;
 
         steps.push(Step {
             set_to,
@@ -307,5 +302,5 @@ fn parse_puzzle_input(text: &str) -> Vec<Step> {
         });
     }
 
-    steps
+    Ok(steps)
 }
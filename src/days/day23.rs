@@ -1,53 +1,30 @@
-const SAMPLE_INPUT_PART_1: &str = "\
-#############
-#...........#
-###B#C#B#D###
-  #A#D#C#A#
-  #########";
+use crate::iter::Line;
+use crate::solver::{Answer, Solver};
 
-const SAMPLE_INPUT_PART_2: &str = "\
-#############
-#...........#
-###B#C#B#D###
-  #D#C#B#A#
-  #D#B#A#C#
-  #A#D#C#A#
-  #########";
+pub struct Day23;
 
-const PUZZLE_INPUT_PART_1: &str = "\
-#############
-#...........#
-###B#D#C#A###
-  #C#D#B#A#
-  #########";
+impl Solver for Day23 {
+    const DAY: u8 = 23;
+    const TITLE: &'static str = "Amphipod";
 
-const PUZZLE_INPUT_PART_2: &str = "\
-#############
-#...........#
-###B#D#C#A###
-  #D#C#B#A#
-  #D#B#A#C#
-  #C#D#B#A#
-  #########";
+    type Input = Burrow;
 
-fn main() {
-    for (name, input) in [
-        ("sample (part 1)", SAMPLE_INPUT_PART_1),
-        ("the real deal (part 1)", PUZZLE_INPUT_PART_1),
-        ("sample (part 2)", SAMPLE_INPUT_PART_2),
-        ("the real deal (part 2)", PUZZLE_INPUT_PART_2),
-    ] {
-        println!("---------------------- {} ----------------------", name);
-        let burrow = parse_puzzle_input(input);
-        let (min_energy, states) =
-            find_minimum_energy_shuffling(&burrow).expect("Failed to sort burrow");
-        println!(
-            "Part 1: minimum energy required to properly sort: {}",
-            min_energy
-        );
-        for (i, b) in states.iter().enumerate() {
-            println!("Step {}:\n{}", i, b);
-        }
+    fn parse(&self, text: &str) -> crate::error::Result<Self::Input> {
+        parse_puzzle_input(text)
+    }
+
+    /// Returns the minimum energy required to sort the burrow.
+    fn part1(&self, burrow: &Self::Input) -> Answer {
+        let (min_energy, _states) =
+            find_minimum_energy_shuffling(burrow).expect("Failed to sort burrow");
+        min_energy.to_string()
+    }
+
+    /// Same as part 1, on the unfolded burrow.
+    fn part2(&self, burrow: &Self::Input) -> Answer {
+        let (min_energy, _states) =
+            find_minimum_energy_shuffling(&burrow.unfold()).expect("Failed to sort burrow");
+        min_energy.to_string()
     }
 }
 
@@ -68,7 +45,7 @@ fn main() {
 /// - Room C: 7 + 2 * slots_per_room, ...
 /// - Room D: 7 + 3 * slots_per_room, ...
 #[derive(Clone, Eq, Ord, PartialEq, PartialOrd)]
-struct Burrow {
+pub struct Burrow {
     slots_per_room: u8,
     spaces: Vec<Space>,
 }
@@ -156,6 +133,30 @@ impl Burrow {
         }
         repr
     }
+
+    /// Unfolds the burrow for part 2, by inserting 2 extra rows in the middle of the rooms:
+    /// ```text
+    ///   #D#C#B#A#
+    ///   #D#B#A#C#
+    /// ```
+    fn unfold(&self) -> Self {
+        use Amphipod::*;
+        assert_eq!(self.slots_per_room, 2);
+        const EXTRA_SLOTS: [[Amphipod; 2]; 4] = [[D, D], [C, B], [B, A], [A, C]];
+
+        let mut spaces = self.spaces[HALLWAY_SPACES].to_vec();
+        for (amphi, extra_slots) in [A, B, C, D].iter().zip(EXTRA_SLOTS) {
+            let room = &self.spaces[self.room_range(*amphi)];
+            spaces.push(room[0]);
+            spaces.extend(extra_slots.iter().map(|x| Space::Occupied(*x)));
+            spaces.push(room[1]);
+        }
+
+        Self {
+            slots_per_room: 4,
+            spaces,
+        }
+    }
 }
 
 /// The range of spaces that constitutes the hallway.
@@ -210,18 +211,19 @@ enum Space {
 }
 
 impl Space {
-    fn from_ascii_byte(b: u8) -> Self {
+    fn from_ascii_byte(b: u8) -> Option<Self> {
         use Amphipod::*;
         match b {
-            b'.' => Space::Empty,
-            b'A' => Space::Occupied(A),
-            b'B' => Space::Occupied(B),
-            b'C' => Space::Occupied(C),
-            b'D' => Space::Occupied(D),
-            _ => panic!("Invalid space char: {}", b as char),
+            b'.' => Some(Space::Empty),
+            b'A' => Some(Space::Occupied(A)),
+            b'B' => Some(Space::Occupied(B)),
+            b'C' => Some(Space::Occupied(C)),
+            b'D' => Some(Space::Occupied(D)),
+            _ => None,
         }
     }
 
+    #[cfg(test)]
     fn content(self) -> Option<Amphipod> {
         if let Space::Occupied(x) = self {
             Some(x)
@@ -253,7 +255,7 @@ fn find_minimum_energy_shuffling(burrow: &Burrow) -> Option<(Cost, Vec<Burrow>)>
     use std::cmp::Reverse;
     let mut to_visit = std::collections::BinaryHeap::new();
     to_visit.push(Reverse(State {
-        estimated_total_cost: heuristical_cost_to_finish(&burrow),
+        estimated_total_cost: heuristical_cost_to_finish(burrow),
         cost_so_far: 0,
         burrow: burrow.clone(),
         prev_burrow: None,
@@ -277,7 +279,6 @@ fn find_minimum_energy_shuffling(burrow: &Burrow) -> Option<(Cost, Vec<Burrow>)>
                 bin_repr = prev.binary_repr();
             }
             burrows.reverse();
-            println!("Visited {} states.", visited.len());
             return Some((state.cost_so_far, burrows));
         }
 
@@ -475,7 +476,7 @@ fn steps_along_path(mut from: usize, mut to: usize, slots_per_room: u8) -> Hallw
     let num_steps_hallway = {
         let mut n = 0;
         for idx in (hallway_steps.start + 1)..hallway_steps.end {
-            if 2 <= idx && idx <= 5 {
+            if (2..=5).contains(&idx) {
                 n += 2;
             } else {
                 n += 1;
@@ -506,12 +507,12 @@ fn steps_between_rooms(mut from: usize, mut to: usize) -> u32 {
     }
 
     let mut steps = 0;
-    if from % 2 == 0 {
+    if from.is_multiple_of(2) {
         // from is at the bottom, replace it by the top.
         steps += 1;
         from -= 1;
     }
-    if to % 2 == 0 {
+    if to.is_multiple_of(2) {
         // Same for to.
         steps += 1;
         to -= 1;
@@ -540,8 +541,8 @@ impl std::fmt::Display for Burrow {
         )?;
         let spr = self.slots_per_room as usize;
         for row in 0..spr {
-            let a = self.spaces[7 + row + 0 * spr];
-            let b = self.spaces[7 + row + 1 * spr];
+            let a = self.spaces[7 + row];
+            let b = self.spaces[7 + row + spr];
             let c = self.spaces[7 + row + 2 * spr];
             let d = self.spaces[7 + row + 3 * spr];
             writeln!(f, "  {} {} {} {}", a, b, c, d)?;
@@ -570,63 +571,92 @@ impl std::fmt::Display for Space {
     }
 }
 
-fn parse_puzzle_input(text: &str) -> Burrow {
-    let mut lines = text.lines();
-    assert_eq!(lines.next().unwrap(), "#############");
+fn parse_puzzle_input(text: &str) -> crate::error::Result<Burrow> {
+    let mut lines = crate::iter::numbered_lines(text);
+    let top_wall = lines.next_or_err("the top wall")?;
+    if top_wall.text != "#############" {
+        return Err(top_wall.error("Invalid top wall line"));
+    }
 
-    // For each space, as indexed in `Burrow`, the corresponding char in the input.
+    // For each row of spaces, as indexed in `Burrow`, the line and byte offsets of the
+    // corresponding chars in the input.
     let mut space_rows = Vec::new();
 
-    let hallway = lines.next().unwrap();
-    match hallway.as_bytes() {
-        [b'#', c0, c1, b'.', c2, b'.', c3, b'.', c4, b'.', c5, c6, b'#'] => {
-            space_rows.push(vec![*c0, *c1, *c2, *c3, *c4, *c5, *c6]);
+    let hallway = lines.next_or_err("the hallway")?;
+    match hallway.text.as_bytes() {
+        [b'#', _, _, b'.', _, b'.', _, b'.', _, b'.', _, _, b'#'] => {
+            space_rows.push((hallway, vec![1, 2, 4, 6, 8, 10, 11]));
         }
-        _ => panic!("Invalid hallway line: {}", hallway),
+        _ => return Err(hallway.error("Invalid hallway line")),
     };
 
     loop {
-        let room_line = lines.next().unwrap();
+        let room_line = lines.next_or_err("a room line or the bottom wall")?;
 
-        if let Some(_) = room_line.strip_prefix("  #########") {
+        if room_line.text.starts_with("  #########") {
             // Reached the end of the room slots.
             break;
         }
 
-        match room_line.as_bytes() {
-            [_, _, b'#', a, b'#', b, b'#', c, b'#', d, b'#', ..] => {
-                space_rows.push(vec![*a, *b, *c, *d]);
+        match room_line.text.as_bytes() {
+            [_, _, b'#', _, b'#', _, b'#', _, b'#', _, b'#', ..] => {
+                space_rows.push((room_line, vec![3, 5, 7, 9]));
             }
-            _ => panic!("Invalid room line: {}", room_line),
+            _ => return Err(room_line.error("Invalid room line")),
         };
     }
 
     let slots_per_room = space_rows.len() - 1;
-    assert!(slots_per_room > 0);
-    assert!(slots_per_room < 256);
+    if slots_per_room == 0 || slots_per_room >= 256 {
+        return Err(space_rows[0].0.error(format!(
+            "Invalid number of slots per room: {}",
+            slots_per_room
+        )));
+    }
 
     let mut spaces = Vec::with_capacity(7 + 4 * slots_per_room);
+    let space_at = |(line, offsets): &(Line, Vec<usize>), idx: usize| {
+        let offset = offsets[idx];
+        Space::from_ascii_byte(line.text.as_bytes()[offset])
+            .ok_or_else(|| line.error_at(&line.text[offset..], "Invalid space char"))
+    };
 
     // Fill in the hallway.
-    for b in &space_rows[0] {
-        spaces.push(Space::from_ascii_byte(*b));
+    for idx in 0..7 {
+        spaces.push(space_at(&space_rows[0], idx)?);
     }
 
     // For each room, fill in the slots.
     for room_idx in 0..4 {
-        for slot in 0..slots_per_room {
-            spaces.push(Space::from_ascii_byte(space_rows[1 + slot][room_idx]));
+        for slot_row in &space_rows[1..] {
+            spaces.push(space_at(slot_row, room_idx)?);
         }
     }
 
-    Burrow {
+    Ok(Burrow {
         slots_per_room: slots_per_room as u8,
         spaces,
-    }
+    })
 }
 
 #[cfg(test)]
 mod tests {
+    const SAMPLE_INPUT_PART_1: &str = "\
+#############
+#...........#
+###B#C#B#D###
+  #A#D#C#A#
+  #########";
+
+    const SAMPLE_INPUT_PART_2: &str = "\
+#############
+#...........#
+###B#C#B#D###
+  #D#C#B#A#
+  #D#B#A#C#
+  #A#D#C#A#
+  #########";
+
     // The moves that yield the optimal solution for the part 1 sample board.
     const SAMPLE_OPTIMAL_MOVES: &[(usize, usize)] = &[
         (11, 2),
@@ -646,7 +676,7 @@ mod tests {
     /// Manually replays the optimal solution, to make sure that we estimate its cost correctly.
     #[test]
     fn replay_optimal_sample_solution() {
-        let mut burrow = super::parse_puzzle_input(super::SAMPLE_INPUT_PART_1);
+        let mut burrow = super::parse_puzzle_input(SAMPLE_INPUT_PART_1).unwrap();
         let mut total_cost = 0;
         for &(from, to) in SAMPLE_OPTIMAL_MOVES {
             let num_steps = match super::steps_along_path_with_obstacle_check(&burrow, from, to) {
@@ -674,7 +704,8 @@ mod tests {
 ###A#B#C#D###
   #A#B#C#D#
   #########",
-        );
+        )
+        .unwrap();
 
         let (min_energy, _states) = super::find_minimum_energy_shuffling(&burrow).unwrap();
         assert_eq!(min_energy, 0);
@@ -701,6 +732,13 @@ mod tests {
         assert_eq!(total_cost, 12521);
     }
 
+    #[test]
+    fn unfold_sample() {
+        let burrow = super::parse_puzzle_input(SAMPLE_INPUT_PART_1).unwrap();
+        let expected = super::parse_puzzle_input(SAMPLE_INPUT_PART_2).unwrap();
+        assert_eq!(burrow.unfold(), expected);
+    }
+
     #[test]
     fn steps_along_path() {
         let path = super::steps_along_path(0, super::ROOM_A_TOP + 2, 2);
@@ -0,0 +1,2695 @@
+#![allow(dead_code)]
+
+use crate::iter::Line;
+use crate::solver::{Answer, Solver};
+
+// Make the 4 register names globally available.
+use Register::*;
+
+#[derive(Default)]
+pub struct Day24 {
+    /// If set, print an analysis of the program (optimization passes, and which input digits
+    /// affect which variables).
+    analyze: bool,
+    /// If set, the dataflow graph of the optimized program is saved to this path, in DOT format.
+    save_dot_to: Option<std::path::PathBuf>,
+    /// Whether to group the nodes of the dataflow graph by input digit.
+    dot_clusters: bool,
+    /// If set, all the model numbers starting with these digits are tested.
+    brute_force: Option<Vec<u8>>,
+    /// If set, the brute force search stops after finding this many valid model numbers.
+    brute_force_limit: Option<u64>,
+}
+
+impl Solver for Day24 {
+    const DAY: u8 = 24;
+    const TITLE: &'static str = "Arithmetic Logic Unit";
+
+    type Input = Program;
+
+    /// Supported options:
+    /// - `analyze=<bool>`: print the optimization passes and the input digit dependencies of the
+    ///   program.
+    /// - `save-dot-to=<path>`: save the dataflow graph of the optimized program in the Graphviz
+    ///   DOT format, eg to be rendered with `dot -Tsvg`.
+    /// - `dot-clusters=<bool>`: in the dataflow graph, group the variables by the last input digit
+    ///   they depend on.
+    /// - `brute-force=<digits>`: test all the model numbers starting with these digits, on all the
+    ///   CPUs, printing the valid ones and the progress.
+    /// - `brute-force-limit=<n>`: stop the brute force search after finding n valid model numbers.
+    fn configure(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "analyze" | "dot-clusters" => {
+                let value = value
+                    .parse::<bool>()
+                    .map_err(|err| format!("Invalid value for {}: {}", name, err))?;
+                if name == "analyze" {
+                    self.analyze = value;
+                } else {
+                    self.dot_clusters = value;
+                }
+                Ok(())
+            }
+            "save-dot-to" => {
+                self.save_dot_to = Some(value.into());
+                Ok(())
+            }
+            "brute-force" => {
+                let prefix = value
+                    .chars()
+                    .map(|c| match c.to_digit(10) {
+                        Some(d) if d > 0 => Some(d as u8),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<u8>>>()
+                    .filter(|prefix| prefix.len() <= ModelNumber::zero().0.len())
+                    .ok_or_else(|| format!("Invalid model number prefix {:?}", value))?;
+                self.brute_force = Some(prefix);
+                Ok(())
+            }
+            "brute-force-limit" => {
+                let limit = value
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|&limit| limit > 0)
+                    .ok_or_else(|| format!("Invalid number of model numbers {:?}", value))?;
+                self.brute_force_limit = Some(limit);
+                Ok(())
+            }
+            _ => Err(format!("Day {} has no option named {:?}", Self::DAY, name)),
+        }
+    }
+
+    fn parse(&self, text: &str) -> crate::error::Result<Self::Input> {
+        let program = parse_program(text.lines())?;
+        let num_inputs = program
+            .instructions
+            .iter()
+            .filter(|insn| matches!(insn, Instruction::Input(_)))
+            .count();
+        let num_digits = ModelNumber::zero().0.len();
+        if num_inputs != num_digits {
+            return Err(crate::error::Error::parse_at_offset(
+                text,
+                text.len(),
+                format!(
+                    "Expected {} inp instructions, one per digit, got {}",
+                    num_digits, num_inputs
+                ),
+            ));
+        }
+        Ok(program)
+    }
+
+    /// Returns the highest valid model number.
+    fn part1(&self, program: &Self::Input) -> Answer {
+        if self.analyze {
+            analyze_program(program);
+        }
+        if let Some(path) = &self.save_dot_to {
+            let (sym_prog, _reg_states) = execute_symbolic(program, Z);
+            let mut dot = String::new();
+            format_dot(&optimize(&sym_prog, false), self.dot_clusters, &mut dot).unwrap();
+            std::fs::write(path, dot).expect("Failed to save the dataflow graph");
+        }
+        if let Some(prefix) = &self.brute_force {
+            brute_force_model_numbers(program, prefix, self.brute_force_limit);
+        }
+        model_number_answer(solve_model_number(program, SearchOrder::Highest))
+    }
+
+    /// Returns the lowest valid model number.
+    fn part2(&self, program: &Self::Input) -> Answer {
+        model_number_answer(solve_model_number(program, SearchOrder::Lowest))
+    }
+}
+
+/// Finds the highest or lowest model number accepted by the program, and checks it. Returns None
+/// if the program accepts none.
+fn solve_model_number(program: &Program, order: SearchOrder) -> Option<ModelNumber> {
+    let (sym_prog, _reg_states) = execute_symbolic(program, Z);
+    let sym_prog = simplify(&sym_prog);
+    let model = find_model_number(&sym_prog, order)?;
+    check_model_number(program, &model);
+    Some(model)
+}
+
+fn model_number_answer(model: Option<ModelNumber>) -> Answer {
+    match model {
+        Some(model) => model.to_string(),
+        None => "No valid model number".to_string(),
+    }
+}
+
+/// Tests all the model numbers starting with `prefix` on all the CPUs, printing the valid ones,
+/// and the progress every second. Stops after `limit` valid model numbers, if set.
+fn brute_force_model_numbers(program: &Program, prefix: &[u8], limit: Option<u64>) {
+    let (sym_prog, _reg_states) = execute_symbolic(program, Z);
+    let sym_prog = simplify(&sym_prog);
+    let num_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let prefix_str: String = prefix.iter().map(|d| (b'0' + d) as char).collect();
+    println!(
+        "Testing the model numbers starting with {:?}, on {} threads...",
+        prefix_str, num_threads
+    );
+
+    let mut num_found = 0;
+    let stats = batch_eval(
+        &sym_prog,
+        prefix,
+        num_threads,
+        std::time::Duration::from_secs(1),
+        |stats| {
+            println!(
+                "[{:>5.1}%] Tested {} / {} model numbers, {} valid",
+                100.0 * stats.num_tested as f64 / stats.num_models as f64,
+                stats.num_tested,
+                stats.num_models,
+                stats.num_valid
+            )
+        },
+        |model| {
+            println!("Valid model number: {}", model);
+            num_found += 1;
+            limit.is_none_or(|limit| num_found < limit)
+        },
+    );
+    println!(
+        "Tested {} / {} model numbers, found {} valid{}.",
+        stats.num_tested,
+        stats.num_models,
+        stats.num_valid,
+        if stats.cancelled {
+            " (stopped early)"
+        } else {
+            ""
+        }
+    );
+}
+
+/// Makes sure that the model number is accepted by the program (ie the result in Z is 0), and
+/// returns it.
+fn check_model_number<'a>(program: &Program, model: &'a ModelNumber) -> &'a ModelNumber {
+    let z = program
+        .execute(&model.0, Z)
+        .expect("Failed to execute program");
+    assert_eq!(z, 0, "Model number {} is rejected by the program", model);
+    model
+}
+
+/// Applies the optimization passes which are valid for any program, until none of them changes
+/// anything anymore.
+fn simplify(prog: &SymbolicProgram) -> SymbolicProgram {
+    let mut prog = prog.clone();
+    loop {
+        let opt = optimize_constant_results(&prog);
+        let opt = optimize_common_subexpressions(&opt);
+        let opt = optimize_prune_dead_code(&opt);
+        if opt == prog {
+            return prog;
+        }
+        prog = opt;
+    }
+}
+
+/// Which model number to look for.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum SearchOrder {
+    Highest,
+    Lowest,
+}
+
+/// Finds the highest or lowest model number for which the program's result is 0, if any.
+///
+/// This is a depth-first search which sets one digit at a time, in the search order, and evaluates
+/// the part of the program that comes after reading that digit. Once a digit is set, the rest of
+/// the program only depends on the variables that are still used afterwards (eg just Z, for MONAD
+/// programs): the values of those variables from which no valid model number can be reached are
+/// remembered, so that they're only explored once.
+///
+/// The search also stops early when the ranges of values of the remaining variables show that the
+/// result can't be 0 anymore, eg when Z is too large to be divided back down to 0 by the rest of
+/// a MONAD program.
+fn find_model_number(prog: &SymbolicProgram, order: SearchOrder) -> Option<ModelNumber> {
+    ModelNumberSearch::new(prog, order)?.run()
+}
+
+struct ModelNumberSearch<'a> {
+    prog: &'a SymbolicProgram,
+    /// The digits in the order in which to try them.
+    digits: [u8; 9],
+    /// For each variable, the last digit on which it depends, or None if it depends on none.
+    levels: Vec<Option<usize>>,
+    /// For each digit, the variables to evaluate once it's set, ie those at its level. Empty if
+    /// nothing depends on the digit.
+    segments: Vec<Vec<VarId>>,
+    /// For each digit, the variables at a lower level which are still used from then on.
+    live_vars: Vec<Vec<VarId>>,
+    /// The values of the variables, evaluated up to the current digit.
+    values: Vec<i64>,
+    /// The ranges of values of the variables, given the digits so far, see `can_reach_zero`.
+    ranges: Vec<ValueRange>,
+    /// The (digit index, live variable values) from which no valid model number can be reached.
+    dead_ends: std::collections::HashSet<(usize, Vec<i64>)>,
+    model: ModelNumber,
+}
+
+impl<'a> ModelNumberSearch<'a> {
+    /// Returns None if the program reads more digits than a model number has, or if the
+    /// variables that don't depend on any digit can't be evaluated.
+    fn new(prog: &'a SymbolicProgram, order: SearchOrder) -> Option<Self> {
+        let num_digits = ModelNumber::zero().0.len();
+        let num_vars = prog.num_vars();
+
+        // Group the variables by the last digit they depend on, rather than by where the digits
+        // are read: the optimizations may copy the inputs to later variables. Within a group,
+        // the variables only depend on earlier ones, so they can be evaluated in order.
+        let mut levels: Vec<Option<usize>> = Vec::with_capacity(num_vars);
+        let mut segments = vec![Vec::new(); num_digits];
+        for (var_id, expr) in prog.vars.iter().enumerate() {
+            let level = match expr {
+                SymbolicExpr::Int(_) => None,
+                SymbolicExpr::Input(idx) if *idx < num_digits => Some(*idx),
+                SymbolicExpr::Input(_) => return None,
+                SymbolicExpr::Op(binop) => levels[binop.a].max(levels[binop.b]),
+            };
+            if let Some(idx) = level {
+                segments[idx].push(var_id);
+            }
+            levels.push(level);
+        }
+
+        // The last level at which each variable is used. The result is used at the very end.
+        let mut last_uses: Vec<Option<usize>> = levels.clone();
+        for (var_id, expr) in prog.vars.iter().enumerate() {
+            if let SymbolicExpr::Op(binop) = expr {
+                last_uses[binop.a] = last_uses[binop.a].max(levels[var_id]);
+                last_uses[binop.b] = last_uses[binop.b].max(levels[var_id]);
+            }
+        }
+        if let Some(result_use) = last_uses.last_mut() {
+            *result_use = Some(num_digits);
+        }
+        // The variables which depend on no digit are the same on all paths, so they don't need
+        // to be remembered.
+        let live_vars = (0..num_digits)
+            .map(|idx| {
+                (0..num_vars)
+                    .filter(|&var_id| levels[var_id].is_some_and(|level| level < idx))
+                    .filter(|&var_id| last_uses[var_id] >= Some(idx))
+                    .collect()
+            })
+            .collect();
+
+        let mut digits = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+        if order == SearchOrder::Highest {
+            digits.reverse();
+        }
+
+        let mut search = Self {
+            prog,
+            digits,
+            levels,
+            segments,
+            live_vars,
+            values: vec![0; num_vars],
+            ranges: vec![ValueRange::Unknown; num_vars],
+            dead_ends: Default::default(),
+            model: ModelNumber::zero(),
+        };
+        // Evaluate the variables which don't depend on any digit.
+        let constants: Vec<VarId> = (0..num_vars)
+            .filter(|&var_id| search.levels[var_id].is_none())
+            .collect();
+        if !search.eval_vars(&constants) {
+            return None;
+        }
+        Some(search)
+    }
+
+    fn run(mut self) -> Option<ModelNumber> {
+        if self.search_from(0) {
+            Some(self.model)
+        } else {
+            None
+        }
+    }
+
+    /// Tries all the digits at `idx`, then recursively the following ones. Returns true once a
+    /// valid model number was found, which is then in `self.model`.
+    fn search_from(&mut self, idx: usize) -> bool {
+        if idx == self.segments.len() {
+            return self.values.last() == Some(&0);
+        }
+
+        let state = (
+            idx,
+            self.live_vars[idx]
+                .iter()
+                .map(|&v| self.values[v])
+                .collect(),
+        );
+        if self.dead_ends.contains(&state) {
+            return false;
+        }
+        if !self.can_reach_zero(idx) {
+            self.dead_ends.insert(state);
+            return false;
+        }
+        let segment = std::mem::take(&mut self.segments[idx]);
+        let mut found = false;
+        for digit in self.digits {
+            self.model.0[idx] = digit;
+            if self.eval_vars(&segment) && self.search_from(idx + 1) {
+                found = true;
+                break;
+            }
+        }
+        self.segments[idx] = segment;
+        if !found {
+            self.dead_ends.insert(state);
+        }
+        found
+    }
+
+    /// Returns whether the result may be 0 once the digits from `idx` onwards are set, according
+    /// to the ranges of values of the variables.
+    fn can_reach_zero(&mut self, idx: usize) -> bool {
+        use ValueRange::*;
+
+        for var_id in 0..self.prog.num_vars() {
+            let is_evaluated = self.levels[var_id].is_none_or(|level| level < idx);
+            self.ranges[var_id] = match &self.prog.vars[var_id] {
+                _ if is_evaluated => RangeIncl(self.values[var_id], self.values[var_id]),
+                SymbolicExpr::Op(binop) => {
+                    ValueRange::apply(binop.op, &self.ranges[binop.a], &self.ranges[binop.b])
+                }
+                // An input digit which isn't set yet.
+                _ => RangeIncl(1, 9),
+            };
+        }
+
+        match self.ranges.last() {
+            Some(RangeIncl(s, e)) => *s <= 0 && 0 <= *e,
+            _ => true,
+        }
+    }
+
+    /// Evaluates variables in order, with the digits of `self.model`. Returns false if an
+    /// operation fails, eg a division by zero.
+    fn eval_vars(&mut self, vars: &[VarId]) -> bool {
+        for &var_id in vars {
+            self.values[var_id] = match &self.prog.vars[var_id] {
+                SymbolicExpr::Int(x) => *x,
+                SymbolicExpr::Input(idx) => self.model.0[*idx] as i64,
+                SymbolicExpr::Op(binop) => {
+                    match binop.op.apply(self.values[binop.a], self.values[binop.b]) {
+                        Ok(value) => value,
+                        Err(_) => return false,
+                    }
+                }
+            };
+        }
+        true
+    }
+}
+
+/// Prints the optimization passes applied to the program in SSA form, and then which input digits
+/// affect which variables.
+fn analyze_program(program: &Program) {
+    println!("Converting program to SSA form.");
+    let (sym_prog, _reg_states) = execute_symbolic(program, Z);
+    assert_eq!(program.num_inputs(), sym_prog.num_inputs());
+
+    println!("Optimizing...");
+    let optimized_prog = optimize(&sym_prog, true);
+
+    display_what_inputs_affect_what_vars(&optimized_prog);
+}
+
+/// Applies all the optimization passes, including those specific to MONAD programs, until none of
+/// them changes anything anymore. If `log_passes` is set, prints the effect of each pass.
+fn optimize(sym_prog: &SymbolicProgram, log_passes: bool) -> SymbolicProgram {
+    let opt_pass = |name: &str,
+                    sym_prog: &SymbolicProgram,
+                    opt_pass: &dyn Fn(&SymbolicProgram) -> SymbolicProgram| {
+        let before_vars = sym_prog.num_vars();
+        let before_consts = sym_prog.num_constants();
+        let result = opt_pass(sym_prog);
+        let after_vars = result.num_vars();
+        let after_consts = result.num_constants();
+        if log_passes {
+            println!(
+                "[{:>20}] variables: {} -> {}, constants: {} -> {}",
+                name, before_vars, after_vars, before_consts, after_consts
+            );
+        }
+        result
+    };
+
+    let mut optimized_prog = sym_prog.clone();
+
+    loop {
+        let opt = opt_pass(
+            "constant folding",
+            &optimized_prog,
+            &optimize_constant_results,
+        );
+        let opt = opt_pass("abstract values", &opt, &optimize_based_on_abstract_values);
+        let opt = opt_pass("common subexpr", &opt, &optimize_common_subexpressions);
+        let opt = opt_pass("prune dead code", &opt, &optimize_prune_dead_code);
+        let opt = opt_pass("modulos", &opt, &optimize_modulos);
+        let opt = opt_pass("divisions", &opt, &optimize_divisions);
+
+        if opt == optimized_prog {
+            return optimized_prog;
+        }
+        optimized_prog = opt;
+    }
+}
+
+/// The progress of a batch evaluation.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct BatchStats {
+    /// The number of model numbers to evaluate.
+    num_models: u64,
+    num_tested: u64,
+    num_valid: u64,
+    /// Whether the evaluation was stopped before testing all the model numbers.
+    cancelled: bool,
+}
+
+/// The number of model numbers that a worker evaluates between progress updates, and checks for
+/// cancellation.
+const BATCH_CHUNK_SIZE: u64 = 4096;
+
+/// Evaluates all the model numbers starting with the digits of `prefix`, on `num_threads` threads.
+///
+/// The digits after the prefix are split into jobs of consecutive model numbers sharing a few more
+/// leading digits, which the threads take in order. Each thread evaluates its jobs with its own
+/// `BytecodeRunner`, and sends the valid model numbers to the calling thread, which passes them
+/// to `on_valid` (in no particular order). The evaluation stops early if `on_valid` returns false,
+/// in which case no other valid model number is reported. `on_progress` is called every
+/// `progress_interval`.
+fn batch_eval(
+    sym_prog: &SymbolicProgram,
+    prefix: &[u8],
+    num_threads: usize,
+    progress_interval: std::time::Duration,
+    mut on_progress: impl FnMut(&BatchStats),
+    mut on_valid: impl FnMut(&ModelNumber) -> bool,
+) -> BatchStats {
+    use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+    use std::sync::mpsc::RecvTimeoutError;
+
+    let num_digits = ModelNumber::zero().0.len();
+    assert!(
+        prefix.len() <= num_digits,
+        "Too many digits in {:?}",
+        prefix
+    );
+    let num_threads = num_threads.max(1);
+
+    // Use enough jobs to balance the load between the threads.
+    let num_free_digits = num_digits - prefix.len();
+    let num_job_digits = (0..=num_free_digits)
+        .find(|&n| 9usize.pow(n as u32) >= 16 * num_threads)
+        .unwrap_or(num_free_digits);
+    let num_jobs = 9usize.pow(num_job_digits as u32);
+    let jobs_end = prefix.len() + num_job_digits;
+
+    let bytecode = Bytecode::compile(sym_prog);
+    let next_job = AtomicUsize::new(0);
+    let num_tested = AtomicU64::new(0);
+    let cancelled = AtomicBool::new(false);
+    let mut stats = BatchStats {
+        num_models: 9u64.pow(num_free_digits as u32),
+        ..Default::default()
+    };
+
+    std::thread::scope(|scope| {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        for _ in 0..num_threads {
+            let sender = sender.clone();
+            let (bytecode, next_job) = (&bytecode, &next_job);
+            let (num_tested, cancelled) = (&num_tested, &cancelled);
+            scope.spawn(move || {
+                let mut runner = BytecodeRunner::new(bytecode);
+                let mut num_unreported = 0;
+                while !cancelled.load(Ordering::Relaxed) {
+                    let job = next_job.fetch_add(1, Ordering::Relaxed);
+                    if job >= num_jobs {
+                        break;
+                    }
+
+                    // The job's digits are its index in base 9, followed by 1s.
+                    let mut model = ModelNumber([1; 14]);
+                    model.0[..prefix.len()].copy_from_slice(prefix);
+                    let mut rest = job;
+                    for digit in model.0[prefix.len()..jobs_end].iter_mut().rev() {
+                        *digit = 1 + (rest % 9) as u8;
+                        rest /= 9;
+                    }
+
+                    loop {
+                        // Sending only fails if the calling thread panicked.
+                        if runner.run(&model.0) == Ok(0) && sender.send(model.clone()).is_err() {
+                            return;
+                        }
+                        num_unreported += 1;
+                        if num_unreported == BATCH_CHUNK_SIZE {
+                            num_tested.fetch_add(num_unreported, Ordering::Relaxed);
+                            num_unreported = 0;
+                            if cancelled.load(Ordering::Relaxed) {
+                                return;
+                            }
+                        }
+                        if !model.increment_from(jobs_end) {
+                            break;
+                        }
+                    }
+                }
+                num_tested.fetch_add(num_unreported, Ordering::Relaxed);
+            });
+        }
+        // The channel disconnects once all the threads are done.
+        drop(sender);
+
+        let mut next_progress = std::time::Instant::now() + progress_interval;
+        loop {
+            let timeout = next_progress.saturating_duration_since(std::time::Instant::now());
+            match receiver.recv_timeout(timeout) {
+                Ok(model) => {
+                    if !stats.cancelled {
+                        stats.num_valid += 1;
+                        if !on_valid(&model) {
+                            stats.cancelled = true;
+                            cancelled.store(true, Ordering::Relaxed);
+                        }
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    stats.num_tested = num_tested.load(Ordering::Relaxed);
+                    on_progress(&stats);
+                    next_progress = std::time::Instant::now() + progress_interval;
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    });
+
+    stats.num_tested = num_tested.load(Ordering::Relaxed);
+    stats
+}
+
+/// A register of the compiled bytecode.
+type Reg = u32;
+
+/// A symbolic program compiled to a flat list of instructions on numbered registers, which is much
+/// faster to evaluate over many model numbers than interpreting the program:
+/// - Each variable gets its own register, and the constant operands are resolved at compile time,
+///   either as immediates or as registers set once and for all.
+/// - The instructions are specialized for their operands, eg dividing by a positive immediate
+///   doesn't need to check for 0, and is done with a multiplication (see `Divisor`).
+/// - Evaluating another model number only re-runs the instructions from the first digit that
+///   changed, see `BytecodeRunner`.
+#[derive(Clone, Debug)]
+struct Bytecode {
+    code: Vec<Insn>,
+    /// The initial values of the registers, with the constants already set.
+    initial_regs: Vec<i64>,
+    /// The number of digits that the program reads.
+    num_inputs: usize,
+    /// For each digit, the first instruction that reads it or a later digit: the instructions
+    /// before it only depend on the previous digits. The last one is the end of the code.
+    resume_points: Vec<usize>,
+    /// The register holding the result.
+    result: Reg,
+}
+
+/// A bytecode instruction, storing its result in the `dst` register.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Insn {
+    Input { dst: Reg, idx: u32 },
+    Add { dst: Reg, a: Reg, b: Reg },
+    AddImm { dst: Reg, a: Reg, imm: i64 },
+    Mul { dst: Reg, a: Reg, b: Reg },
+    MulImm { dst: Reg, a: Reg, imm: i64 },
+    Div { dst: Reg, a: Reg, b: Reg },
+    DivImm { dst: Reg, a: Reg, imm: Divisor },
+    Mod { dst: Reg, a: Reg, b: Reg },
+    ModImm { dst: Reg, a: Reg, imm: Divisor },
+    Eql { dst: Reg, a: Reg, b: Reg },
+    EqlImm { dst: Reg, a: Reg, imm: i64 },
+}
+
+impl Bytecode {
+    fn compile(prog: &SymbolicProgram) -> Bytecode {
+        let imm = |var_id: VarId| match prog.vars[var_id] {
+            SymbolicExpr::Int(x) => Some(x),
+            _ => None,
+        };
+
+        let mut code = Vec::new();
+        // Where each digit is first read.
+        let mut first_reads: Vec<Option<usize>> = Vec::new();
+        for (var_id, expr) in prog.vars.iter().enumerate() {
+            let dst = var_id as Reg;
+            let binop = match expr {
+                SymbolicExpr::Int(_) => continue,
+                SymbolicExpr::Input(idx) => {
+                    if first_reads.len() <= *idx {
+                        first_reads.resize(idx + 1, None);
+                    }
+                    first_reads[*idx].get_or_insert(code.len());
+                    code.push(Insn::Input {
+                        dst,
+                        idx: *idx as u32,
+                    });
+                    continue;
+                }
+                SymbolicExpr::Op(binop) => binop,
+            };
+
+            let (a, b) = (binop.a as Reg, binop.b as Reg);
+            // Additions, multiplications and comparisons are commutative, so a constant lhs can
+            // be swapped with the rhs. Divisions and modulos by other constants than positive ones
+            // keep the register forms, which check for failures.
+            let divisor = imm(binop.b).and_then(Divisor::new);
+            let insn = match (binop.op, imm(binop.a), imm(binop.b)) {
+                (Op::Add, _, Some(imm)) => Insn::AddImm { dst, a, imm },
+                (Op::Add, Some(imm), None) => Insn::AddImm { dst, a: b, imm },
+                (Op::Add, None, None) => Insn::Add { dst, a, b },
+                (Op::Mul, _, Some(imm)) => Insn::MulImm { dst, a, imm },
+                (Op::Mul, Some(imm), None) => Insn::MulImm { dst, a: b, imm },
+                (Op::Mul, None, None) => Insn::Mul { dst, a, b },
+                (Op::Div, _, _) => match divisor {
+                    Some(imm) => Insn::DivImm { dst, a, imm },
+                    None => Insn::Div { dst, a, b },
+                },
+                (Op::Mod, _, _) => match divisor {
+                    Some(imm) => Insn::ModImm { dst, a, imm },
+                    None => Insn::Mod { dst, a, b },
+                },
+                (Op::Eql, _, Some(imm)) => Insn::EqlImm { dst, a, imm },
+                (Op::Eql, Some(imm), None) => Insn::EqlImm { dst, a: b, imm },
+                (Op::Eql, None, None) => Insn::Eql { dst, a, b },
+            };
+            code.push(insn);
+        }
+
+        let num_inputs = first_reads.len();
+        let mut resume_points = vec![code.len(); num_inputs + 1];
+        for idx in (0..num_inputs).rev() {
+            resume_points[idx] = first_reads[idx]
+                .unwrap_or(code.len())
+                .min(resume_points[idx + 1]);
+        }
+
+        Bytecode {
+            code,
+            initial_regs: prog
+                .vars
+                .iter()
+                .map(|expr| match expr {
+                    SymbolicExpr::Int(x) => *x,
+                    _ => 0,
+                })
+                .collect(),
+            num_inputs,
+            resume_points,
+            result: (prog.num_vars() - 1) as Reg,
+        }
+    }
+}
+
+impl Insn {
+    #[inline(always)]
+    fn execute(&self, inputs: &[u8], regs: &mut [i64]) -> Result<(), ExecutionError> {
+        let (dst, value) = match *self {
+            Insn::Input { dst, idx } => (dst, inputs[idx as usize] as i64),
+            Insn::Add { dst, a, b } => (dst, regs[a as usize] + regs[b as usize]),
+            Insn::AddImm { dst, a, imm } => (dst, regs[a as usize] + imm),
+            Insn::Mul { dst, a, b } => (dst, regs[a as usize] * regs[b as usize]),
+            Insn::MulImm { dst, a, imm } => (dst, regs[a as usize] * imm),
+            Insn::Div { dst, a, b } => (dst, Op::Div.apply(regs[a as usize], regs[b as usize])?),
+            Insn::DivImm { dst, a, imm } => match regs[a as usize] {
+                x if x < 0 => (dst, x / imm.value),
+                x => (dst, imm.quotient(x)),
+            },
+            Insn::Mod { dst, a, b } => (dst, Op::Mod.apply(regs[a as usize], regs[b as usize])?),
+            Insn::ModImm { dst, a, imm } => match regs[a as usize] {
+                x if x < 0 => return Err(ExecutionError::NegativeModulo),
+                x => (dst, x - imm.quotient(x) * imm.value),
+            },
+            Insn::Eql { dst, a, b } => (dst, (regs[a as usize] == regs[b as usize]) as i64),
+            Insn::EqlImm { dst, a, imm } => (dst, (regs[a as usize] == imm) as i64),
+        };
+        regs[dst as usize] = value;
+        Ok(())
+    }
+}
+
+/// A positive divisor, with which dividing non-negative values is done with a multiplication and a
+/// shift, which is several times faster than a division. See "Division by Invariant Integers using
+/// Multiplication", Granlund and Montgomery, 1994.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Divisor {
+    value: i64,
+    multiplier: u64,
+    shift: u32,
+}
+
+impl Divisor {
+    /// Returns None if the divisor isn't positive, or if the multiplier doesn't fit in 64 bits.
+    fn new(value: i64) -> Option<Divisor> {
+        if value <= 0 {
+            return None;
+        }
+        // With l = ceil(log2(d)) and a multiplier of floor(2^(63 + l) / d) + 1, the quotient is
+        // exact for all the values in 0..2^63.
+        let l = u64::BITS - (value as u64 - 1).leading_zeros();
+        let shift = 63 + l;
+        let multiplier = u64::try_from((1u128 << shift) / value as u128 + 1).ok()?;
+        Some(Divisor {
+            value,
+            multiplier,
+            shift,
+        })
+    }
+
+    /// Returns x / value, for x >= 0.
+    fn quotient(&self, x: i64) -> i64 {
+        ((x as u128 * self.multiplier as u128) >> self.shift) as i64
+    }
+}
+
+/// Evaluates a `Bytecode` for many model numbers, keeping the registers between evaluations so
+/// that only the instructions from the first digit that changed are re-run. This is fastest when
+/// the model numbers are evaluated in order.
+struct BytecodeRunner<'a> {
+    bytecode: &'a Bytecode,
+    regs: Vec<i64>,
+    /// The digits of the previous evaluation.
+    inputs: Vec<u8>,
+    /// The number of instructions whose results are up to date with `inputs`, which is less than
+    /// the whole code before the first evaluation, or if the previous one failed.
+    num_run: usize,
+}
+
+impl<'a> BytecodeRunner<'a> {
+    fn new(bytecode: &'a Bytecode) -> Self {
+        Self {
+            bytecode,
+            regs: bytecode.initial_regs.clone(),
+            inputs: vec![0; bytecode.num_inputs],
+            num_run: 0,
+        }
+    }
+
+    /// Evaluates the program with these digits, extra digits being ignored. Like
+    /// `Program::execute`, this fails if an operation does.
+    fn run(&mut self, inputs: &[u8]) -> Result<i64, ExecutionError> {
+        let inputs = inputs
+            .get(..self.bytecode.num_inputs)
+            .ok_or(ExecutionError::InvalidInput)?;
+
+        let first_changed = self
+            .inputs
+            .iter()
+            .zip(inputs)
+            .position(|(prev, digit)| prev != digit)
+            .unwrap_or(inputs.len());
+        let start = self.bytecode.resume_points[first_changed].min(self.num_run);
+        self.inputs[first_changed..].copy_from_slice(&inputs[first_changed..]);
+
+        for (i, insn) in self.bytecode.code[start..].iter().enumerate() {
+            if let Err(err) = insn.execute(inputs, &mut self.regs) {
+                self.num_run = start + i;
+                return Err(err);
+            }
+        }
+        self.num_run = self.bytecode.code.len();
+        Ok(self.regs[self.bytecode.result as usize])
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Program {
+    instructions: Vec<Instruction>,
+}
+
+/// A single instruction in a program.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Instruction {
+    Input(Register),
+    Op(BinaryOp),
+}
+
+/// A binary operation involving the source & destination register a, and the second operand b
+/// (either a literal or a register).
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct BinaryOp {
+    op: Op,
+    a: Register,
+    b: Value,
+}
+
+/// The 5 types of binary operations supported by the ALU.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Op {
+    Add,
+    Mul,
+    Div,
+    Mod,
+    Eql,
+}
+
+/// A register in the ALU.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Register {
+    W = 0,
+    X = 1,
+    Y = 2,
+    Z = 3,
+}
+
+/// A part of an operation, either a literal integer, or the contents of a register.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Value {
+    Int(i64),
+    Reg(Register),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct RegisterState([i64; 4]);
+
+impl RegisterState {
+    fn new() -> Self {
+        Self([0; 4])
+    }
+
+    fn get(&self, reg: Register) -> i64 {
+        self.0[reg as usize]
+    }
+
+    fn set(&mut self, reg: Register, value: i64) {
+        self.0[reg as usize] = value;
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum ExecutionError {
+    InvalidInput,
+    DivideByZero,
+    NegativeModulo,
+}
+
+impl Program {
+    fn execute<T>(&self, mut inputs: &[T], result_reg: Register) -> Result<i64, ExecutionError>
+    where
+        T: Copy + Into<i64>,
+    {
+        if inputs.len() != self.num_inputs() {
+            return Err(ExecutionError::InvalidInput);
+        }
+
+        let mut state = RegisterState::new();
+
+        for instr in &self.instructions {
+            match instr {
+                Instruction::Input(reg) => {
+                    let (x, rest) = inputs.split_first().unwrap();
+                    state.set(*reg, (*x).into());
+                    inputs = rest;
+                }
+                Instruction::Op(binop) => {
+                    let a = state.get(binop.a);
+                    let b: i64 = match binop.b {
+                        Value::Int(x) => x,
+                        Value::Reg(reg) => state.get(reg),
+                    };
+                    state.set(binop.a, binop.op.apply(a, b)?);
+                }
+            }
+        }
+        Ok(state.get(result_reg))
+    }
+
+    fn execute_with_logging(
+        &self,
+        mut inputs: &[i64],
+    ) -> Result<Vec<RegisterState>, ExecutionError> {
+        if inputs.len() != self.num_inputs() {
+            return Err(ExecutionError::InvalidInput);
+        }
+
+        let mut state = RegisterState::new();
+        let mut all_states = vec![state.clone()];
+
+        for instr in &self.instructions {
+            match instr {
+                Instruction::Input(reg) => {
+                    let (x, rest) = inputs.split_first().unwrap();
+                    state.set(*reg, *x);
+                    inputs = rest;
+                }
+                Instruction::Op(binop) => {
+                    let a = state.get(binop.a);
+                    let b: i64 = match binop.b {
+                        Value::Int(x) => x,
+                        Value::Reg(reg) => state.get(reg),
+                    };
+                    state.set(binop.a, binop.op.apply(a, b)?);
+                }
+            }
+            all_states.push(state.clone());
+        }
+
+        Ok(all_states)
+    }
+
+    /// Returns the number of inputs that the program expects.
+    fn num_inputs(&self) -> usize {
+        self.instructions
+            .iter()
+            .filter(|i| matches!(i, Instruction::Input(_)))
+            .count()
+    }
+}
+
+impl Register {
+    fn from_str(s: &str) -> Option<Register> {
+        match s {
+            "w" => Some(W),
+            "x" => Some(X),
+            "y" => Some(Y),
+            "z" => Some(Z),
+            _ => None,
+        }
+    }
+}
+
+impl Op {
+    fn apply(self, lhs: i64, rhs: i64) -> Result<i64, ExecutionError> {
+        match self {
+            Op::Add => Ok(lhs + rhs),
+            Op::Mul => Ok(lhs * rhs),
+            Op::Div => {
+                if rhs == 0 {
+                    Err(ExecutionError::DivideByZero)
+                } else {
+                    Ok(lhs / rhs)
+                }
+            }
+            Op::Mod => {
+                if lhs < 0 || rhs <= 0 {
+                    Err(ExecutionError::NegativeModulo)
+                } else {
+                    Ok(lhs % rhs)
+                }
+            }
+            Op::Eql => {
+                if lhs == rhs {
+                    Ok(1)
+                } else {
+                    Ok(0)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct ModelNumber([u8; 14]);
+
+impl ModelNumber {
+    fn zero() -> Self {
+        Self([0; 14])
+    }
+
+    fn increment(&mut self) -> bool {
+        self.increment_from(0)
+    }
+
+    /// Increments the model number, only changing the digits from `start` onwards.
+    fn increment_from(&mut self, start: usize) -> bool {
+        for digit in self.0[start..].iter_mut().rev() {
+            if *digit == 9 {
+                *digit = 1;
+            } else {
+                *digit += 1;
+                // Produced a new model number.
+                return true;
+            }
+        }
+
+        // Reached the final model number and looped around.
+        false
+    }
+}
+
+/// A program where all the register assignments are in SSA form.
+/// I.e., instead of "add x 2" mutating "x", it creates a new variable v (a virtual register)
+/// containing the result, and makes the physical register "x" point to v.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct SymbolicProgram {
+    /// The list of variables that the original program defines (implicitly).
+    /// Variables containing binary operations depend on other variables, which creates a dataflow
+    /// graph.
+    /// The last variable in the graph contains the value of the output register (Z).
+    vars: Vec<SymbolicExpr>,
+}
+
+impl SymbolicProgram {
+    fn push_var(&mut self, expr: SymbolicExpr) -> VarId {
+        let var_id = self.vars.len();
+        self.vars.push(expr);
+        var_id
+    }
+
+    /// Returns the number of inputs that the program expects.
+    fn num_inputs(&self) -> usize {
+        let set: std::collections::HashSet<usize> = self
+            .vars
+            .iter()
+            .filter_map(|v| {
+                if let SymbolicExpr::Input(input_idx) = v {
+                    Some(*input_idx)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        set.len()
+    }
+
+    fn num_vars(&self) -> usize {
+        self.vars.len()
+    }
+
+    fn num_constants(&self) -> usize {
+        self.vars
+            .iter()
+            .filter(|expr| matches!(expr, SymbolicExpr::Int(_)))
+            .count()
+    }
+
+    fn substitute_input_value(&self, input_idx: usize, value: i64) -> Self {
+        SymbolicProgram {
+            vars: self
+                .vars
+                .iter()
+                .map(|expr| match expr {
+                    SymbolicExpr::Input(x) if *x == input_idx => SymbolicExpr::Int(value),
+                    _ => expr.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A symbolic program variable, identified by its index in the `vars` vec.
+type VarId = usize;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum SymbolicExpr {
+    Int(i64),
+    Input(usize),
+    Op(SymbolicBinaryOp),
+}
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct SymbolicBinaryOp {
+    op: Op,
+    a: VarId,
+    b: VarId,
+}
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct SymbolicRegisterState([VarId; 4]);
+
+impl SymbolicRegisterState {
+    fn get(&self, reg: Register) -> VarId {
+        self.0[reg as usize]
+    }
+
+    fn set(&mut self, reg: Register, var: VarId) {
+        self.0[reg as usize] = var;
+    }
+}
+
+/// Describes the range of values that a particular variable may take.
+///
+/// Ranges form a lattice, where `Unknown` is the top (any value), and joining two ranges gives the
+/// smallest one containing both.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum ValueRange {
+    /// No information available.
+    Unknown,
+    /// A closed range, start..=end.
+    RangeIncl(i64, i64),
+}
+
+impl ValueRange {
+    /// The smallest range containing both ranges.
+    fn join(&self, other: &ValueRange) -> ValueRange {
+        use ValueRange::*;
+        match (self, other) {
+            (RangeIncl(sa, ea), RangeIncl(sb, eb)) => RangeIncl(*sa.min(sb), *ea.max(eb)),
+            _ => Unknown,
+        }
+    }
+
+    /// The range of the result of an operation, given the ranges of its operands.
+    fn apply(op: Op, range_a: &ValueRange, range_b: &ValueRange) -> ValueRange {
+        use ValueRange::*;
+        match (op, range_a, range_b) {
+            // Exactly known operands => exactly known result, unless the operation fails.
+            (_, RangeIncl(sa, ea), RangeIncl(sb, eb)) if sa == ea && sb == eb => {
+                match op.apply(*sa, *sb) {
+                    Ok(x) => RangeIncl(x, x),
+                    Err(_) => Unknown,
+                }
+            }
+            // Add
+            (Op::Add, Unknown, _) | (Op::Add, _, Unknown) => Unknown,
+            (Op::Add, RangeIncl(sa, ea), RangeIncl(sb, eb)) => {
+                match (sa.checked_add(*sb), ea.checked_add(*eb)) {
+                    (Some(s), Some(e)) => RangeIncl(s, e),
+                    _ => Unknown,
+                }
+            }
+            // Mul
+            (Op::Mul, Unknown, _) | (Op::Mul, _, Unknown) => Unknown,
+            (Op::Mul, RangeIncl(sa, ea), RangeIncl(sb, eb)) => {
+                let products = [
+                    sa.checked_mul(*sb),
+                    sa.checked_mul(*eb),
+                    ea.checked_mul(*sb),
+                    ea.checked_mul(*eb),
+                ];
+                match products.into_iter().collect::<Option<Vec<i64>>>() {
+                    Some(products) => RangeIncl(
+                        *products.iter().min().unwrap(),
+                        *products.iter().max().unwrap(),
+                    ),
+                    None => Unknown,
+                }
+            }
+            // Div
+            // When the sign of the RHS is fixed, the division is monotonic in both operands, so
+            // the result is in the range given by the corners. Dividing by 0 fails, so the
+            // negative and positive parts of the RHS are handled separately.
+            (Op::Div, RangeIncl(sa, ea), RangeIncl(sb, eb)) => {
+                let corners_range = |sb: i64, eb: i64| {
+                    let quotients = [
+                        sa.checked_div(sb),
+                        sa.checked_div(eb),
+                        ea.checked_div(sb),
+                        ea.checked_div(eb),
+                    ];
+                    match quotients.into_iter().collect::<Option<Vec<i64>>>() {
+                        Some(quotients) => RangeIncl(
+                            *quotients.iter().min().unwrap(),
+                            *quotients.iter().max().unwrap(),
+                        ),
+                        None => Unknown,
+                    }
+                };
+                let negative = (*sb < 0).then(|| corners_range(*sb, (*eb).min(-1)));
+                let positive = (*eb > 0).then(|| corners_range((*sb).max(1), *eb));
+                match (negative, positive) {
+                    (Some(negative), Some(positive)) => negative.join(&positive),
+                    (Some(range), None) | (None, Some(range)) => range,
+                    // Always a division by 0.
+                    (None, None) => Unknown,
+                }
+            }
+            (Op::Div, _, _) => Unknown,
+            // Mod
+            // Always a modulo by 0 or a negative number, which fails.
+            (Op::Mod, _, RangeIncl(_, e)) if *e <= 0 => Unknown,
+            (Op::Mod, RangeIncl(_, ea), RangeIncl(_, eb)) => {
+                RangeIncl(0, i64::min((*ea).max(0), *eb - 1))
+            }
+            (Op::Mod, _, RangeIncl(_, e)) => RangeIncl(0, e - 1),
+            (Op::Mod, _, Unknown) => Unknown,
+            // Eql
+            // No overlap between the ranges => never equal.
+            (Op::Eql, RangeIncl(sa, ea), RangeIncl(sb, eb)) if (ea < sb) || (eb < sa) => {
+                RangeIncl(0, 0)
+            }
+            // General case: 0 or 1.
+            (Op::Eql, _, _) => RangeIncl(0, 1),
+        }
+    }
+}
+
+/// Describes the remainder modulo some number of the values that a particular variable may take:
+/// the values are all of the form `rem + k * modulus`. With a power of 2 modulus, this gives the
+/// known low bits of the values.
+///
+/// Congruences form a lattice, where a modulus of 1 is the top (any value), and a modulus of 0
+/// means that the value is exactly `rem`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Congruence {
+    modulus: i64,
+    /// In 0..modulus, unless the modulus is 0.
+    rem: i64,
+}
+
+impl Congruence {
+    const UNKNOWN: Congruence = Congruence { modulus: 1, rem: 0 };
+
+    fn exact(x: i64) -> Congruence {
+        Congruence { modulus: 0, rem: x }
+    }
+
+    /// Normalizes the modulus and the remainder, falling back to `UNKNOWN` if they don't fit in an
+    /// i64. Computations are done in i128 to avoid overflows.
+    fn new(modulus: i128, rem: i128) -> Congruence {
+        let modulus = modulus.abs();
+        let rem = if modulus == 0 {
+            rem
+        } else {
+            rem.rem_euclid(modulus)
+        };
+        match (i64::try_from(modulus), i64::try_from(rem)) {
+            (Ok(modulus), Ok(rem)) => Congruence { modulus, rem },
+            _ => Self::UNKNOWN,
+        }
+    }
+
+    fn exact_value(&self) -> Option<i64> {
+        (self.modulus == 0).then_some(self.rem)
+    }
+
+    /// Whether all the values are multiples of `d`, which must not be 0.
+    fn all_multiples_of(&self, d: i64) -> bool {
+        let d = d as i128;
+        self.modulus as i128 % d == 0 && self.rem as i128 % d == 0
+    }
+
+    /// The smallest congruence containing both congruences.
+    fn join(&self, other: &Congruence) -> Congruence {
+        let (ma, ra) = (self.modulus as i128, self.rem as i128);
+        let (mb, rb) = (other.modulus as i128, other.rem as i128);
+        Congruence::new(gcd(gcd(ma, mb), ra - rb), ra)
+    }
+
+    /// Whether some value is in both congruences.
+    fn intersects(&self, other: &Congruence) -> bool {
+        let (ma, ra) = (self.modulus as i128, self.rem as i128);
+        let (mb, rb) = (other.modulus as i128, other.rem as i128);
+        match gcd(ma, mb) {
+            0 => ra == rb,
+            g => (ra - rb) % g == 0,
+        }
+    }
+}
+
+/// The greatest common divisor, which is non-negative, and 0 only if both numbers are 0.
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// What is known about the values that a particular variable may take, on all the executions of
+/// the program: a range and a congruence, which are kept consistent with each other (eg a range
+/// of 3..=9 of even values is tightened to 4..=8).
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct AbstractValue {
+    range: ValueRange,
+    congruence: Congruence,
+}
+
+impl AbstractValue {
+    fn exact(x: i64) -> AbstractValue {
+        AbstractValue {
+            range: ValueRange::RangeIncl(x, x),
+            congruence: Congruence::exact(x),
+        }
+    }
+
+    /// Inputs are digits in 1-9.
+    fn input() -> AbstractValue {
+        AbstractValue {
+            range: ValueRange::RangeIncl(1, 9),
+            congruence: Congruence::UNKNOWN,
+        }
+    }
+
+    fn exact_value(&self) -> Option<i64> {
+        self.congruence.exact_value()
+    }
+
+    /// Whether all the values are positive or 0.
+    fn is_non_negative(&self) -> bool {
+        matches!(self.range, ValueRange::RangeIncl(s, _) if s >= 0)
+    }
+
+    /// The abstract value of the result of an operation, given those of its operands.
+    fn apply(op: Op, a: &AbstractValue, b: &AbstractValue) -> AbstractValue {
+        let range = ValueRange::apply(op, &a.range, &b.range);
+
+        let (ca, cb) = (a.congruence, b.congruence);
+        let (ma, ra) = (ca.modulus as i128, ca.rem as i128);
+        let (mb, rb) = (cb.modulus as i128, cb.rem as i128);
+        let congruence = match op {
+            Op::Add => Congruence::new(gcd(ma, mb), ra + rb),
+            // (ra + i * ma) * (rb + j * mb) = ra * rb + i * ma * rb + j * mb * ra + i * j * ma * mb
+            Op::Mul => Congruence::new(gcd(gcd(ma * mb, ma * rb), mb * ra), ra * rb),
+            Op::Div => match (ca.exact_value(), cb.exact_value()) {
+                (Some(x), Some(d)) if d != 0 => Congruence::new(0, x as i128 / d as i128),
+                // (ra + k * ma) / d = ra / d + k * (ma / d) when ma is a multiple of d, as long as
+                // the values don't change sign (the division rounds towards 0).
+                (_, Some(d)) if d != 0 && ma % d as i128 == 0 && a.is_non_negative() => {
+                    Congruence::new(ma / d as i128, ra / d as i128)
+                }
+                _ => Congruence::UNKNOWN,
+            },
+            // The modulo by d only removes multiples of d, which doesn't change the remainders
+            // modulo the divisors of d.
+            Op::Mod => match cb.exact_value() {
+                Some(d) if d > 0 => Congruence::new(gcd(ma, d as i128), ra),
+                _ => Congruence::UNKNOWN,
+            },
+            Op::Eql => match (ca.exact_value(), cb.exact_value()) {
+                _ if !ca.intersects(&cb) => Congruence::exact(0),
+                (Some(x), Some(y)) => Congruence::exact((x == y) as i64),
+                _ => Congruence::exact(0).join(&Congruence::exact(1)),
+            },
+        };
+
+        AbstractValue { range, congruence }.reduced()
+    }
+
+    /// Tightens the range to the values allowed by the congruence, and the other way around. If
+    /// they contradict each other, the variable is never successfully computed: it's left as is.
+    fn reduced(self) -> AbstractValue {
+        use ValueRange::*;
+        let (s, e) = match self.range {
+            RangeIncl(s, e) => (s as i128, e as i128),
+            Unknown => return self,
+        };
+        let (m, r) = (self.congruence.modulus as i128, self.congruence.rem as i128);
+
+        // The first and last values of the range with the right remainder.
+        let (s, e) = match m {
+            0 => (r, r),
+            m => (s + (r - s).rem_euclid(m), e - (e - r).rem_euclid(m)),
+        };
+        match self.range {
+            RangeIncl(old_s, old_e) if old_s as i128 <= s && s <= e && e <= old_e as i128 => {
+                AbstractValue {
+                    range: RangeIncl(s as i64, e as i64),
+                    congruence: if s == e {
+                        Congruence::exact(s as i64)
+                    } else {
+                        self.congruence
+                    },
+                }
+            }
+            _ => self,
+        }
+    }
+}
+
+/// Computes what is known about the value of each variable, by propagating the abstract values of
+/// the constants and inputs through the operations, until they don't change anymore.
+///
+/// Since variables only depend on the ones before them, the first pass in order reaches the
+/// fixpoint, and the second one only confirms it.
+fn analyze_values(prog: &SymbolicProgram) -> Vec<AbstractValue> {
+    let unknown = AbstractValue {
+        range: ValueRange::Unknown,
+        congruence: Congruence::UNKNOWN,
+    };
+    let mut values = vec![unknown; prog.num_vars()];
+    loop {
+        let mut changed = false;
+        for (var_id, expr) in prog.vars.iter().enumerate() {
+            let value = match expr {
+                SymbolicExpr::Int(x) => AbstractValue::exact(*x),
+                SymbolicExpr::Input(_) => AbstractValue::input(),
+                SymbolicExpr::Op(binop) => {
+                    AbstractValue::apply(binop.op, &values[binop.a], &values[binop.b])
+                }
+            };
+            if value != values[var_id] {
+                values[var_id] = value;
+                changed = true;
+            }
+        }
+        if !changed {
+            return values;
+        }
+    }
+}
+
+/// Perform symbolic execution of a program, returning it in Static Single Assignment form, as well
+/// as the state of the registers after each instructions.
+fn execute_symbolic(
+    program: &Program,
+    result_reg: Register,
+) -> (SymbolicProgram, Vec<SymbolicRegisterState>) {
+    // Create 4 variables set to 0 for the initial register states.
+    let mut sym_prog = SymbolicProgram {
+        vars: vec![SymbolicExpr::Int(0); 4],
+    };
+    // For each register, which variable it currently points to.
+    let mut states = vec![SymbolicRegisterState([0, 1, 2, 3])];
+    // The next input digit to be read.
+    let mut next_input_idx = 0;
+
+    for instr in &program.instructions {
+        let mut new_state = states.last().unwrap().clone();
+
+        match instr {
+            Instruction::Input(reg) => {
+                let var_id = sym_prog.push_var(SymbolicExpr::Input(next_input_idx));
+                new_state.set(*reg, var_id);
+                next_input_idx += 1;
+            }
+            Instruction::Op(binop) => {
+                let a = new_state.get(binop.a);
+                let b = match binop.b {
+                    Value::Int(x) => sym_prog.push_var(SymbolicExpr::Int(x)),
+                    Value::Reg(reg) => new_state.get(reg),
+                };
+                let result =
+                    sym_prog.push_var(SymbolicExpr::Op(SymbolicBinaryOp { op: binop.op, a, b }));
+                new_state.set(binop.a, result);
+            }
+        }
+
+        states.push(new_state);
+    }
+
+    // The last assigment in our symbolic program must be the one that yields the final state of
+    // the result register. If the program ends with useless instructions, copy the result.
+    let result_var = states.last().unwrap().get(result_reg);
+    if result_var + 1 != sym_prog.vars.len() {
+        let b = sym_prog.push_var(SymbolicExpr::Int(0));
+        sym_prog.push_var(SymbolicExpr::Op(SymbolicBinaryOp {
+            op: Op::Add,
+            a: result_var,
+            b,
+        }));
+    }
+
+    (sym_prog, states)
+}
+
+/// Optimizes a symbolic program by:
+/// - Propagating constants, eg c = op(a, b) when a and b are both known integer values.
+/// - Simplifying operations like adding 0, multiplying by 0, modulo 1, etc.
+fn optimize_constant_results(prog: &SymbolicProgram) -> SymbolicProgram {
+    let mut res = SymbolicProgram { vars: Vec::new() };
+
+    for expr in &prog.vars {
+        let new_expr = if let SymbolicExpr::Op(binop) = expr {
+            simplify_symbolic_op(prog, binop)
+        } else {
+            expr.clone()
+        };
+        res.push_var(new_expr);
+    }
+
+    res
+}
+
+fn simplify_symbolic_op(prog: &SymbolicProgram, binop: &SymbolicBinaryOp) -> SymbolicExpr {
+    let is_scalar = |x: VarId, scalar: i64| -> bool {
+        matches!(prog.vars[x], SymbolicExpr::Int(x) if x == scalar)
+    };
+
+    let unpack_scalars = |a: VarId, b: VarId| -> Option<(i64, i64)> {
+        if let (SymbolicExpr::Int(a), SymbolicExpr::Int(b)) = (&prog.vars[a], &prog.vars[b]) {
+            Some((*a, *b))
+        } else {
+            None
+        }
+    };
+
+    let a = binop.a;
+    let b = binop.b;
+
+    let a_is_zero = is_scalar(a, 0);
+    let b_is_zero = is_scalar(b, 0);
+    let a_is_one = is_scalar(a, 1);
+    let b_is_one = is_scalar(b, 1);
+
+    match binop.op {
+        Op::Add => {
+            if a_is_zero {
+                prog.vars[b].clone()
+            } else if b_is_zero {
+                prog.vars[a].clone()
+            } else if let Some((a, b)) = unpack_scalars(a, b) {
+                SymbolicExpr::Int(a + b)
+            } else {
+                SymbolicExpr::Op(SymbolicBinaryOp { op: Op::Add, a, b })
+            }
+        }
+        Op::Mul => {
+            if a_is_zero || b_is_zero {
+                SymbolicExpr::Int(0)
+            } else if a_is_one {
+                prog.vars[b].clone()
+            } else if b_is_one {
+                prog.vars[a].clone()
+            } else if let Some((a, b)) = unpack_scalars(a, b) {
+                SymbolicExpr::Int(a * b)
+            } else {
+                SymbolicExpr::Op(SymbolicBinaryOp { op: Op::Mul, a, b })
+            }
+        }
+        Op::Div => {
+            if a_is_zero || b_is_one {
+                prog.vars[a].clone()
+            } else if let Some((a, b)) = unpack_scalars(a, b) {
+                SymbolicExpr::Int(a / b)
+            } else {
+                SymbolicExpr::Op(SymbolicBinaryOp { op: Op::Div, a, b })
+            }
+        }
+        Op::Mod => {
+            if a_is_zero {
+                prog.vars[a].clone()
+            } else if b_is_one {
+                SymbolicExpr::Int(0)
+            } else if let Some((a, b)) = unpack_scalars(a, b) {
+                SymbolicExpr::Int(a % b)
+            } else {
+                SymbolicExpr::Op(SymbolicBinaryOp { op: Op::Mod, a, b })
+            }
+        }
+        Op::Eql => {
+            if let Some((a, b)) = unpack_scalars(a, b) {
+                SymbolicExpr::Int((a == b) as i64)
+            } else if a == b {
+                SymbolicExpr::Int(1)
+            } else {
+                SymbolicExpr::Op(SymbolicBinaryOp { op: Op::Eql, a, b })
+            }
+        }
+    }
+}
+
+/// Starting from a result variable `result_var`, backtracks to identify live and dead
+/// code, and removes the dead parts.
+/// Returns:
+/// - A new var id corresponding to the original `result_var`.
+/// - A list of intermediate variables that contribute to this final result.
+fn optimize_prune_dead_code(prog: &SymbolicProgram) -> SymbolicProgram {
+    assert!(!prog.vars.is_empty());
+
+    let result_var = prog.vars.len() - 1;
+    let mut used_vars = vec![false; prog.vars.len()];
+
+    let mut to_visit = Vec::new();
+    // First variable that is used is whichever lives in the result register.
+    to_visit.push(result_var);
+
+    while let Some(var_id) = to_visit.pop() {
+        if used_vars[var_id] {
+            continue;
+        }
+        used_vars[var_id] = true;
+
+        match &prog.vars[var_id] {
+            SymbolicExpr::Op(binop) => {
+                to_visit.push(binop.a);
+                to_visit.push(binop.b);
+            }
+            SymbolicExpr::Int(_) | SymbolicExpr::Input(_) => {}
+        }
+    }
+
+    // A vector mapping new var ids to old ones.
+    let new_to_old: Vec<usize> = used_vars
+        .iter()
+        .copied()
+        .enumerate()
+        .filter_map(|(var_id, is_used)| if is_used { Some(var_id) } else { None })
+        .collect();
+
+    // A vector mapping old var id to Some(new id).
+    let mut old_to_new = vec![None; prog.vars.len()];
+    for (new_id, old_id) in new_to_old.iter().copied().enumerate() {
+        old_to_new[old_id] = Some(new_id);
+    }
+
+    let new_vars: Vec<SymbolicExpr> = prog
+        .vars
+        .iter()
+        .zip(used_vars)
+        .filter_map(|(expr, is_used)| {
+            if !is_used {
+                None
+            } else {
+                let new_expr = match expr {
+                    SymbolicExpr::Int(_) => expr.clone(),
+                    SymbolicExpr::Input(_) => expr.clone(),
+                    SymbolicExpr::Op(binop) => SymbolicExpr::Op(SymbolicBinaryOp {
+                        op: binop.op,
+                        a: old_to_new[binop.a].unwrap(),
+                        b: old_to_new[binop.b].unwrap(),
+                    }),
+                };
+                Some(new_expr)
+            }
+        })
+        .collect();
+
+    assert_eq!(old_to_new[result_var].unwrap(), new_vars.len() - 1);
+
+    SymbolicProgram { vars: new_vars }
+}
+
+/// Optimizes a symbolic program using the abstract values of its variables, by:
+/// - Replacing the variables whose value is known exactly by constants, eg the comparisons which
+///   are always false because the ranges or the congruences of the operands don't intersect.
+/// - Removing the modulos which don't change their operand, eg `x % 26` when x is in 0..=25.
+fn optimize_based_on_abstract_values(prog: &SymbolicProgram) -> SymbolicProgram {
+    let values = analyze_values(prog);
+
+    let new_vars = prog
+        .vars
+        .iter()
+        .zip(values.iter())
+        .map(|(var, value)| {
+            // Integers and inputs don't need to be optimized.
+            let binop = match var {
+                SymbolicExpr::Op(binop) => binop,
+                _ => return var.clone(),
+            };
+            if let Some(x) = value.exact_value() {
+                return SymbolicExpr::Int(x);
+            }
+            let is_modulo_of_smaller_value = binop.op == Op::Mod
+                && matches!(
+                    (&values[binop.a].range, values[binop.b].exact_value()),
+                    (ValueRange::RangeIncl(s, e), Some(d)) if *s >= 0 && *e < d
+                );
+            if is_modulo_of_smaller_value {
+                prog.vars[binop.a].clone()
+            } else {
+                var.clone()
+            }
+        })
+        .collect();
+
+    SymbolicProgram { vars: new_vars }
+}
+
+fn optimize_common_subexpressions(prog: &SymbolicProgram) -> SymbolicProgram {
+    let mut res = SymbolicProgram { vars: Vec::new() };
+
+    let mut bin_op_to_old_id: std::collections::HashMap<&SymbolicBinaryOp, VarId> =
+        Default::default();
+    let mut old_id_to_new_id = vec![None; prog.num_vars()];
+
+    for (old_id, expr) in prog.vars.iter().enumerate() {
+        // The last variable is the result, so it must stay last even if it's a duplicate.
+        let is_result = old_id + 1 == prog.num_vars();
+        let new_id = if let SymbolicExpr::Op(binop) = expr {
+            if let Some(other_old_id) = bin_op_to_old_id.get(binop).filter(|_| !is_result) {
+                // We have already seen an identical binary op. Just reuse the corresponding
+                // new_id.
+                old_id_to_new_id[*other_old_id].unwrap()
+            } else {
+                // First time seeing this binary op. Record the op, and create a new `new_id`.
+                bin_op_to_old_id.insert(binop, old_id);
+                res.push_var(SymbolicExpr::Op(SymbolicBinaryOp {
+                    op: binop.op,
+                    a: old_id_to_new_id[binop.a].unwrap(),
+                    b: old_id_to_new_id[binop.b].unwrap(),
+                }))
+            }
+        } else {
+            res.push_var(expr.clone())
+        };
+
+        old_id_to_new_id[old_id] = Some(new_id);
+    }
+
+    res
+}
+
+/// Removes the multiples of the divisor from the additions in modulos:
+/// (a + b) % d   =>   b % d, when a is a multiple of d, eg a = c * 26 and d = 26.
+///
+/// The operands of the addition must be positive or 0, for both modulos to succeed.
+fn optimize_modulos(prog: &SymbolicProgram) -> SymbolicProgram {
+    let values = analyze_values(prog);
+    let mut res = prog.clone();
+
+    for (i, expr) in prog.vars.iter().enumerate() {
+        // Only look at modulos by a positive constant.
+        let binop = match expr {
+            SymbolicExpr::Op(binop) if binop.op == Op::Mod => binop,
+            _ => continue,
+        };
+        let mod_by = match values[binop.b].exact_value() {
+            Some(m) if m > 0 => m,
+            _ => continue,
+        };
+
+        // Where the lhs is an addition of non-negative values.
+        let (add_a, add_b) = match prog.vars[binop.a] {
+            SymbolicExpr::Op(SymbolicBinaryOp { op: Op::Add, a, b }) => (a, b),
+            _ => continue,
+        };
+        if !values[add_a].is_non_negative() || !values[add_b].is_non_negative() {
+            continue;
+        }
+
+        // One of which is a multiple of `mod_by`.
+        let remaining = if values[add_a].congruence.all_multiples_of(mod_by) {
+            add_b
+        } else if values[add_b].congruence.all_multiples_of(mod_by) {
+            add_a
+        } else {
+            continue;
+        };
+        res.vars[i] = SymbolicExpr::Op(SymbolicBinaryOp {
+            op: Op::Mod,
+            a: remaining,
+            b: binop.b,
+        });
+    }
+
+    res
+}
+
+/// Splits the divisions of additions where one side is a multiplication by the divisor:
+/// (a * 26 + b) / 26 => a + b / 26
+///
+/// The operands of the addition must be positive or 0, since the division rounds towards 0, and
+/// the divisor positive. If b is in 0..26, the division then gets folded away.
+fn optimize_divisions(prog: &SymbolicProgram) -> SymbolicProgram {
+    let values = analyze_values(prog);
+    let mut res = SymbolicProgram { vars: Vec::new() };
+    let mut old_id_to_new_id = vec![0; prog.num_vars()];
+
+    let push_new_op = |new_prog: &mut SymbolicProgram,
+                       old_id_to_new_id: &mut [VarId],
+                       old_id: VarId,
+                       old_expr: &SymbolicExpr| {
+        let new_expr = match old_expr {
+            SymbolicExpr::Op(binop) => SymbolicExpr::Op(SymbolicBinaryOp {
+                op: binop.op,
+                a: old_id_to_new_id[binop.a],
+                b: old_id_to_new_id[binop.b],
+            }),
+            _ => old_expr.clone(),
+        };
+        let new_id = new_prog.push_var(new_expr);
+        old_id_to_new_id[old_id] = new_id;
+    };
+
+    let is_mul_by_x = |var_id: VarId, x: i64| -> Option<VarId> {
+        match prog.vars[var_id] {
+            SymbolicExpr::Op(SymbolicBinaryOp { op: Op::Mul, a, b }) => {
+                if prog.vars[a] == SymbolicExpr::Int(x) {
+                    Some(b)
+                } else if prog.vars[b] == SymbolicExpr::Int(x) {
+                    Some(a)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    };
+
+    for (i, expr) in prog.vars.iter().enumerate() {
+        // Only look at binary ops.
+        let binop = if let SymbolicExpr::Op(binop) = expr {
+            binop
+        } else {
+            push_new_op(&mut res, &mut old_id_to_new_id, i, expr);
+            continue;
+        };
+
+        // Where the op is a division.
+        if binop.op != Op::Div {
+            push_new_op(&mut res, &mut old_id_to_new_id, i, expr);
+            continue;
+        }
+        let lhs = &prog.vars[binop.a];
+        let rhs = &prog.vars[binop.b];
+
+        // Where the rhs is a positive constant.
+        let div_by = match rhs {
+            SymbolicExpr::Int(d) if *d > 0 => *d,
+            _ => {
+                push_new_op(&mut res, &mut old_id_to_new_id, i, expr);
+                continue;
+            }
+        };
+
+        // And the lhs is an addition of non-negative values.
+        let (add_a, add_b) = match lhs {
+            SymbolicExpr::Op(SymbolicBinaryOp { op: Op::Add, a, b })
+                if values[*a].is_non_negative() && values[*b].is_non_negative() =>
+            {
+                (*a, *b)
+            }
+            _ => {
+                push_new_op(&mut res, &mut old_id_to_new_id, i, expr);
+                continue;
+            }
+        };
+
+        // One side of the addition is a multiplication by `div_by`.
+        let (stuff_multiplied_by_div_by, add_b) = if let Some(stuff) = is_mul_by_x(add_a, div_by) {
+            (stuff, add_b)
+        } else if let Some(stuff) = is_mul_by_x(add_b, div_by) {
+            (stuff, add_a)
+        } else {
+            push_new_op(&mut res, &mut old_id_to_new_id, i, expr);
+            continue;
+        };
+        // The new variable is:
+        //      add_a + add_b / div_by
+        // This must be done in 2 assignments.
+        let add_b_div_by = res.push_var(SymbolicExpr::Op(SymbolicBinaryOp {
+            op: Op::Div,
+            a: old_id_to_new_id[add_b],
+            b: old_id_to_new_id[binop.b],
+        }));
+        let new_i = res.push_var(SymbolicExpr::Op(SymbolicBinaryOp {
+            op: Op::Add,
+            a: old_id_to_new_id[stuff_multiplied_by_div_by],
+            b: add_b_div_by,
+        }));
+        old_id_to_new_id[i] = new_i;
+    }
+
+    res
+}
+
+/// Displays the program, and at the same time, keeps track of which input values are affecting the
+/// contents of each variable.
+fn display_what_inputs_affect_what_vars(prog: &SymbolicProgram) {
+    let num_inputs = 14;
+    let mut affected_by = vec![u32::MAX; prog.num_vars()];
+    // Represent each variable as a fully expanded version, depending only on constants, inputs and
+    // binary ops on them.
+    let mut fully_expanded_assignments = vec![String::new(); prog.num_vars()];
+
+    for (i, expr) in prog.vars.iter().enumerate() {
+        let bits = match expr {
+            SymbolicExpr::Int(_) => 0,
+            SymbolicExpr::Input(input_idx) => 1u32 << (num_inputs - 1 - input_idx),
+            SymbolicExpr::Op(binop) => {
+                let a_bits = affected_by[binop.a];
+                let b_bits = affected_by[binop.b];
+                a_bits | b_bits
+            }
+        };
+        affected_by[i] = bits;
+    }
+
+    println!("Which vars depend on which input digits?");
+    for (i, (expr, bits)) in prog.vars.iter().zip(affected_by.iter()).enumerate() {
+        let lhs = format!("v{}", i);
+        let rhs = match expr {
+            SymbolicExpr::Int(x) => x.to_string(),
+            SymbolicExpr::Input(input_idx) => format!("in[{}]", input_idx),
+            SymbolicExpr::Op(binop) => format!("v{} {} v{}", binop.a, binop.op, binop.b),
+        };
+        let affected_by_str: String = (0..num_inputs)
+            .map(|rev_idx| (bits >> (num_inputs - 1 - rev_idx)) & 1)
+            .map(|is_on| if is_on == 1 { '#' } else { '.' })
+            .collect();
+        let fully_expanded = match expr {
+            SymbolicExpr::Int(x) => x.to_string(),
+            SymbolicExpr::Input(input_idx) => format!("in[{}]", input_idx),
+            SymbolicExpr::Op(binop) => {
+                let a_is_binop = matches!(prog.vars[binop.a], SymbolicExpr::Op(_));
+                let b_is_binop = matches!(prog.vars[binop.b], SymbolicExpr::Op(_));
+                let a = &fully_expanded_assignments[binop.a];
+                let b = &fully_expanded_assignments[binop.b];
+
+                let a_str = if a.len() > 150 {
+                    "...".into()
+                } else if a_is_binop {
+                    format!("({})", a)
+                } else {
+                    a.into()
+                };
+                let b_str = if b.len() > 150 {
+                    "...".into()
+                } else if b_is_binop {
+                    format!("({})", b)
+                } else {
+                    b.into()
+                };
+                format!("{} {} {}", a_str, binop.op, b_str)
+            }
+        };
+        println!(
+            "| {:>4} = {:<16} {:<14}  {}",
+            lhs, rhs, affected_by_str, fully_expanded
+        );
+        fully_expanded_assignments[i] = fully_expanded;
+    }
+    println!("+---");
+}
+
+impl std::fmt::Display for Register {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let str = match self {
+            W => "W",
+            X => "X",
+            Y => "Y",
+            Z => "Z",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+impl std::fmt::Display for Op {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let str = match self {
+            Op::Add => "+",
+            Op::Mul => "*",
+            Op::Div => "/",
+            Op::Mod => "%",
+            Op::Eql => "==",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+impl std::fmt::Display for ModelNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        for digit in self.0.iter() {
+            write!(f, "{}", *digit)?;
+        }
+        Ok(())
+    }
+}
+
+fn format_vars(vars: &[SymbolicExpr], mut f: impl std::fmt::Write) -> Result<(), std::fmt::Error> {
+    writeln!(f, "+--- Variables")?;
+    for (i, v) in vars.iter().enumerate() {
+        write!(f, "| v{} = ", i)?;
+        match v {
+            SymbolicExpr::Int(x) => writeln!(f, "{}", x)?,
+            SymbolicExpr::Input(x) => writeln!(f, "input[{}]", x)?,
+            SymbolicExpr::Op(binop) => writeln!(f, "v{} {} v{}", binop.a, binop.op, binop.b)?,
+        }
+    }
+    writeln!(f, "+---")?;
+
+    Ok(())
+}
+
+/// Writes the dataflow graph of the program in the Graphviz DOT format: the inputs, constants and
+/// operations are nodes, with edges from the operands to the operations, and from the result to a
+/// final `z` node. The edges of the right operands of divisions and modulos are dashed.
+///
+/// With `cluster_by_digit`, the variables are grouped by the last input digit they depend on, ie
+/// by the digit after which they can be computed. Constants go with their first user.
+fn format_dot(
+    prog: &SymbolicProgram,
+    cluster_by_digit: bool,
+    mut f: impl std::fmt::Write,
+) -> Result<(), std::fmt::Error> {
+    let mut clusters: Vec<Option<usize>> = vec![None; prog.num_vars()];
+    if cluster_by_digit {
+        for (var_id, expr) in prog.vars.iter().enumerate() {
+            match expr {
+                SymbolicExpr::Int(_) => {}
+                SymbolicExpr::Input(idx) => clusters[var_id] = Some(*idx),
+                SymbolicExpr::Op(binop) => {
+                    let cluster = clusters[binop.a].max(clusters[binop.b]);
+                    for operand in [binop.a, binop.b] {
+                        if matches!(prog.vars[operand], SymbolicExpr::Int(_)) {
+                            clusters[operand] = clusters[operand].or(cluster);
+                        }
+                    }
+                    clusters[var_id] = cluster;
+                }
+            }
+        }
+    }
+    let num_clusters = clusters.iter().flatten().max().map_or(0, |idx| idx + 1);
+
+    let format_node = |f: &mut dyn std::fmt::Write, var_id: VarId| match &prog.vars[var_id] {
+        SymbolicExpr::Int(x) => writeln!(f, "    v{} [label=\"{}\", shape=plaintext];", var_id, x),
+        SymbolicExpr::Input(idx) => writeln!(
+            f,
+            "    v{} [label=\"in[{}]\", shape=box, style=filled, fillcolor=lightblue];",
+            var_id, idx
+        ),
+        SymbolicExpr::Op(binop) => writeln!(f, "    v{} [label=\"{}\"];", var_id, binop.op),
+    };
+
+    writeln!(f, "digraph program {{")?;
+    writeln!(f, "    node [fontname=monospace];")?;
+    for digit in 0..num_clusters {
+        writeln!(f, "    subgraph cluster_digit_{} {{", digit)?;
+        writeln!(f, "        label=\"digit {}\";", digit)?;
+        for var_id in (0..prog.num_vars()).filter(|&v| clusters[v] == Some(digit)) {
+            write!(f, "    ")?;
+            format_node(&mut f, var_id)?;
+        }
+        writeln!(f, "    }}")?;
+    }
+    for var_id in (0..prog.num_vars()).filter(|&v| clusters[v].is_none()) {
+        format_node(&mut f, var_id)?;
+    }
+
+    for (var_id, expr) in prog.vars.iter().enumerate() {
+        if let SymbolicExpr::Op(binop) = expr {
+            writeln!(f, "    v{} -> v{};", binop.a, var_id)?;
+            if matches!(binop.op, Op::Div | Op::Mod) {
+                writeln!(f, "    v{} -> v{} [style=dashed];", binop.b, var_id)?;
+            } else {
+                writeln!(f, "    v{} -> v{};", binop.b, var_id)?;
+            }
+        }
+    }
+    writeln!(f, "    z [shape=doublecircle];")?;
+    writeln!(f, "    v{} -> z;", prog.num_vars() - 1)?;
+    writeln!(f, "}}")
+}
+
+impl std::fmt::Display for SymbolicProgram {
+    fn fmt(&self, mut f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        writeln!(f, "SymbolicProgram")?;
+        format_vars(&self.vars, &mut f)?;
+        Ok(())
+    }
+}
+
+fn parse_program<Iter, Item>(lines: Iter) -> crate::error::Result<Program>
+where
+    Iter: Iterator<Item = Item>,
+    Item: AsRef<str>,
+{
+    let mut instructions = Vec::new();
+    for (line_idx, text) in lines.enumerate() {
+        let line = Line {
+            number: line_idx + 1,
+            text: text.as_ref(),
+        };
+        let parse_register = |s: &str| {
+            Register::from_str(s)
+                .ok_or_else(|| line.error_at(s, format!("Invalid register: {:?}", s)))
+        };
+        let mut parts = line.text.split_whitespace();
+
+        let op = match parts.next() {
+            None => continue,
+            Some(op) => op,
+        };
+        let mut next_operand = || {
+            parts
+                .next()
+                .ok_or_else(|| line.error_at(op, format!("Missing operand for {:?}", op)))
+        };
+
+        if op == "inp" {
+            instructions.push(Instruction::Input(parse_register(next_operand()?)?));
+        } else {
+            let a = next_operand()?;
+            let b = next_operand()?;
+
+            let op = match op {
+                "add" => Op::Add,
+                "mul" => Op::Mul,
+                "div" => Op::Div,
+                "mod" => Op::Mod,
+                "eql" => Op::Eql,
+                _ => return Err(line.error_at(op, format!("Invalid op: {:?}", op))),
+            };
+
+            let a = parse_register(a)?;
+
+            let b_first = b.chars().next().unwrap();
+            let b = if b_first.is_ascii_digit() || b_first == '-' {
+                Value::Int(line.parse::<i64>(b)?)
+            } else {
+                Value::Reg(parse_register(b)?)
+            };
+
+            instructions.push(Instruction::Op(BinaryOp { op, a, b }));
+        }
+
+        if let Some(extra) = parts.next() {
+            return Err(line.error_at(extra, "Unexpected trailing operand"));
+        }
+    }
+
+    Ok(Program { instructions })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AbstractValue, BatchStats, BinaryOp, Bytecode, BytecodeRunner, Congruence, Divisor,
+        ExecutionError, Instruction, ModelNumber, Op, Program, Register::*, RegisterState,
+        SearchOrder, SymbolicBinaryOp, SymbolicExpr, Value, ValueRange::*,
+    };
+
+    #[test]
+    fn parse_program() {
+        let program = super::parse_program("inp x\nmul x -1".lines()).unwrap();
+        assert_eq!(
+            program.instructions,
+            vec![
+                Instruction::Input(X),
+                Instruction::Op(BinaryOp {
+                    op: Op::Mul,
+                    a: X,
+                    b: Value::Int(-1),
+                }),
+            ]
+        );
+
+        let program_str = "\
+                           inp w
+                           add x w
+                           mod y 123
+                               div w -123
+                                   eql w z";
+        let program = super::parse_program(program_str.lines()).unwrap();
+        assert_eq!(
+            program.instructions,
+            vec![
+                Instruction::Input(W),
+                Instruction::Op(BinaryOp {
+                    op: Op::Add,
+                    a: X,
+                    b: Value::Reg(W),
+                }),
+                Instruction::Op(BinaryOp {
+                    op: Op::Mod,
+                    a: Y,
+                    b: Value::Int(123),
+                }),
+                Instruction::Op(BinaryOp {
+                    op: Op::Div,
+                    a: W,
+                    b: Value::Int(-123),
+                }),
+                Instruction::Op(BinaryOp {
+                    op: Op::Eql,
+                    a: W,
+                    b: Value::Reg(Z),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn execute_program() {
+        let prog_mul_minus_1 = super::parse_program("inp x\nmul x -1".lines()).unwrap();
+        assert_eq!(
+            prog_mul_minus_1.execute_with_logging(&[]),
+            Err(ExecutionError::InvalidInput)
+        );
+        assert_eq!(
+            prog_mul_minus_1
+                .execute_with_logging(&[123])
+                .unwrap()
+                .last()
+                .unwrap(),
+            &RegisterState([0, -123, 0, 0])
+        );
+
+        let prog_is_mul_by_3 =
+            super::parse_program("inp z\ninp x\nmul z 3\neql z x".lines()).unwrap();
+        assert_eq!(
+            prog_is_mul_by_3
+                .execute_with_logging(&[22, 66])
+                .unwrap()
+                .last()
+                .unwrap(),
+            &RegisterState([0, 66, 0, 1])
+        );
+        assert_eq!(
+            prog_is_mul_by_3
+                .execute_with_logging(&[22, 65])
+                .unwrap()
+                .last()
+                .unwrap(),
+            &RegisterState([0, 65, 0, 0])
+        );
+
+        let prog_decompose_into_bits = super::parse_program(
+            "\
+            inp w
+            add z w
+            mod z 2
+            div w 2
+            add y w
+            mod y 2
+            div w 2
+            add x w
+            mod x 2
+            div w 2
+            mod w 2"
+                .lines(),
+        )
+        .unwrap();
+        assert_eq!(
+            prog_decompose_into_bits
+                .execute_with_logging(&[0b1010])
+                .unwrap()
+                .last()
+                .unwrap(),
+            &RegisterState([1, 0, 1, 0])
+        );
+    }
+
+    /// Check that the optimized symbolic program produces output identical to the regular program.
+    #[test]
+    fn no_mistake_during_symbolic_execution() {
+        let input_program = crate::env::get_puzzle_input_path("2021-12-24.txt");
+
+        let prog =
+            super::parse_program(std::fs::read_to_string(input_program).unwrap().lines()).unwrap();
+        let (sym_prog, sym_reg_states) = super::execute_symbolic(&prog, Z);
+
+        assert_eq!(prog.num_inputs(), sym_prog.num_inputs());
+        let inputs: Vec<i64> = (1..=prog.num_inputs()).map(|x| x as i64).collect();
+
+        let reg_states = prog.execute_with_logging(&inputs).unwrap();
+        assert_eq!(reg_states.len(), sym_reg_states.len());
+
+        // Go state by state, making sure both programs have matching registers.
+        // The symbolic program needs to evaluate variables as it goes to do so.
+        let mut evaluated_vars = vec![0i64; 4];
+        for (idx, (regs, sym_regs)) in reg_states.iter().zip(sym_reg_states.iter()).enumerate() {
+            // How far down the list of variables do we need to go to evaluate the symbolic
+            // registers?
+            let max_required_var_id = *sym_regs.0.iter().max().unwrap();
+            for var_id in evaluated_vars.len()..=max_required_var_id {
+                let val: i64 = match &sym_prog.vars[var_id] {
+                    SymbolicExpr::Int(x) => *x,
+                    SymbolicExpr::Input(x) => inputs[*x],
+                    SymbolicExpr::Op(binop) => {
+                        let a = evaluated_vars[binop.a];
+                        let b = evaluated_vars[binop.b];
+                        binop.op.apply(a, b).unwrap()
+                    }
+                };
+
+                assert_eq!(evaluated_vars.len(), var_id);
+                evaluated_vars.push(val);
+            }
+
+            let evaled_sym_regs = RegisterState(sym_regs.0.map(|var_id| evaluated_vars[var_id]));
+            assert_eq!(
+                regs, &evaled_sym_regs,
+                "Mismatch at instruction {}:\nProg: {:?}\nSym prog: {:?} = {:?}",
+                idx, prog.instructions[idx], sym_regs, evaled_sym_regs
+            );
+        }
+    }
+
+    /// A program which reads the digits in pairs, and requires the second digit of the k-th pair
+    /// (starting from 0) to be the first one minus k.
+    fn pairs_program() -> Program {
+        let text: String = (0..7)
+            .map(|k| format!("inp w\ninp x\nadd x {}\neql x w\neql x 0\nadd z x\n", k))
+            .collect();
+        super::parse_program(text.lines()).unwrap()
+    }
+
+    #[test]
+    fn solve_model_number() {
+        let prog = pairs_program();
+
+        assert_eq!(
+            super::solve_model_number(&prog, SearchOrder::Highest),
+            Some(ModelNumber([9, 9, 9, 8, 9, 7, 9, 6, 9, 5, 9, 4, 9, 3]))
+        );
+        assert_eq!(
+            super::solve_model_number(&prog, SearchOrder::Lowest),
+            Some(ModelNumber([1, 1, 2, 1, 3, 1, 4, 1, 5, 1, 6, 1, 7, 1]))
+        );
+
+        let solve = |text: String| {
+            let prog = super::parse_program(text.lines()).unwrap();
+            [SearchOrder::Highest, SearchOrder::Lowest]
+                .map(|order| super::solve_model_number(&prog, order).map(|m| m.to_string()))
+        };
+        let inputs = |n: usize| "inp y\n".repeat(n);
+
+        // Digit 0 is used again after reading digit 1, through a copy of the input: d0 + d1 +
+        // (d0 == 3) == 9.
+        let text = "inp w\nadd x 3\neql x w\ninp y\nadd z w\nadd z y\n";
+        assert_eq!(
+            solve(format!("{}{}add z x\nadd z -9", text, inputs(12))),
+            [Some("81999999999999".into()), Some("18111111111111".into())]
+        );
+
+        // Both digits are used after all the digits are read: d0 + d1 == 5.
+        assert_eq!(
+            solve(format!(
+                "inp w\ninp x\n{}add z w\nadd z x\nadd z -5",
+                inputs(12)
+            )),
+            [Some("41999999999999".into()), Some("14111111111111".into())]
+        );
+
+        // No valid model number.
+        assert_eq!(
+            solve(format!("inp w\nadd z w\n{}", inputs(13))),
+            [None, None]
+        );
+        assert_eq!(
+            solve(format!("inp z\nmod z 0\n{}", inputs(13))),
+            [None, None]
+        );
+    }
+
+    #[test]
+    fn abstract_values() {
+        let apply = AbstractValue::apply;
+        let exact = AbstractValue::exact;
+
+        // in[0] * 4 + 2
+        let x = apply(Op::Mul, &AbstractValue::input(), &exact(4));
+        let x = apply(Op::Add, &x, &exact(2));
+        assert_eq!(
+            x,
+            AbstractValue {
+                range: RangeIncl(6, 38),
+                congruence: Congruence { modulus: 4, rem: 2 },
+            }
+        );
+
+        // The ranges intersect, but not the congruences.
+        assert_eq!(apply(Op::Eql, &x, &exact(4)).exact_value(), Some(0));
+        assert_eq!(apply(Op::Eql, &x, &exact(10)).exact_value(), None);
+        assert_eq!(apply(Op::Mod, &x, &exact(2)).exact_value(), Some(0));
+        // The range is tightened to the values with the right remainder.
+        assert_eq!(apply(Op::Mod, &x, &exact(8)).range, RangeIncl(2, 6));
+
+        let half = apply(Op::Div, &x, &exact(2));
+        assert_eq!(
+            half,
+            AbstractValue {
+                range: RangeIncl(3, 19),
+                congruence: Congruence { modulus: 2, rem: 1 },
+            }
+        );
+        assert_eq!(apply(Op::Mod, &half, &exact(2)).exact_value(), Some(1));
+
+        // Division by a range of both signs.
+        let x = AbstractValue {
+            range: RangeIncl(10, 20),
+            congruence: Congruence::UNKNOWN,
+        };
+        let y = AbstractValue {
+            range: RangeIncl(-2, 5),
+            congruence: Congruence::UNKNOWN,
+        };
+        assert_eq!(apply(Op::Div, &x, &y).range, RangeIncl(-20, 20));
+        let y = AbstractValue {
+            range: RangeIncl(-5, -2),
+            congruence: Congruence::UNKNOWN,
+        };
+        assert_eq!(apply(Op::Div, &x, &y).range, RangeIncl(-10, -2));
+    }
+
+    #[test]
+    fn optimize_based_on_abstract_values() {
+        let prog = super::parse_program(
+            "inp w\nmul w 4\nadd w 2\nmod w 40\nadd x w\neql x 4\nadd z w\nadd z x".lines(),
+        )
+        .unwrap();
+        let (sym_prog, _reg_states) = super::execute_symbolic(&prog, Z);
+        let sym_prog = super::simplify(&super::optimize_based_on_abstract_values(&sym_prog));
+
+        // Only in[0] * 4 + 2 is left.
+        assert_eq!(
+            sym_prog.vars,
+            vec![
+                SymbolicExpr::Input(0),
+                SymbolicExpr::Int(4),
+                SymbolicExpr::Op(SymbolicBinaryOp {
+                    op: Op::Mul,
+                    a: 0,
+                    b: 1,
+                }),
+                SymbolicExpr::Int(2),
+                SymbolicExpr::Op(SymbolicBinaryOp {
+                    op: Op::Add,
+                    a: 2,
+                    b: 3,
+                }),
+            ]
+        );
+    }
+
+    /// A pseudo-random program reading 14 digits, whose result is in Z. There are no
+    /// multiplications by registers, so that the values don't overflow.
+    fn random_program(next: &mut impl FnMut() -> u64) -> Program {
+        let registers = [W, X, Y, Z];
+        let ops = [Op::Add, Op::Mul, Op::Div, Op::Mod, Op::Eql];
+        let mut instructions = Vec::new();
+        for _ in 0..14 {
+            instructions.push(Instruction::Input(registers[next() as usize % 4]));
+            for _ in 0..next() % 3 {
+                let op = ops[next() as usize % ops.len()];
+                let b = if op == Op::Mul {
+                    Value::Int(next() as i64 % 7 - 3)
+                } else if next().is_multiple_of(2) {
+                    Value::Reg(registers[next() as usize % 4])
+                } else {
+                    Value::Int(next() as i64 % 30 - 2)
+                };
+                instructions.push(Instruction::Op(BinaryOp {
+                    op,
+                    a: registers[next() as usize % 4],
+                    b,
+                }));
+            }
+        }
+        instructions.push(Instruction::Op(BinaryOp {
+            op: Op::Add,
+            a: Z,
+            b: Value::Reg(W),
+        }));
+        Program { instructions }
+    }
+
+    /// Check that the bytecode gives the same results as the program, errors included, when
+    /// resuming from any digit.
+    #[test]
+    fn bytecode_random_programs() {
+        let mut state = 24u64;
+        let mut next = move || {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            state >> 33
+        };
+
+        let mut num_errors = 0;
+        for _ in 0..200 {
+            let prog = random_program(&mut next);
+            let (sym_prog, _reg_states) = super::execute_symbolic(&prog, Z);
+            let bytecode = Bytecode::compile(&sym_prog);
+            let mut runner = BytecodeRunner::new(&bytecode);
+            // The simplified program may succeed where the original one fails, eg on `0 / 0`.
+            let simplified = Bytecode::compile(&super::simplify(&sym_prog));
+            let mut simplified_runner = BytecodeRunner::new(&simplified);
+
+            let mut digits = [1u8; 14];
+            for _ in 0..50 {
+                // Change a random number of the last digits.
+                let first_changed = next() as usize % digits.len();
+                for digit in &mut digits[first_changed..] {
+                    *digit = 1 + (next() % 9) as u8;
+                }
+
+                let expected = prog.execute(&digits, Z);
+                assert_eq!(runner.run(&digits), expected, "{:?}", prog);
+                if expected.is_ok() {
+                    assert_eq!(simplified_runner.run(&digits), expected, "{:?}", prog);
+                } else {
+                    num_errors += 1;
+                }
+            }
+        }
+        // Both successes and failures are tested.
+        assert!(
+            0 < num_errors && num_errors < 200 * 50,
+            "{} errors",
+            num_errors
+        );
+
+        let bytecode = Bytecode::compile(&super::execute_symbolic(&random_program(&mut next), Z).0);
+        assert_eq!(
+            BytecodeRunner::new(&bytecode).run(&[1; 13]),
+            Err(ExecutionError::InvalidInput)
+        );
+    }
+
+    /// Check that the bytecode of the simplified puzzle input gives the same results as the
+    /// program, on consecutive model numbers.
+    #[test]
+    fn bytecode_puzzle_input() {
+        let input_program = crate::env::get_puzzle_input_path("2021-12-24.txt");
+        let prog =
+            super::parse_program(std::fs::read_to_string(input_program).unwrap().lines()).unwrap();
+        let (sym_prog, _reg_states) = super::execute_symbolic(&prog, Z);
+        let bytecode = Bytecode::compile(&super::simplify(&sym_prog));
+        let mut runner = BytecodeRunner::new(&bytecode);
+
+        let mut model = ModelNumber([1, 3, 5, 7, 9, 2, 4, 6, 8, 1, 3, 5, 7, 9]);
+        for _ in 0..10_000 {
+            assert_eq!(runner.run(&model.0), prog.execute(&model.0, Z), "{}", model);
+            model.increment();
+        }
+    }
+
+    #[test]
+    fn divisor() {
+        let mut state = 7u64;
+        let mut next = move || {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            state
+        };
+
+        assert_eq!(Divisor::new(0), None);
+        assert_eq!(Divisor::new(-26), None);
+        for d in [1, 2, 3, 7, 26, 1000, (1 << 32) + 1, (1 << 62) + 1, i64::MAX] {
+            let divisor = Divisor::new(d).unwrap();
+            let values = [0, 1, d - 1, d, d.saturating_add(1), i64::MAX - 1, i64::MAX];
+            let random_values = (0..1000).map(|_| (next() >> (1 + next() % 63)) as i64);
+            for x in values.into_iter().filter(|x| *x >= 0).chain(random_values) {
+                assert_eq!(divisor.quotient(x), x / d, "{} / {}", x, d);
+            }
+        }
+    }
+
+    #[test]
+    fn format_dot() {
+        let prog = super::parse_program("inp w\nadd z w\ndiv z 3".lines()).unwrap();
+        let (sym_prog, _reg_states) = super::execute_symbolic(&prog, Z);
+
+        let mut dot = String::new();
+        super::format_dot(&sym_prog, true, &mut dot).unwrap();
+        assert_eq!(
+            dot,
+            r#"digraph program {
+    node [fontname=monospace];
+    subgraph cluster_digit_0 {
+        label="digit 0";
+        v3 [label="0", shape=plaintext];
+        v4 [label="in[0]", shape=box, style=filled, fillcolor=lightblue];
+        v5 [label="+"];
+        v6 [label="3", shape=plaintext];
+        v7 [label="/"];
+    }
+    v0 [label="0", shape=plaintext];
+    v1 [label="0", shape=plaintext];
+    v2 [label="0", shape=plaintext];
+    v3 -> v5;
+    v4 -> v5;
+    v5 -> v7;
+    v6 -> v7 [style=dashed];
+    z [shape=doublecircle];
+    v7 -> z;
+}
+"#
+        );
+
+        let mut dot = String::new();
+        super::format_dot(&sym_prog, false, &mut dot).unwrap();
+        assert!(!dot.contains("subgraph"));
+        assert!(dot.contains("\n    v4 [label=\"in[0]\", shape=box"));
+    }
+
+    #[test]
+    fn batch_eval() {
+        let (sym_prog, _reg_states) = super::execute_symbolic(&pairs_program(), Z);
+        let sym_prog = super::simplify(&sym_prog);
+        // The first 5 pairs are valid, the last 2 are free.
+        let prefix = [9, 9, 9, 8, 9, 7, 9, 6, 9, 5];
+        let progress_interval = std::time::Duration::from_millis(1);
+
+        let mut valid = Vec::new();
+        let mut progress = Vec::new();
+        let stats = super::batch_eval(
+            &sym_prog,
+            &prefix,
+            3,
+            progress_interval,
+            |stats| progress.push(stats.num_tested),
+            |model| {
+                valid.push(model.0);
+                true
+            },
+        );
+        assert_eq!(
+            stats,
+            BatchStats {
+                num_models: 6561,
+                num_tested: 6561,
+                num_valid: 12,
+                cancelled: false,
+            }
+        );
+        valid.sort();
+        let expected: Vec<[u8; 14]> = (6..=9)
+            .flat_map(|a| (7..=9).map(move |b| (a, b)))
+            .map(|(a, b)| {
+                let mut model = [0; 14];
+                model[..10].copy_from_slice(&prefix);
+                model[10..].copy_from_slice(&[a, a - 5, b, b - 6]);
+                model
+            })
+            .collect();
+        assert_eq!(valid, expected);
+        assert!(progress.windows(2).all(|w| w[0] <= w[1] && w[1] <= 6561));
+
+        // Stop after 2 valid model numbers: the others aren't reported.
+        let mut num_found = 0;
+        let stats = super::batch_eval(
+            &sym_prog,
+            &prefix,
+            3,
+            progress_interval,
+            |_| {},
+            |_| {
+                num_found += 1;
+                num_found < 2
+            },
+        );
+        assert_eq!(num_found, 2);
+        assert!(stats.cancelled);
+        assert_eq!(stats.num_valid, 2);
+
+        // No digit left to split.
+        let stats = super::batch_eval(
+            &sym_prog,
+            &expected[0],
+            3,
+            progress_interval,
+            |_| {},
+            |model| model.0 == expected[0],
+        );
+        assert_eq!(
+            (stats.num_models, stats.num_tested, stats.num_valid),
+            (1, 1, 1)
+        );
+    }
+}
@@ -1,43 +1,114 @@
-use advent_of_code::image::Image;
+use crate::animation::Format;
+use crate::colormap::{Colormap, Rgb};
+use crate::image::Image;
+use crate::recorder::{ImageRecorder, Recorder};
+use crate::solver::{Answer, Solver};
+use crate::terminal::{frame_delay_from_fps, LiveView};
 
-fn main() {
-    for test_file in ["2021-12-25.sample.txt", "2021-12-25.txt"] {
-        println!(
-            "----------------------- {} -----------------------",
-            test_file
-        );
-        let input_path = advent_of_code::env::get_puzzle_input_path(test_file);
+#[derive(Default)]
+pub struct Day25 {
+    /// If set, an animation of the sea cucumbers' moves is saved to this path.
+    record_to: Option<(std::path::PathBuf, Format)>,
+    /// If set, the sea cucumbers' moves are drawn in the terminal, with this delay between steps.
+    watch: Option<std::time::Duration>,
+}
+
+impl Solver for Day25 {
+    const DAY: u8 = 25;
+    const TITLE: &'static str = "Sea Cucumber";
+
+    type Input = Image<Spot>;
+
+    /// Supported options:
+    /// - `record-to=<path>`: save an animation of the sea cucumbers' moves in part 1, as a GIF or
+    ///   an APNG depending on the extension (`.gif` or `.png`).
+    /// - `watch=<fps>`: draw the sea cucumbers' moves in part 1 in the terminal, at this many steps
+    ///   per second.
+    fn configure(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "record-to" => {
+                let path = std::path::PathBuf::from(value);
+                let format = Format::from_path(&path)
+                    .ok_or_else(|| format!("Expected a .gif or .png path, got {:?}", value))?;
+                self.record_to = Some((path, format));
+                Ok(())
+            }
+            "watch" => {
+                self.watch = Some(frame_delay_from_fps(value)?);
+                Ok(())
+            }
+            _ => Err(format!("Day {} has no option named {:?}", Self::DAY, name)),
+        }
+    }
 
-        let mut image = parse_puzzle_input(&std::fs::read_to_string(&input_path).unwrap());
+    fn parse(&self, text: &str) -> crate::error::Result<Self::Input> {
+        parse_puzzle_input(text)
+    }
+
+    /// Returns the first step during which no sea cucumber moves.
+    fn part1(&self, image: &Self::Input) -> Answer {
+        let mut image = image.clone();
         let mut next_image = Image::new_with_same_shape(&image, Spot::Empty);
+        let recorder = self.record_to.as_ref().map(|_| {
+            let mut recorder = ImageRecorder::new(spot_color, 600);
+            recorder.scale = 4;
+            recorder
+        });
+        let watcher = self
+            .watch
+            .map(|delay| LiveView::new(std::io::stdout(), spot_color, delay));
+        let mut recorders = (recorder, watcher);
 
         let mut num_steps = 0;
         loop {
             num_steps += 1;
             let moved_east = step::<EAST>(&image, &mut next_image);
             let moved_south = step::<SOUTH>(&next_image, &mut image);
+            recorders.record(&image);
 
             if !moved_east && !moved_south {
                 break;
             }
         }
 
-        println!("Part 1: no more movement after {} steps.", num_steps);
+        if let ((Some(recorder), _), Some((path, format))) = (recorders, &self.record_to) {
+            let frame_delay = std::time::Duration::from_millis(50);
+            let num_frames = recorder
+                .save(path, *format, frame_delay)
+                .expect("Failed to save animation");
+            println!("Saved an animation of {} frames to {:?}!", num_frames, path);
+        }
+
+        num_steps.to_string()
+    }
+
+    /// There is no puzzle on the last day, only a star for having solved all the others.
+    fn part2(&self, _image: &Self::Input) -> Answer {
+        "Merry Christmas!".to_string()
     }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-enum Spot {
+pub enum Spot {
     /// The spot is empty (.)
     Empty,
     /// The spot contains a sea cucumber, traveling in a certain direction.
     SeaCuc(Direction),
 }
 
-type Direction = u8;
+pub type Direction = u8;
 const EAST: Direction = 0;
 const SOUTH: Direction = 1;
 
+/// The sea floor is dark blue, with east-facing sea cucumbers in orange and south-facing ones in
+/// green.
+fn spot_color(spot: &Spot) -> Rgb {
+    match *spot {
+        Spot::Empty => Rgb::new(8, 24, 64),
+        Spot::SeaCuc(dir) => Colormap::CATEGORICAL.color(1 + dir as usize),
+    }
+}
+
 /// Advances all the sea cucumbers by one step, storing the result in `next`.
 ///
 /// `next` is passed in to avoid dynamic memory allocations.
@@ -73,38 +144,47 @@ fn step<const DIR: Direction>(current: &Image<Spot>, next: &mut Image<Spot>) ->
     any_movement
 }
 
-fn parse_puzzle_input(text: &str) -> Image<Spot> {
+fn parse_puzzle_input(text: &str) -> crate::error::Result<Image<Spot>> {
     let mut height = 0;
     let mut width = 0;
     let mut data = Vec::new();
 
-    for line in text.lines() {
-        let line = line.trim();
+    for l in crate::iter::numbered_lines(text) {
+        let line = l.text.trim();
         if line.is_empty() {
             continue;
         }
 
         height += 1;
 
-        let line_len = line.as_bytes().len() as u16;
+        let line_len = line.len();
         if width == 0 {
             width = line_len;
         }
-        assert_eq!(width, line_len);
-
-        data.extend(line.bytes().map(|b| match b {
-            b'.' => Spot::Empty,
-            b'>' => Spot::SeaCuc(EAST),
-            b'v' => Spot::SeaCuc(SOUTH),
-            _ => panic!("Invalid image pixel character: {}", b),
-        }));
+        if width != line_len {
+            return Err(l.error(format!("Expected {} columns, got {}", width, line_len)));
+        }
+
+        for (i, c) in line.char_indices() {
+            data.push(match c {
+                '.' => Spot::Empty,
+                '>' => Spot::SeaCuc(EAST),
+                'v' => Spot::SeaCuc(SOUTH),
+                _ => {
+                    return Err(l.error_at(
+                        &line[i..],
+                        format!("Invalid image pixel character: {:?}", c),
+                    ))
+                }
+            });
+        }
     }
 
-    Image {
+    Ok(Image {
         height,
         width,
         data,
-    }
+    })
 }
 
 #[cfg(test)]
@@ -119,7 +199,7 @@ mod tests {
         let input_str = "\
 v...>
 .vv>>";
-        let img = parse_puzzle_input(input_str);
+        let img = parse_puzzle_input(input_str).unwrap();
         assert_eq!(
             img,
             Image {
@@ -144,7 +224,8 @@ v>v.vv.v..
 v.v..>>v.v
 ....v..v.>
 ",
-        );
+        )
+        .unwrap();
 
         let mut img = initial_img.clone();
         let mut next = Image {
@@ -169,7 +250,8 @@ v>>.>vvv..
 vv...>>vv.
 >.v.v..v.v
 ",
-                ),
+                )
+                .unwrap(),
             ),
             (
                 2,
@@ -185,7 +267,8 @@ v....v>v>.
 .vv..>>v..
 v>.....vv.
 ",
-                ),
+                )
+                .unwrap(),
             ),
             (
                 3,
@@ -201,7 +284,8 @@ v...>>.v.v
 v.v..>>v..
 .v>....v..
 ",
-                ),
+                )
+                .unwrap(),
             ),
             (
                 4,
@@ -216,7 +300,8 @@ v.v.>.>.v.
 >.v.vv>v.v
 .....>>vv.
 vvv>...v..",
-                ),
+                )
+                .unwrap(),
             ),
             (
                 5,
@@ -231,7 +316,8 @@ v.v.v>.>v.
 .>...v>v..
 ..v.v>>v.v
 v.v.>...v.",
-                ),
+                )
+                .unwrap(),
             ),
             (
                 10,
@@ -246,7 +332,8 @@ v>.>v.>>>.
 v.v..>v>..
 ..v...>v.>
 .vv..v>vv.",
-                ),
+                )
+                .unwrap(),
             ),
             (
                 20,
@@ -261,7 +348,8 @@ v>>>v.>v.>
 ..v..>>vv.
 v.v...>>.v
 ..v.....v>",
-                ),
+                )
+                .unwrap(),
             ),
             (
                 30,
@@ -276,7 +364,8 @@ v>...v...>
 ....v>..>v
 v.v...>vv>
 v.v...>vvv",
-                ),
+                )
+                .unwrap(),
             ),
             (
                 40,
@@ -291,7 +380,8 @@ v.v...>v>>
 >vv.....v>
 .>v...v.>v
 vvv.v..v.>",
-                ),
+                )
+                .unwrap(),
             ),
             (
                 50,
@@ -306,7 +396,8 @@ vvv....>vv
 v>.......>
 .vv>....v>
 .>v.vv.v..",
-                ),
+                )
+                .unwrap(),
             ),
             (
                 55,
@@ -321,7 +412,8 @@ v>v....>>v
 vvv...>..>
 >vv.....>.
 .>v.vv.v..",
-                ),
+                )
+                .unwrap(),
             ),
             (
                 56,
@@ -336,7 +428,8 @@ v>v....>>v
 vvv....>.>
 >vv......>
 .>v.vv.v..",
-                ),
+                )
+                .unwrap(),
             ),
             (
                 57,
@@ -351,7 +444,8 @@ v>v....>>v
 vvv.....>>
 >vv......>
 .>v.vv.v..",
-                ),
+                )
+                .unwrap(),
             ),
         ]
         .into_iter()
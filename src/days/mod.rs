@@ -0,0 +1,135 @@
+//! The solvers for each day of Advent of Code 2021.
+
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+
+use crate::solver::DynSolver;
+
+/// The days for which a solver exists.
+pub const ALL_DAYS: std::ops::RangeInclusive<u8> = 1..=25;
+
+/// Returns a new solver for a given day, or None if there is no such solver.
+pub fn solver_for_day(day: u8) -> Option<Box<dyn DynSolver>> {
+    let solver: Box<dyn DynSolver> = match day {
+        1 => Box::new(day01::Day01),
+        2 => Box::new(day02::Day02),
+        3 => Box::new(day03::Day03),
+        4 => Box::new(day04::Day04),
+        5 => Box::new(day05::Day05),
+        6 => Box::new(day06::Day06),
+        7 => Box::new(day07::Day07),
+        8 => Box::new(day08::Day08),
+        9 => Box::new(day09::Day09::default()),
+        10 => Box::new(day10::Day10),
+        11 => Box::new(day11::Day11::default()),
+        12 => Box::new(day12::Day12),
+        13 => Box::new(day13::Day13),
+        14 => Box::new(day14::Day14),
+        15 => Box::new(day15::Day15::default()),
+        16 => Box::new(day16::Day16::default()),
+        17 => Box::new(day17::Day17),
+        18 => Box::new(day18::Day18),
+        19 => Box::new(day19::Day19),
+        20 => Box::new(day20::Day20::default()),
+        21 => Box::new(day21::Day21),
+        22 => Box::new(day22::Day22::default()),
+        23 => Box::new(day23::Day23),
+        24 => Box::new(day24::Day24::default()),
+        25 => Box::new(day25::Day25::default()),
+        _ => return None,
+    };
+    Some(solver)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn all_days_have_a_solver() {
+        for day in super::ALL_DAYS {
+            let solver = super::solver_for_day(day).unwrap();
+            assert_eq!(solver.day(), day);
+        }
+        assert!(super::solver_for_day(0).is_none());
+        assert!(super::solver_for_day(26).is_none());
+    }
+
+    #[test]
+    fn malformed_inputs_are_rejected() {
+        let bingo_board = "1 2 3 4 5\n6 7 8 9 10\n11 12 13 14 15\n16 17 18 19 20\n21 22 23 24 25\n";
+        let bingo = format!(
+            "1,2,3,4,5\n\n{}\n{}",
+            bingo_board,
+            bingo_board.replace('1', "9")
+        );
+        let cases = [
+            (1, "199\n200\n", "Expected at least 3 depths, got 2"),
+            (3, "", "Expected at least 1 number"),
+            (
+                3,
+                "000000000001\n000000000001\n",
+                "Duplicate number 000000000001",
+            ),
+            (4, &bingo, "Expected at least 2 boards which can win, got 1"),
+            (
+                10,
+                "()\n(\n[\n",
+                "Expected an odd number of incomplete lines, got 2",
+            ),
+            (15, "", "Expected at least 1 row of risk levels"),
+            (18, "[1,2]\n", "Expected at least 2 numbers, got 1"),
+            (19, "\n", "Expected at least 1 scanner report"),
+            (
+                24,
+                "inp w\nadd z w\nadd z -3\n",
+                "Expected 14 inp instructions, one per digit, got 1",
+            ),
+        ];
+        for (day, text, msg) in cases {
+            let solver = super::solver_for_day(day).unwrap();
+            match solver.parse(text) {
+                Ok(_) => panic!("Day {} accepted the input {:?}", day, text),
+                Err(err) => assert!(
+                    err.to_string().ends_with(msg),
+                    "Day {}: unexpected error {:?}",
+                    day,
+                    err.to_string()
+                ),
+            }
+        }
+    }
+
+    /// Empty inputs are either rejected, or valid inputs which the parts can solve.
+    #[test]
+    fn empty_inputs_dont_panic() {
+        for day in super::ALL_DAYS {
+            let solver = super::solver_for_day(day).unwrap();
+            if let Ok(input) = solver.parse("") {
+                solver.part1(&*input);
+                solver.part2(&*input);
+            }
+        }
+    }
+}
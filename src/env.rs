@@ -1,9 +1,26 @@
 const ENV_DATA_DIR: &str = "AOC_DATA_DIR";
 
+/// Returns the directory containing the puzzle inputs.
+///
+/// This is read from the `AOC_DATA_DIR` env var, falling back to the `data` dir of this repository
+/// when it isn't set.
+///
+/// Panics if the env var can't be read, see `try_get_data_dir` for a non-panicking version.
 pub fn get_data_dir() -> std::path::PathBuf {
+    try_get_data_dir().unwrap_or_else(|err| panic!("Failed to read data dir: {}", err))
+}
+
+/// Same as `get_data_dir`, but returns an error if the env var is set to a non-unicode value.
+pub fn try_get_data_dir() -> crate::error::Result<std::path::PathBuf> {
     match std::env::var(ENV_DATA_DIR) {
-        Ok(path) => path.into(),
-        Err(err) => panic!("Failed to read env var {}: {:?}", ENV_DATA_DIR, err),
+        Ok(path) => Ok(path.into()),
+        Err(std::env::VarError::NotPresent) => {
+            Ok(concat!(env!("CARGO_MANIFEST_DIR"), "/data").into())
+        }
+        Err(source) => Err(crate::error::Error::EnvVar {
+            name: ENV_DATA_DIR,
+            source,
+        }),
     }
 }
 
@@ -12,3 +29,15 @@ pub fn get_puzzle_input_path(filename: &str) -> std::path::PathBuf {
     path.push(filename);
     path
 }
+
+/// Returns the path to the (real, non-sample) puzzle input for a given day.
+pub fn get_puzzle_input_path_for_day(day: u8) -> std::path::PathBuf {
+    get_puzzle_input_path(&format!("2021-12-{:02}.txt", day))
+}
+
+/// Same as `get_puzzle_input_path_for_day`, but returns an error if the data dir can't be found.
+pub fn try_get_puzzle_input_path_for_day(day: u8) -> crate::error::Result<std::path::PathBuf> {
+    let mut path = try_get_data_dir()?;
+    path.push(format!("2021-12-{:02}.txt", day));
+    Ok(path)
+}
@@ -0,0 +1,107 @@
+//! The crate-level error type, for reporting bad inputs instead of panicking.
+
+/// An error while reading or parsing a puzzle input, an image, etc.
+#[derive(Debug)]
+pub enum Error {
+    /// Failed to read or write a file.
+    Io {
+        /// The file involved, if known.
+        path: Option<std::path::PathBuf>,
+        source: std::io::Error,
+    },
+    /// An environment variable is set, but can't be used.
+    EnvVar {
+        name: &'static str,
+        source: std::env::VarError,
+    },
+    /// The input text is malformed.
+    Parse {
+        /// 1-based line number.
+        line: usize,
+        /// 1-based column number, in chars.
+        column: usize,
+        msg: String,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn io(path: impl Into<std::path::PathBuf>, source: std::io::Error) -> Self {
+        Self::Io {
+            path: Some(path.into()),
+            source,
+        }
+    }
+
+    pub fn parse(line: usize, column: usize, msg: impl Into<String>) -> Self {
+        Self::Parse {
+            line,
+            column,
+            msg: msg.into(),
+        }
+    }
+
+    /// Builds a parse error pointing at the byte `offset` in `text`, computing the line and
+    /// column from it.
+    pub fn parse_at_offset(text: &str, offset: usize, msg: impl Into<String>) -> Self {
+        let before = &text[..offset.min(text.len())];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        Self::parse(
+            before.matches('\n').count() + 1,
+            before[line_start..].chars().count() + 1,
+            msg,
+        )
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io {
+                path: Some(path),
+                source,
+            } => write!(f, "{}: {}", path.display(), source),
+            Error::Io { path: None, source } => write!(f, "{}", source),
+            Error::EnvVar { name, source } => write!(f, "env var {}: {}", name, source),
+            Error::Parse { line, column, msg } => {
+                write!(f, "line {}, column {}: {}", line, column, msg)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::EnvVar { source, .. } => Some(source),
+            Error::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(source: std::io::Error) -> Self {
+        Self::Io { path: None, source }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn parse_at_offset() {
+        let text = "abc\ndef\n\nghi";
+        let pos = |offset| match super::Error::parse_at_offset(text, offset, "") {
+            super::Error::Parse { line, column, .. } => (line, column),
+            err => panic!("Unexpected error {:?}", err),
+        };
+        assert_eq!(pos(0), (1, 1));
+        assert_eq!(pos(2), (1, 3));
+        assert_eq!(pos(4), (2, 1));
+        assert_eq!(pos(9), (4, 1));
+        assert_eq!(pos(11), (4, 3));
+        // Past the end: points right after the last char.
+        assert_eq!(pos(100), (4, 4));
+    }
+}
@@ -1,53 +1,167 @@
+use crate::colormap::Rgb;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::ops::Range;
+
 /// A simple 2D image.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Image<T> {
-    pub height: u16,
-    pub width: u16,
+    pub height: usize,
+    pub width: usize,
     /// Linear buffer of pixels, in row-major order.
     pub data: Vec<T>,
 }
 
 impl<T> Image<T> {
     pub fn len(&self) -> usize {
-        self.height as usize * self.width as usize
+        self.height * self.width
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 
-    pub fn size_hw(&self) -> (u16, u16) {
+    pub fn size_hw(&self) -> (usize, usize) {
         (self.height, self.width)
     }
 
-    pub fn pixel(&self, row: u16, col: u16) -> &T {
-        &self.data[row as usize * self.width as usize + col as usize]
+    pub fn pixel(&self, row: usize, col: usize) -> &T {
+        &self.data[row * self.width + col]
+    }
+
+    pub fn pixel_mut(&mut self, row: usize, col: usize) -> &mut T {
+        &mut self.data[row * self.width + col]
+    }
+
+    /// Same as `pixel`, but returns None if out of bounds.
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if row < self.height && col < self.width {
+            Some(self.pixel(row, col))
+        } else {
+            None
+        }
+    }
+
+    /// Same as `pixel_mut`, but returns None if out of bounds.
+    pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut T> {
+        if row < self.height && col < self.width {
+            Some(self.pixel_mut(row, col))
+        } else {
+            None
+        }
+    }
+
+    /// The (row, col) coordinates of the up to 4 horizontal and vertical neighbors of a pixel,
+    /// excluding those out of bounds.
+    pub fn neighbors4(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> {
+        const OFFSETS: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+        neighbors_at_offsets(self.size_hw(), row, col, &OFFSETS)
     }
 
-    pub fn pixel_mut(&mut self, row: u16, col: u16) -> &mut T {
-        &mut self.data[row as usize * self.width as usize + col as usize]
+    /// The (row, col) coordinates of the up to 8 neighbors of a pixel, including diagonal ones,
+    /// excluding those out of bounds.
+    pub fn neighbors8(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> {
+        #[rustfmt::skip]
+        const OFFSETS: [(isize, isize); 8] = [
+            (-1, -1), (-1, 0), (-1, 1),
+            ( 0, -1),          ( 0, 1),
+            ( 1, -1), ( 1, 0), ( 1, 1),
+        ];
+        neighbors_at_offsets(self.size_hw(), row, col, &OFFSETS)
+    }
+
+    /// A borrowed view of the `height` x `width` rectangle starting at (row, col).
+    ///
+    /// Panics if the rectangle doesn't fit in the image.
+    pub fn view(&self, row: usize, col: usize, height: usize, width: usize) -> ImageView<'_, T> {
+        assert!(
+            row + height <= self.height && col + width <= self.width,
+            "View out of bounds"
+        );
+        ImageView {
+            image: self,
+            row,
+            col,
+            height,
+            width,
+        }
+    }
+
+    /// Builds an image of the same shape, by applying `f` to every pixel.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Image<U> {
+        Image {
+            height: self.height,
+            width: self.width,
+            data: self.data.iter().map(f).collect(),
+        }
     }
 
     /// Enumerates the pixels, yielding (row, col, &pixel) tuples.
-    pub fn enumerate_pixels(&self) -> impl Iterator<Item = (u16, u16, &T)> {
-        self.data.iter().enumerate().map(|(lin_idx, px)| {
-            (
-                (lin_idx / self.width as usize) as u16,
-                (lin_idx % self.width as usize) as u16,
-                px,
-            )
-        })
+    pub fn enumerate_pixels(&self) -> impl Iterator<Item = (usize, usize, &T)> {
+        self.data
+            .iter()
+            .enumerate()
+            .map(|(lin_idx, px)| (lin_idx / self.width, lin_idx % self.width, px))
     }
 
     /// Enumerates the pixels, yielding (row, col, &mut pixel) tuples.
-    pub fn enumerate_pixels_mut(&mut self) -> impl Iterator<Item = (u16, u16, &mut T)> {
-        self.data.iter_mut().enumerate().map(|(lin_idx, px)| {
-            (
-                (lin_idx / self.width as usize) as u16,
-                (lin_idx % self.width as usize) as u16,
-                px,
-            )
-        })
+    pub fn enumerate_pixels_mut(&mut self) -> impl Iterator<Item = (usize, usize, &mut T)> {
+        self.data
+            .iter_mut()
+            .enumerate()
+            .map(|(lin_idx, px)| (lin_idx / self.width, lin_idx % self.width, px))
+    }
+
+    /// Like `render`, going through a pixel-to-color function.
+    pub fn render_mapped(&self, mut to_rgb: impl FnMut(&T) -> Rgb) -> String {
+        let mut text = String::new();
+        for row in (0..self.height).step_by(2) {
+            // Only emit the colors when they change.
+            let mut colors = None;
+            for col in 0..self.width {
+                let top = to_rgb(self.pixel(row, col));
+                let bottom = self.get(row + 1, col).map(&mut to_rgb);
+                if colors != Some((top, bottom)) {
+                    let (r, g, b) = (top.r(), top.g(), top.b());
+                    write!(text, "\x1b[38;2;{};{};{}m", r, g, b).unwrap();
+                    match bottom {
+                        Some(px) => write!(text, "\x1b[48;2;{};{};{}m", px.r(), px.g(), px.b()),
+                        None => write!(text, "\x1b[49m"),
+                    }
+                    .unwrap();
+                    colors = Some((top, bottom));
+                }
+                text.push(UPPER_HALF_BLOCK);
+            }
+            text.push_str("\x1b[0m\n");
+        }
+        text
+    }
+}
+
+/// The upper half block character: its foreground color is the top pixel, and its background
+/// color the bottom pixel.
+const UPPER_HALF_BLOCK: char = '\u{2580}';
+
+impl Image<Rgb> {
+    /// Renders the image for a terminal, with 24-bit ANSI colors and two rows of pixels per line.
+    /// Each line ends by resetting the colors. If the height is odd, the bottom half of the last
+    /// line keeps the terminal's background.
+    pub fn render(&self) -> String {
+        self.render_mapped(|&px| px)
     }
 }
 
 impl<T: Clone> Image<T> {
+    /// An image with all pixels set to `fill_value`.
+    pub fn filled(height: usize, width: usize, fill_value: T) -> Self {
+        Self {
+            height,
+            width,
+            data: vec![fill_value; height * width],
+        }
+    }
+
     pub fn new_with_same_shape(other: &Self, fill_value: T) -> Self {
         Self {
             height: other.height,
@@ -55,4 +169,624 @@ impl<T: Clone> Image<T> {
             data: vec![fill_value; other.len()],
         }
     }
+
+    /// Appends the rows of `other` below this image. Panics if the widths differ.
+    pub fn append_rows(&mut self, other: &Self) {
+        assert_eq!(self.width, other.width, "Images have different widths");
+        self.height += other.height;
+        self.data.extend_from_slice(&other.data);
+    }
+}
+
+/// A borrowed rectangular part of an image, see `Image::view`. Coordinates are relative to the
+/// view's top-left corner.
+#[derive(Clone, Copy, Debug)]
+pub struct ImageView<'a, T> {
+    image: &'a Image<T>,
+    row: usize,
+    col: usize,
+    height: usize,
+    width: usize,
+}
+
+impl<'a, T> ImageView<'a, T> {
+    pub fn size_hw(&self) -> (usize, usize) {
+        (self.height, self.width)
+    }
+
+    pub fn pixel(&self, row: usize, col: usize) -> &'a T {
+        self.get(row, col).expect("Pixel out of the view's bounds")
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<&'a T> {
+        if row < self.height && col < self.width {
+            self.image.get(self.row + row, self.col + col)
+        } else {
+            None
+        }
+    }
+
+    /// Enumerates the pixels, yielding (row, col, &pixel) tuples.
+    pub fn enumerate_pixels(&self) -> impl Iterator<Item = (usize, usize, &'a T)> + '_ {
+        (0..self.height)
+            .flat_map(move |row| (0..self.width).map(move |col| (row, col, self.pixel(row, col))))
+    }
+
+    /// Copies the view into a new image.
+    pub fn to_image(&self) -> Image<T>
+    where
+        T: Clone,
+    {
+        Image {
+            height: self.height,
+            width: self.width,
+            data: self
+                .enumerate_pixels()
+                .map(|(_, _, px)| px.clone())
+                .collect(),
+        }
+    }
+}
+
+/// An image whose top-left pixel is at a signed (row, col) origin, rather than at (0, 0). Useful
+/// for grids which can grow in every direction.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OffsetImage<T> {
+    pub image: Image<T>,
+    /// The coordinates of the image's top-left pixel.
+    pub origin: (isize, isize),
+}
+
+impl<T> OffsetImage<T> {
+    pub fn new(image: Image<T>, origin: (isize, isize)) -> Self {
+        Self { image, origin }
+    }
+
+    /// The range of valid rows.
+    pub fn rows(&self) -> Range<isize> {
+        self.origin.0..self.origin.0 + self.image.height as isize
+    }
+
+    /// The range of valid columns.
+    pub fn cols(&self) -> Range<isize> {
+        self.origin.1..self.origin.1 + self.image.width as isize
+    }
+
+    /// Converts signed coordinates to ones in the underlying image, if in bounds.
+    fn to_image_coords(&self, row: isize, col: isize) -> Option<(usize, usize)> {
+        let r = usize::try_from(row.checked_sub(self.origin.0)?).ok()?;
+        let c = usize::try_from(col.checked_sub(self.origin.1)?).ok()?;
+        (r < self.image.height && c < self.image.width).then_some((r, c))
+    }
+
+    pub fn get(&self, row: isize, col: isize) -> Option<&T> {
+        let (r, c) = self.to_image_coords(row, col)?;
+        Some(self.image.pixel(r, c))
+    }
+
+    pub fn get_mut(&mut self, row: isize, col: isize) -> Option<&mut T> {
+        let (r, c) = self.to_image_coords(row, col)?;
+        Some(self.image.pixel_mut(r, c))
+    }
+
+    /// Panics if out of bounds.
+    pub fn pixel(&self, row: isize, col: isize) -> &T {
+        self.get(row, col).expect("Pixel out of bounds")
+    }
+
+    /// Panics if out of bounds.
+    pub fn pixel_mut(&mut self, row: isize, col: isize) -> &mut T {
+        self.get_mut(row, col).expect("Pixel out of bounds")
+    }
+
+    /// Enumerates the pixels, yielding (row, col, &pixel) tuples with signed coordinates.
+    pub fn enumerate_pixels(&self) -> impl Iterator<Item = (isize, isize, &T)> {
+        let (row0, col0) = self.origin;
+        self.image
+            .enumerate_pixels()
+            .map(move |(r, c, px)| (row0 + r as isize, col0 + c as isize, px))
+    }
+}
+
+impl<T: Clone> OffsetImage<T> {
+    /// Returns a copy of the image, grown by `margin` pixels on all sides, which are set to
+    /// `fill_value`. The existing pixels keep their coordinates.
+    pub fn grow(&self, margin: usize, fill_value: T) -> Self {
+        let mut image = Image::filled(
+            self.image.height + 2 * margin,
+            self.image.width + 2 * margin,
+            fill_value,
+        );
+        for (r, c, px) in self.image.enumerate_pixels() {
+            *image.pixel_mut(r + margin, c + margin) = px.clone();
+        }
+        Self {
+            image,
+            origin: (
+                self.origin.0 - margin as isize,
+                self.origin.1 - margin as isize,
+            ),
+        }
+    }
+}
+
+/// An unbounded grid with signed coordinates, which only stores the cells that were set. The other
+/// cells have a `background` value.
+#[derive(Clone, Debug)]
+pub struct SparseGrid<T> {
+    cells: HashMap<(isize, isize), T>,
+    background: T,
+    /// The bounding box of the set cells, as (rows, cols). Empty if no cell is set.
+    bbox: (Range<isize>, Range<isize>),
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new(background: T) -> Self {
+        Self {
+            cells: HashMap::new(),
+            background,
+            bbox: (0..0, 0..0),
+        }
+    }
+
+    pub fn background(&self) -> &T {
+        &self.background
+    }
+
+    /// The number of set cells.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The smallest (rows, cols) ranges containing all the set cells.
+    pub fn bbox(&self) -> (Range<isize>, Range<isize>) {
+        self.bbox.clone()
+    }
+
+    /// The value of a cell, which is the background if it isn't set.
+    pub fn get(&self, row: isize, col: isize) -> &T {
+        self.cells.get(&(row, col)).unwrap_or(&self.background)
+    }
+
+    pub fn contains(&self, row: isize, col: isize) -> bool {
+        self.cells.contains_key(&(row, col))
+    }
+
+    /// Sets a cell, returning its previous value if it was set.
+    pub fn insert(&mut self, row: isize, col: isize, value: T) -> Option<T> {
+        self.bbox = expand_bbox(self.bbox.clone(), (row, col));
+        self.cells.insert((row, col), value)
+    }
+
+    /// Unsets a cell, returning its value if it was set.
+    pub fn remove(&mut self, row: isize, col: isize) -> Option<T> {
+        let value = self.cells.remove(&(row, col))?;
+        let (rows, cols) = &self.bbox;
+        let on_border = row == rows.start || row == rows.end - 1;
+        if on_border || col == cols.start || col == cols.end - 1 {
+            self.bbox = self.cells.keys().copied().fold((0..0, 0..0), expand_bbox);
+        }
+        Some(value)
+    }
+
+    /// Iterates over the set cells in arbitrary order, yielding (row, col, &value) tuples.
+    pub fn iter(&self) -> impl Iterator<Item = (isize, isize, &T)> {
+        self.cells
+            .iter()
+            .map(|(&(row, col), value)| (row, col, value))
+    }
+}
+
+impl<T: Clone> SparseGrid<T> {
+    /// Renders the bounding box as a dense image, where the cells that aren't set have the
+    /// background value.
+    pub fn to_image(&self) -> OffsetImage<T> {
+        let (rows, cols) = &self.bbox;
+        let mut image = OffsetImage::new(
+            Image::filled(
+                (rows.end - rows.start) as usize,
+                (cols.end - cols.start) as usize,
+                self.background.clone(),
+            ),
+            (rows.start, cols.start),
+        );
+        for (&(row, col), value) in &self.cells {
+            *image.pixel_mut(row, col) = value.clone();
+        }
+        image
+    }
+}
+
+impl<T: Clone + PartialEq> SparseGrid<T> {
+    /// Builds a grid from the pixels of an image, leaving out the ones equal to the background.
+    pub fn from_image(image: &OffsetImage<T>, background: T) -> Self {
+        let mut grid = Self::new(background);
+        for (row, col, px) in image.enumerate_pixels() {
+            if *px != grid.background {
+                grid.insert(row, col, px.clone());
+            }
+        }
+        grid
+    }
+}
+
+/// Grows a (rows, cols) bounding box to include a point.
+fn expand_bbox(
+    (rows, cols): (Range<isize>, Range<isize>),
+    (row, col): (isize, isize),
+) -> (Range<isize>, Range<isize>) {
+    if rows.is_empty() {
+        return (row..row + 1, col..col + 1);
+    }
+    (
+        rows.start.min(row)..rows.end.max(row + 1),
+        cols.start.min(col)..cols.end.max(col + 1),
+    )
+}
+
+/// One of the axes of a `Volume`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+/// A dense 3D grid of voxels, covering signed (x, y, z) ranges.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Volume<T> {
+    /// The x, y and z ranges covered by the volume.
+    pub bounds: [Range<isize>; 3],
+    /// Linear buffer of voxels, where x varies fastest, then y, then z. Each z-slice is
+    /// contiguous.
+    pub data: Vec<T>,
+}
+
+impl<T> Volume<T> {
+    /// The (x, y, z) sizes.
+    pub fn size_xyz(&self) -> [usize; 3] {
+        self.bounds
+            .clone()
+            .map(|r| (r.end - r.start).max(0) as usize)
+    }
+
+    pub fn len(&self) -> usize {
+        self.size_xyz().iter().product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The index of a voxel in `data`, if in bounds.
+    fn index(&self, x: isize, y: isize, z: isize) -> Option<usize> {
+        let [sx, sy, _] = self.size_xyz();
+        let [xr, yr, zr] = &self.bounds;
+        if !(xr.contains(&x) && yr.contains(&y) && zr.contains(&z)) {
+            return None;
+        }
+        let (x, y, z) = (
+            (x - xr.start) as usize,
+            (y - yr.start) as usize,
+            (z - zr.start) as usize,
+        );
+        Some((z * sy + y) * sx + x)
+    }
+
+    pub fn get(&self, x: isize, y: isize, z: isize) -> Option<&T> {
+        self.index(x, y, z).map(|i| &self.data[i])
+    }
+
+    pub fn get_mut(&mut self, x: isize, y: isize, z: isize) -> Option<&mut T> {
+        self.index(x, y, z).map(|i| &mut self.data[i])
+    }
+
+    /// Panics if out of bounds.
+    pub fn voxel(&self, x: isize, y: isize, z: isize) -> &T {
+        self.get(x, y, z).expect("Voxel out of bounds")
+    }
+
+    /// Panics if out of bounds.
+    pub fn voxel_mut(&mut self, x: isize, y: isize, z: isize) -> &mut T {
+        self.get_mut(x, y, z).expect("Voxel out of bounds")
+    }
+
+    /// Enumerates the voxels, yielding (x, y, z, &voxel) tuples.
+    pub fn enumerate_voxels(&self) -> impl Iterator<Item = (isize, isize, isize, &T)> {
+        let [sx, sy, _] = self.size_xyz();
+        let [x0, y0, z0] = self.bounds.clone().map(|r| r.start);
+        self.data.iter().enumerate().map(move |(i, v)| {
+            let (x, y, z) = (i % sx, (i / sx) % sy, i / (sx * sy));
+            (x0 + x as isize, y0 + y as isize, z0 + z as isize, v)
+        })
+    }
+}
+
+impl<T: Clone> Volume<T> {
+    /// A volume with all voxels set to `fill_value`.
+    pub fn filled(bounds: [Range<isize>; 3], fill_value: T) -> Self {
+        let mut volume = Self {
+            bounds,
+            data: Vec::new(),
+        };
+        volume.data = vec![fill_value; volume.len()];
+        volume
+    }
+
+    /// Sets all the voxels of a cuboid, given as (x, y, z) ranges, to `value`. The parts of the
+    /// cuboid which are out of bounds are ignored.
+    pub fn fill_cuboid(&mut self, cuboid: [Range<isize>; 3], value: T) {
+        let [xr, yr, zr] = clip_ranges(&cuboid, &self.bounds);
+        if xr.is_empty() {
+            return;
+        }
+        // Each row along x is contiguous.
+        let len = (xr.end - xr.start) as usize;
+        for z in zr {
+            for y in yr.clone() {
+                let start = self.index(xr.start, y, z).unwrap();
+                self.data[start..start + len].fill(value.clone());
+            }
+        }
+    }
+
+    /// Extracts the 2D slice where the coordinate along `axis` is `at`. The image's columns
+    /// follow the first of the remaining axes, and its rows the second one, eg for a z-slice the
+    /// rows are y and the columns x.
+    ///
+    /// Panics if `at` is out of bounds.
+    pub fn slice(&self, axis: Axis, at: isize) -> Image<T> {
+        let axis_index = axis as usize;
+        assert!(self.bounds[axis_index].contains(&at), "Slice out of bounds");
+        let (col_axis, row_axis) = match axis {
+            Axis::X => (1, 2),
+            Axis::Y => (0, 2),
+            Axis::Z => (0, 1),
+        };
+
+        let mut data = Vec::new();
+        let mut pos = [at; 3];
+        for row in self.bounds[row_axis].clone() {
+            for col in self.bounds[col_axis].clone() {
+                pos[row_axis] = row;
+                pos[col_axis] = col;
+                data.push(self.voxel(pos[0], pos[1], pos[2]).clone());
+            }
+        }
+
+        let size = self.size_xyz();
+        Image {
+            height: size[row_axis],
+            width: size[col_axis],
+            data,
+        }
+    }
+}
+
+/// Intersects each of the `ranges` with the corresponding `bounds`.
+fn clip_ranges(ranges: &[Range<isize>; 3], bounds: &[Range<isize>; 3]) -> [Range<isize>; 3] {
+    std::array::from_fn(|i| {
+        let (r, b) = (&ranges[i], &bounds[i]);
+        r.start.clamp(b.start, b.end)..r.end.clamp(b.start, b.end)
+    })
+}
+
+/// The in-bounds (row, col) coordinates at the given offsets from a pixel.
+fn neighbors_at_offsets(
+    (height, width): (usize, usize),
+    row: usize,
+    col: usize,
+    offsets: &'static [(isize, isize)],
+) -> impl Iterator<Item = (usize, usize)> {
+    offsets.iter().filter_map(move |&(dr, dc)| {
+        let r = row.checked_add_signed(dr)?;
+        let c = col.checked_add_signed(dc)?;
+        (r < height && c < width).then_some((r, c))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Image;
+    use crate::colormap::Rgb;
+
+    /// A 3x4 image where each pixel is 10 * row + col.
+    fn test_image() -> Image<usize> {
+        Image {
+            height: 3,
+            width: 4,
+            data: (0..3)
+                .flat_map(|r| (0..4).map(move |c| 10 * r + c))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn get() {
+        let mut img = test_image();
+        assert_eq!(img.get(2, 3), Some(&23));
+        assert_eq!(img.get(3, 0), None);
+        assert_eq!(img.get(0, 4), None);
+        *img.get_mut(1, 1).unwrap() = 0;
+        assert_eq!(img.pixel(1, 1), &0);
+        assert_eq!(img.get_mut(5, 5), None);
+    }
+
+    #[test]
+    fn neighbors() {
+        let img = test_image();
+        let values = |neighbors: &mut dyn Iterator<Item = (usize, usize)>| -> Vec<usize> {
+            neighbors.map(|(r, c)| *img.pixel(r, c)).collect()
+        };
+        assert_eq!(values(&mut img.neighbors4(1, 1)), vec![1, 10, 12, 21]);
+        assert_eq!(values(&mut img.neighbors4(0, 0)), vec![1, 10]);
+        assert_eq!(values(&mut img.neighbors4(2, 3)), vec![13, 22]);
+        assert_eq!(
+            values(&mut img.neighbors8(1, 1)),
+            vec![0, 1, 2, 10, 12, 20, 21, 22]
+        );
+        assert_eq!(values(&mut img.neighbors8(0, 3)), vec![2, 12, 13]);
+        assert_eq!(values(&mut img.neighbors8(2, 0)), vec![10, 11, 21]);
+
+        let single_pixel = Image::filled(1, 1, 0u8);
+        assert_eq!(single_pixel.neighbors8(0, 0).count(), 0);
+    }
+
+    #[test]
+    fn view_and_map() {
+        let img = test_image();
+        let view = img.view(1, 1, 2, 3);
+        assert_eq!(view.size_hw(), (2, 3));
+        assert_eq!(view.pixel(0, 0), &11);
+        assert_eq!(view.get(1, 2), Some(&23));
+        assert_eq!(view.get(2, 0), None);
+        assert_eq!(view.get(0, 3), None);
+        assert_eq!(view.to_image().data, vec![11, 12, 13, 21, 22, 23]);
+        assert_eq!(img.view(0, 0, 0, 4).to_image().len(), 0);
+
+        let doubled = img.map(|&px| px as u32 * 2);
+        assert_eq!(doubled.size_hw(), img.size_hw());
+        assert_eq!(doubled.pixel(2, 1), &42);
+
+        let mut stacked = img.clone();
+        stacked.append_rows(&img.view(1, 0, 2, 4).to_image());
+        assert_eq!(stacked.size_hw(), (5, 4));
+        assert_eq!(stacked.pixel(4, 3), &23);
+    }
+
+    #[test]
+    fn offset_image() {
+        let mut img = super::OffsetImage::new(test_image(), (-1, -2));
+        assert_eq!((img.rows(), img.cols()), (-1..2, -2..2));
+        assert_eq!(img.get(-1, -2), Some(&0));
+        assert_eq!(img.get(1, 1), Some(&23));
+        assert_eq!(img.get(-2, 0), None);
+        assert_eq!(img.get(0, 2), None);
+        assert_eq!(img.get(isize::MIN, isize::MAX), None);
+        *img.pixel_mut(0, 0) = 100;
+        assert_eq!(img.image.pixel(1, 2), &100);
+
+        let grown = img.grow(1, 7);
+        assert_eq!((grown.rows(), grown.cols()), (-2..3, -3..3));
+        assert_eq!(grown.pixel(0, 0), &100);
+        assert_eq!(grown.pixel(-2, -3), &7);
+        assert_eq!(grown.pixel(2, 2), &7);
+        assert_eq!(
+            grown
+                .enumerate_pixels()
+                .filter(|&(_, _, &px)| px == 7)
+                .count(),
+            5 * 6 - 3 * 4
+        );
+        assert!(grown
+            .enumerate_pixels()
+            .all(|(r, c, px)| img.get(r, c).is_none_or(|p| p == px)));
+    }
+
+    #[test]
+    fn sparse_grid() {
+        let mut grid = super::SparseGrid::new('.');
+        assert_eq!((grid.len(), grid.bbox()), (0, (0..0, 0..0)));
+        assert_eq!(grid.to_image().image.len(), 0);
+
+        grid.insert(-2, 3, '#');
+        grid.insert(1, -1, '#');
+        grid.insert(0, 0, 'o');
+        assert_eq!(grid.bbox(), (-2..2, -1..4));
+        assert_eq!(grid.get(0, 0), &'o');
+        assert_eq!(grid.get(100, -100), &'.');
+        assert!(grid.contains(-2, 3) && !grid.contains(-2, 2));
+
+        let img = grid.to_image();
+        assert_eq!(img.origin, (-2, -1));
+        let rendered: Vec<String> = img
+            .image
+            .data
+            .chunks(img.image.width)
+            .map(|row| row.iter().collect())
+            .collect();
+        assert_eq!(rendered, ["....#", ".....", ".o...", "#...."]);
+
+        // Removing a cell on the border shrinks the bounding box.
+        assert_eq!(grid.remove(-2, 3), Some('#'));
+        assert_eq!(grid.remove(-2, 3), None);
+        assert_eq!(grid.bbox(), (0..2, -1..1));
+
+        let round_trip = super::SparseGrid::from_image(&img, '.');
+        assert_eq!(round_trip.len(), 3);
+        assert_eq!(round_trip.bbox(), (-2..2, -1..4));
+        assert_eq!(round_trip.to_image(), img);
+    }
+
+    #[test]
+    fn volume() {
+        use super::{Axis, Volume};
+
+        let mut vol = Volume::filled([-1..2, 0..2, -2..0], 0);
+        assert_eq!((vol.size_xyz(), vol.len()), ([3, 2, 2], 12));
+        assert_eq!(vol.get(-1, 0, -2), Some(&0));
+        assert_eq!(vol.get(2, 0, -2), None);
+        assert_eq!(vol.get(0, 0, 0), None);
+
+        // Partly out of bounds.
+        vol.fill_cuboid([0..5, 1..2, -5..5], 1);
+        *vol.voxel_mut(-1, 0, -1) = 2;
+        assert_eq!(vol.voxel(1, 1, -2), &1);
+        assert_eq!(
+            vol.enumerate_voxels()
+                .filter(|&(_, _, _, &v)| v == 1)
+                .map(|(x, y, z, _)| (x, y, z))
+                .collect::<Vec<_>>(),
+            vec![(0, 1, -2), (1, 1, -2), (0, 1, -1), (1, 1, -1)]
+        );
+
+        let z_slice = vol.slice(Axis::Z, -1);
+        assert_eq!(z_slice.size_hw(), (2, 3));
+        assert_eq!(z_slice.data, vec![2, 0, 0, 0, 1, 1]);
+        let x_slice = vol.slice(Axis::X, -1);
+        assert_eq!(x_slice.size_hw(), (2, 2));
+        assert_eq!(x_slice.data, vec![0, 0, 2, 0]);
+        let y_slice = vol.slice(Axis::Y, 1);
+        assert_eq!(y_slice.size_hw(), (2, 3));
+        assert_eq!(y_slice.data, vec![0, 1, 1, 0, 1, 1]);
+
+        // Empty cuboids are fine.
+        vol.fill_cuboid([1..1, 0..2, -2..0], 5);
+        assert!(!vol.data.contains(&5));
+    }
+
+    #[test]
+    #[should_panic]
+    fn view_out_of_bounds() {
+        test_image().view(1, 1, 3, 1);
+    }
+
+    #[test]
+    fn render_half_blocks() {
+        let (red, blue) = (Rgb::new(255, 0, 0), Rgb::new(0, 0, 255));
+        let img = Image {
+            height: 3,
+            width: 3,
+            data: vec![red, red, blue, blue, blue, blue, red, red, red],
+        };
+        assert_eq!(
+            img.render(),
+            concat!(
+                "\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m▀▀",
+                "\x1b[38;2;0;0;255m\x1b[48;2;0;0;255m▀\x1b[0m\n",
+                "\x1b[38;2;255;0;0m\x1b[49m▀▀▀\x1b[0m\n",
+            )
+        );
+
+        let gray = |&v: &u8| Rgb::new(v, v, v);
+        assert_eq!(
+            Image::filled(2, 1, 7u8).render_mapped(gray),
+            "\x1b[38;2;7;7;7m\x1b[48;2;7;7;7m▀\x1b[0m\n"
+        );
+        assert_eq!(Image::filled(0, 3, 7u8).render_mapped(gray), "");
+    }
 }
@@ -1,3 +1,5 @@
+use crate::error::{Error, Result};
+
 /// An iterator over overlapping windows.
 ///
 /// Similar to `std::slice::windows`, but as an adapter for any iterator, and using a compile-time
@@ -67,18 +69,158 @@ where
 }
 
 /// Returns an iterator over the lines from a file, discarding empty lines.
-pub fn line_iter_from_file(path: &std::path::Path) -> impl Iterator<Item=String> {
-    let file = std::io::BufReader::new(std::fs::File::open(path).expect("Failed to open file"));
+///
+/// Panics on I/O errors, see `try_line_iter_from_file` for a non-panicking version.
+pub fn line_iter_from_file(path: &std::path::Path) -> impl Iterator<Item = String> {
+    try_line_iter_from_file(path)
+        .expect("Failed to open file")
+        .map(|l| l.expect("Failed to read line"))
+}
+
+/// Returns an iterator over the lines from a file, discarding empty lines.
+///
+/// Fails if the file can't be opened, then yields an error for each line that can't be read.
+pub fn try_line_iter_from_file(
+    path: &std::path::Path,
+) -> Result<impl Iterator<Item = Result<String>>> {
+    let file = std::fs::File::open(path).map_err(|err| Error::io(path, err))?;
+    let file = std::io::BufReader::new(file);
     use std::io::BufRead;
 
-    file.lines().filter_map(|l| {
-        let l = l.expect("Failed to read line");
-        if l.is_empty() {
-            None
-        } else {
-            Some(l)
+    let path = path.to_path_buf();
+    Ok(file.lines().filter_map(move |l| match l {
+        Ok(l) if l.is_empty() => None,
+        Ok(l) => Some(Ok(l)),
+        Err(err) => Some(Err(Error::io(&path, err))),
+    }))
+}
+
+/// Returns an iterator over the lines from a string, discarding empty lines.
+pub fn line_iter_from_str(text: &str) -> impl Iterator<Item = String> + '_ {
+    numbered_lines(text).map(|l| l.text.to_string())
+}
+
+/// Returns an iterator over the lines from a string, discarding empty lines, but keeping track of
+/// line numbers for error reporting.
+pub fn numbered_lines(text: &str) -> Lines<'_> {
+    Lines {
+        lines: text.lines(),
+        num_lines_read: 0,
+    }
+}
+
+/// An iterator over the non-empty lines of a string, see `numbered_lines`.
+pub struct Lines<'a> {
+    lines: std::str::Lines<'a>,
+    /// The number of lines read so far, including the empty ones.
+    num_lines_read: usize,
+}
+
+impl<'a> Lines<'a> {
+    /// Returns the next non-empty line, or an "unexpected end of input" error if there are none
+    /// left. `what` describes what was expected instead, eg "a board row".
+    pub fn next_or_err(&mut self, what: &str) -> Result<Line<'a>> {
+        match self.next() {
+            Some(line) => Ok(line),
+            None => Err(Error::parse(
+                self.num_lines_read + 1,
+                1,
+                format!("Unexpected end of input, expected {}", what),
+            )),
         }
-    })
+    }
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = Line<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let text = self.lines.next()?;
+            self.num_lines_read += 1;
+            if !text.is_empty() {
+                return Some(Line {
+                    number: self.num_lines_read,
+                    text,
+                });
+            }
+        }
+    }
+}
+
+/// A single line of text, remembering where it came from so that parse errors can point at the
+/// offending part of it.
+#[derive(Clone, Copy, Debug)]
+pub struct Line<'a> {
+    /// 1-based line number.
+    pub number: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    /// Returns a parse error pointing at the start of the line.
+    pub fn error(&self, msg: impl Into<String>) -> Error {
+        Error::parse(self.number, 1, msg)
+    }
+
+    /// Returns a parse error pointing at `token`, which should be a substring of the line's text.
+    /// Points at the start of the line otherwise.
+    pub fn error_at(&self, token: &str, msg: impl Into<String>) -> Error {
+        let start = self.text.as_ptr() as usize;
+        let token_start = token.as_ptr() as usize;
+        let offset = if (start..=start + self.text.len()).contains(&token_start) {
+            token_start - start
+        } else {
+            0
+        };
+        Error::parse(self.number, self.text[..offset].chars().count() + 1, msg)
+    }
+
+    /// Parses `token`, a substring of the line, eg a number.
+    pub fn parse<T>(&self, token: &str) -> Result<T>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display,
+    {
+        token.parse::<T>().map_err(|err| {
+            self.error_at(
+                token,
+                format!(
+                    "Failed to parse {:?} as {}: {}",
+                    token,
+                    std::any::type_name::<T>(),
+                    err
+                ),
+            )
+        })
+    }
+
+    /// Parses the whole line as a sequence of decimal digits, eg "0129" => [0, 1, 2, 9].
+    pub fn parse_digits(&self) -> Result<Vec<u8>> {
+        self.text
+            .char_indices()
+            .map(|(i, c)| match c.to_digit(10) {
+                Some(d) => Ok(d as u8),
+                None => {
+                    Err(self.error_at(&self.text[i..], format!("Expected a digit, got {:?}", c)))
+                }
+            })
+            .collect()
+    }
+
+    /// Splits `token`, a substring of the line, around the first occurrence of `sep`.
+    pub fn split_once(&self, token: &'a str, sep: &str) -> Result<(&'a str, &'a str)> {
+        token
+            .split_once(sep)
+            .ok_or_else(|| self.error_at(token, format!("Missing {:?}", sep)))
+    }
+
+    /// Removes `prefix` from `token`, a substring of the line.
+    pub fn strip_prefix(&self, token: &'a str, prefix: &str) -> Result<&'a str> {
+        token
+            .strip_prefix(prefix)
+            .ok_or_else(|| self.error_at(token, format!("Expected {:?}", prefix)))
+    }
 }
 
 #[cfg(test)]
@@ -105,4 +247,30 @@ mod tests {
             vec![[0, 1, 2, 3, 4], [1, 2, 3, 4, 5], [2, 3, 4, 5, 6]]
         );
     }
+
+    #[test]
+    fn numbered_lines() {
+        let text = "12 ab\n\n\nx,y\n";
+        let mut lines = super::numbered_lines(text);
+
+        let first = lines.next_or_err("a line").unwrap();
+        assert_eq!((first.number, first.text), (1, "12 ab"));
+        let (num, rest) = first.split_once(first.text, " ").unwrap();
+        assert_eq!(first.parse::<u8>(num).unwrap(), 12);
+        match first.parse::<u8>(rest) {
+            Err(crate::error::Error::Parse { line, column, .. }) => {
+                assert_eq!((line, column), (1, 4))
+            }
+            res => panic!("Unexpected result {:?}", res),
+        }
+
+        let second = lines.next().unwrap();
+        assert_eq!((second.number, second.text), (4, "x,y"));
+        match lines.next_or_err("another line") {
+            Err(crate::error::Error::Parse { line, column, .. }) => {
+                assert_eq!((line, column), (5, 1))
+            }
+            res => panic!("Unexpected result {:?}", res),
+        }
+    }
 }
@@ -1,6 +1,16 @@
+pub mod animation;
+pub mod answers;
+pub mod bench;
 pub mod bits;
 pub mod colormap;
+pub mod days;
 pub mod env;
+pub mod error;
 pub mod image;
 pub mod iter;
 pub mod netpbm;
+pub mod recorder;
+pub mod solver;
+pub mod terminal;
+
+pub use error::Error;
@@ -1,119 +1,1006 @@
 //! Helpers for reading/writing Netpbm-format images.
 //! https://en.wikipedia.org/wiki/Netpbm
+//!
+//! All six formats are supported, in both ASCII and binary encodings:
+//! - P1/P4: bitmaps (PBM),
+//! - P2/P5: graymaps (PGM),
+//! - P3/P6: pixmaps (PPM),
+//!
+//! with up to 16 bits per sample. PAM images (P7) are also supported, with any number of
+//! channels. Use `read_any` to read any of them.
+//!
+//! The readers parse the images incrementally, from any `BufRead` (a file, stdin, a byte slice,
+//! etc.), without holding the whole file in memory.
 
 use crate::colormap::Rgb;
-use crate::image::Image;
+use crate::error::{Error, Result};
+use crate::image::{Axis, Image, Volume};
+
+/// The kind of image stored in a Netpbm file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Kind {
+    /// Black and white (PBM).
+    Bitmap,
+    /// Grayscale (PGM).
+    Graymap,
+    /// RGB (PPM).
+    Pixmap,
+}
+
+impl Kind {
+    /// The number of samples per pixel.
+    pub fn channels(self) -> usize {
+        match self {
+            Kind::Bitmap | Kind::Graymap => 1,
+            Kind::Pixmap => 3,
+        }
+    }
+}
+
+/// How the pixel data is stored.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Encoding {
+    /// As whitespace-separated decimal numbers (P1, P2, P3).
+    Ascii,
+    /// As raw bytes (P4, P5, P6).
+    Binary,
+}
+
+/// A Netpbm format, identified by its magic number "P1" to "P6".
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Format {
+    pub kind: Kind,
+    pub encoding: Encoding,
+}
+
+impl Format {
+    const ALL: [(&'static str, Format); 6] = [
+        ("P1", Format::new(Kind::Bitmap, Encoding::Ascii)),
+        ("P2", Format::new(Kind::Graymap, Encoding::Ascii)),
+        ("P3", Format::new(Kind::Pixmap, Encoding::Ascii)),
+        ("P4", Format::new(Kind::Bitmap, Encoding::Binary)),
+        ("P5", Format::new(Kind::Graymap, Encoding::Binary)),
+        ("P6", Format::new(Kind::Pixmap, Encoding::Binary)),
+    ];
+
+    pub const fn new(kind: Kind, encoding: Encoding) -> Self {
+        Self { kind, encoding }
+    }
+
+    pub fn from_magic(magic: &[u8]) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|(m, _)| m.as_bytes() == magic)
+            .map(|&(_, format)| format)
+    }
+
+    pub fn magic(self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(_, format)| *format == self)
+            .map(|&(m, _)| m)
+            .expect("BUG: missing magic number")
+    }
+}
+
+/// The type of the tuples (ie pixels) of a PAM image, from its TUPLTYPE header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TupleType {
+    BlackAndWhite,
+    Grayscale,
+    Rgb,
+    BlackAndWhiteAlpha,
+    GrayscaleAlpha,
+    RgbAlpha,
+    /// Any other, application-specific, type.
+    Other(String),
+}
+
+impl TupleType {
+    const KNOWN: [(&'static str, TupleType, u16); 6] = [
+        ("BLACKANDWHITE", TupleType::BlackAndWhite, 1),
+        ("GRAYSCALE", TupleType::Grayscale, 1),
+        ("RGB", TupleType::Rgb, 3),
+        ("BLACKANDWHITE_ALPHA", TupleType::BlackAndWhiteAlpha, 2),
+        ("GRAYSCALE_ALPHA", TupleType::GrayscaleAlpha, 2),
+        ("RGB_ALPHA", TupleType::RgbAlpha, 4),
+    ];
+
+    pub fn from_name(name: &str) -> Self {
+        Self::KNOWN
+            .iter()
+            .find(|(n, _, _)| *n == name)
+            .map(|(_, tuple_type, _)| tuple_type.clone())
+            .unwrap_or_else(|| TupleType::Other(name.to_string()))
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            TupleType::Other(name) => name,
+            known => Self::KNOWN
+                .iter()
+                .find(|(_, tuple_type, _)| tuple_type == known)
+                .map(|(n, _, _)| *n)
+                .expect("BUG: missing tuple type name"),
+        }
+    }
+
+    /// The number of channels of the known types.
+    pub fn depth(&self) -> Option<u16> {
+        Self::KNOWN
+            .iter()
+            .find(|(_, tuple_type, _)| tuple_type == self)
+            .map(|&(_, _, depth)| depth)
+    }
+}
+
+/// A PAM image (P7), where each pixel is a tuple of `depth` samples.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PamImage {
+    pub height: usize,
+    pub width: usize,
+    pub depth: u16,
+    pub max_val: u16,
+    pub tuple_type: Option<TupleType>,
+    /// The samples in [0, max_val], in row-major order, with the `depth` samples of each pixel
+    /// next to each other.
+    pub samples: Vec<u16>,
+}
+
+impl PamImage {
+    /// Builds an 8-bit PAM image from an image with N channels.
+    pub fn from_image<const N: usize>(
+        image: &Image<[u8; N]>,
+        tuple_type: Option<TupleType>,
+    ) -> Self {
+        Self {
+            height: image.height,
+            width: image.width,
+            depth: N as u16,
+            max_val: 255,
+            tuple_type,
+            samples: image.data.iter().flatten().map(|&s| s as u16).collect(),
+        }
+    }
+
+    /// Converts to an image with N channels. Returns None if the depth isn't N, or if the samples
+    /// don't fit in 8 bits.
+    pub fn to_image<const N: usize>(&self) -> Option<Image<[u8; N]>> {
+        if self.depth as usize != N || self.max_val > 255 {
+            return None;
+        }
+        Some(Image {
+            height: self.height,
+            width: self.width,
+            data: self
+                .samples
+                .chunks_exact(N)
+                .map(|s| std::array::from_fn(|i| s[i] as u8))
+                .collect(),
+        })
+    }
+}
+
+/// An image of any of the supported formats, as returned by `read_any`.
+///
+/// The samples are stored as read from the file, ie in [0, max_val]. Images with a max value up
+/// to 255 use 8 bits per sample, the others 16 bits.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AnyImage {
+    /// A bitmap, where true is black (and 1 in the file).
+    Bitmap(Image<bool>),
+    Gray8 {
+        image: Image<u8>,
+        max_val: u8,
+    },
+    Gray16 {
+        image: Image<u16>,
+        max_val: u16,
+    },
+    Rgb8 {
+        image: Image<Rgb>,
+        max_val: u8,
+    },
+    Rgb16 {
+        image: Image<[u16; 3]>,
+        max_val: u16,
+    },
+    Pam(PamImage),
+}
+
+impl AnyImage {
+    /// The kind of the classic formats, None for PAM images.
+    pub fn kind(&self) -> Option<Kind> {
+        match self {
+            AnyImage::Bitmap(_) => Some(Kind::Bitmap),
+            AnyImage::Gray8 { .. } | AnyImage::Gray16 { .. } => Some(Kind::Graymap),
+            AnyImage::Rgb8 { .. } | AnyImage::Rgb16 { .. } => Some(Kind::Pixmap),
+            AnyImage::Pam(_) => None,
+        }
+    }
+
+    /// The maximum value of a sample, 1 for bitmaps.
+    pub fn max_val(&self) -> u16 {
+        match self {
+            AnyImage::Bitmap(_) => 1,
+            AnyImage::Gray8 { max_val, .. } | AnyImage::Rgb8 { max_val, .. } => *max_val as u16,
+            AnyImage::Gray16 { max_val, .. } | AnyImage::Rgb16 { max_val, .. } => *max_val,
+            AnyImage::Pam(pam) => pam.max_val,
+        }
+    }
+
+    pub fn size_hw(&self) -> (usize, usize) {
+        match self {
+            AnyImage::Bitmap(image) => image.size_hw(),
+            AnyImage::Gray8 { image, .. } => image.size_hw(),
+            AnyImage::Gray16 { image, .. } => image.size_hw(),
+            AnyImage::Rgb8 { image, .. } => image.size_hw(),
+            AnyImage::Rgb16 { image, .. } => image.size_hw(),
+            AnyImage::Pam(pam) => (pam.height, pam.width),
+        }
+    }
+
+    /// All the samples, in row-major order, with the channels of each pixel next to each other.
+    fn samples(&self) -> Box<dyn Iterator<Item = u16> + '_> {
+        match self {
+            AnyImage::Bitmap(image) => Box::new(image.data.iter().map(|&px| px as u16)),
+            AnyImage::Gray8 { image, .. } => Box::new(image.data.iter().map(|&px| px as u16)),
+            AnyImage::Gray16 { image, .. } => Box::new(image.data.iter().copied()),
+            AnyImage::Rgb8 { image, .. } => Box::new(
+                image
+                    .data
+                    .iter()
+                    .flat_map(|px| [px.r(), px.g(), px.b()].map(u16::from)),
+            ),
+            AnyImage::Rgb16 { image, .. } => Box::new(image.data.iter().flatten().copied()),
+            AnyImage::Pam(pam) => Box::new(pam.samples.iter().copied()),
+        }
+    }
+}
+
+/// Reads an image in any of the supported formats, detected from its magic number.
+pub fn read_any(path: &std::path::Path) -> Result<AnyImage> {
+    read_file(path, read_any_from)
+}
+
+/// Parses an image in any of the supported formats, see `read_any`.
+pub fn parse_any(bytes: &[u8]) -> Result<AnyImage> {
+    read_any_from(bytes)
+}
+
+/// Reads an image in any of the supported formats from a reader, see `read_any`.
+///
+/// Only the image is read: anything after it is left in the reader.
+pub fn read_any_from<R: std::io::BufRead>(reader: R) -> Result<AnyImage> {
+    let mut parser = Parser::new(reader);
+    let header = parser.parse_header()?;
+    let samples = parser.parse_samples(&header)?;
+
+    let format = match header.format {
+        Some(format) => format,
+        None => {
+            return Ok(AnyImage::Pam(PamImage {
+                height: header.height,
+                width: header.width,
+                depth: header.channels as u16,
+                max_val: header.max_val,
+                tuple_type: header.tuple_type,
+                samples,
+            }))
+        }
+    };
+    Ok(match (format.kind, header.max_val) {
+        (Kind::Bitmap, _) => {
+            AnyImage::Bitmap(header.image(samples.iter().map(|&s| s == 1).collect()))
+        }
+        (Kind::Graymap, max_val @ 0..=255) => AnyImage::Gray8 {
+            image: header.image(samples.iter().map(|&s| s as u8).collect()),
+            max_val: max_val as u8,
+        },
+        (Kind::Graymap, max_val) => AnyImage::Gray16 {
+            image: header.image(samples),
+            max_val,
+        },
+        (Kind::Pixmap, max_val @ 0..=255) => AnyImage::Rgb8 {
+            image: header.image(
+                samples
+                    .chunks_exact(3)
+                    .map(|s| Rgb::new(s[0] as u8, s[1] as u8, s[2] as u8))
+                    .collect(),
+            ),
+            max_val: max_val as u8,
+        },
+        (Kind::Pixmap, max_val) => AnyImage::Rgb16 {
+            image: header.image(
+                samples
+                    .chunks_exact(3)
+                    .map(|s| [s[0], s[1], s[2]])
+                    .collect(),
+            ),
+            max_val,
+        },
+    })
+}
+
+/// Writes an image in the format matching its kind, with the given encoding. PAM images are
+/// always binary.
+pub fn write_any(
+    image: &AnyImage,
+    encoding: Encoding,
+    writer: &mut dyn std::io::Write,
+) -> std::io::Result<()> {
+    let kind = match (image.kind(), image) {
+        (Some(kind), _) => kind,
+        (None, AnyImage::Pam(pam)) => return write_pam(pam, writer),
+        (None, _) => unreachable!("BUG: only PAM images have no kind"),
+    };
+    let (height, width) = image.size_hw();
+    write_image(
+        writer,
+        Format::new(kind, encoding),
+        width,
+        height,
+        image.max_val(),
+        image.samples(),
+    )
+}
+
+/// Saves an image to a file, see `write_any`.
+pub fn save_any(image: &AnyImage, encoding: Encoding, path: &std::path::Path) -> Result<()> {
+    use std::io::Write;
+    let mut writer =
+        std::io::BufWriter::new(std::fs::File::create(path).map_err(|err| Error::io(path, err))?);
+    write_any(image, encoding, &mut writer)
+        .and_then(|()| writer.flush())
+        .map_err(|err| Error::io(path, err))
+}
+
+/// Writes a PAM image (P7).
+pub fn write_pam(pam: &PamImage, writer: &mut dyn std::io::Write) -> std::io::Result<()> {
+    assert_eq!(
+        pam.samples.len(),
+        pam.height * pam.width * pam.depth as usize,
+        "BUG: inconsistent number of samples"
+    );
+
+    writeln!(writer, "P7")?;
+    writeln!(writer, "WIDTH {}", pam.width)?;
+    writeln!(writer, "HEIGHT {}", pam.height)?;
+    writeln!(writer, "DEPTH {}", pam.depth)?;
+    writeln!(writer, "MAXVAL {}", pam.max_val)?;
+    if let Some(tuple_type) = &pam.tuple_type {
+        writeln!(writer, "TUPLTYPE {}", tuple_type.name())?;
+    }
+    writeln!(writer, "ENDHDR")?;
+
+    let bytes: Vec<u8> = if pam.max_val <= 255 {
+        pam.samples.iter().map(|&s| s as u8).collect()
+    } else {
+        pam.samples.iter().flat_map(|s| s.to_be_bytes()).collect()
+    };
+    writer.write_all(&bytes)
+}
+
+/// Saves a PAM image (P7) to a file.
+pub fn save_pam(pam: &PamImage, path: &std::path::Path) -> Result<()> {
+    use std::io::Write;
+    let mut writer =
+        std::io::BufWriter::new(std::fs::File::create(path).map_err(|err| Error::io(path, err))?);
+    write_pam(pam, &mut writer)
+        .and_then(|()| writer.flush())
+        .map_err(|err| Error::io(path, err))
+}
+
+/// Saves the z-slice of a volume as a binary PGM (P5), converting each voxel to gray with
+/// `to_gray`. The rows of the image are y, and its columns x.
+pub fn save_z_slice_as_pgm<T: Clone>(
+    volume: &Volume<T>,
+    z: isize,
+    to_gray: impl FnMut(&T) -> u8,
+    path: &std::path::Path,
+) -> Result<()> {
+    let image = AnyImage::Gray8 {
+        image: volume.slice(Axis::Z, z).map(to_gray),
+        max_val: 255,
+    };
+    save_any(&image, Encoding::Binary, path)
+}
 
 /// Saves an RGB image as a Portable PixMap (PPM), in binary format (format P6).
 pub fn save_image_as_ppm(img: &Image<Rgb>, path: &std::path::Path) -> std::io::Result<()> {
     use std::io::Write;
     let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    write_image(
+        &mut writer,
+        Format::new(Kind::Pixmap, Encoding::Binary),
+        img.width,
+        img.height,
+        255,
+        img.data
+            .iter()
+            .flat_map(|px| [px.r(), px.g(), px.b()].map(u16::from)),
+    )?;
+    writer.flush()
+}
 
-    // Magic number for a binary PPM.
-    writer.write_all(b"P6\n")?;
-    // Width, then height, as ASCII.
-    writeln!(&mut writer, "{} {}", img.width, img.height)?;
-    // The maximum value for each color.
-    writeln!(&mut writer, "255")?;
-    // Now the binary pixel data.
-    for px in &img.data {
-        writer.write_all(&[px.r(), px.g(), px.b()])?;
+/// Reads a grayscale ([0, 255]) image in Portable GrayMap format, ASCII or binary (PGM P2 or
+/// P5).
+///
+/// Panics if the file is malformed, see `try_read_pgm_image` for a non-panicking version.
+pub fn read_pgm_image(path: &std::path::Path) -> std::io::Result<Image<u8>> {
+    let file = std::fs::File::open(path)?;
+    match read_pgm_from(std::io::BufReader::new(file)) {
+        Ok(img) => Ok(img),
+        Err(Error::Io { source, .. }) => Err(source),
+        Err(err) => panic!("Invalid PGM image: {}", err),
     }
+}
 
-    writer.flush()
+/// Same as `read_pgm_image`, but returns an error if the file is malformed.
+pub fn try_read_pgm_image(path: &std::path::Path) -> Result<Image<u8>> {
+    read_file(path, read_pgm_from)
 }
 
-/// Reads a grayscale ([0, 255]) image in Portable GrayMap ASCII format (PGM P2).
-pub fn read_pgm_image(path: &std::path::Path) -> std::io::Result<Image<u8>> {
-    let contents = std::fs::read_to_string(path)?;
-    Ok(parse_pgm_image(contents.as_str()))
+/// Reads a grayscale ([0, 255]) PGM image (P2 or P5) from a reader, see `read_any_from`.
+pub fn read_pgm_from<R: std::io::BufRead>(reader: R) -> Result<Image<u8>> {
+    let (header, samples) = read_8bit_image_from(reader, Kind::Graymap)?;
+    Ok(header.image(samples.into_iter().map(|s| s as u8).collect()))
+}
+
+/// Reads an RGB ([0, 255]) PPM image (P3 or P6) from a reader, see `read_any_from`.
+pub fn read_ppm_from<R: std::io::BufRead>(reader: R) -> Result<Image<Rgb>> {
+    let (header, samples) = read_8bit_image_from(reader, Kind::Pixmap)?;
+    Ok(header.image(
+        samples
+            .chunks_exact(3)
+            .map(|s| Rgb::new(s[0] as u8, s[1] as u8, s[2] as u8))
+            .collect(),
+    ))
+}
+
+/// Reads the header and samples of an image of the given kind, with samples in [0, 255].
+fn read_8bit_image_from<R: std::io::BufRead>(reader: R, kind: Kind) -> Result<(Header, Vec<u16>)> {
+    let mut parser = Parser::new(reader);
+    let header = parser.parse_header()?;
+    if header.format.map(|f| f.kind) != Some(kind) {
+        let expected = match kind {
+            Kind::Bitmap => "P1 or P4",
+            Kind::Graymap => "P2 or P5",
+            Kind::Pixmap => "P3 or P6",
+        };
+        return Err(Error::parse(
+            1,
+            1,
+            format!("Expected magic number {}", expected),
+        ));
+    }
+    if header.max_val > 255 {
+        return Err(parser.error_at(
+            header.max_val_pos,
+            format!(
+                "Unsupported max pixel value {}, must be <= 255",
+                header.max_val
+            ),
+        ));
+    }
+
+    let samples = parser.parse_samples(&header)?;
+    Ok((header, samples))
 }
 
-pub fn parse_pgm_image(mut contents: &str) -> Image<u8> {
-    contents = contents
-        .strip_prefix("P2")
-        .expect("Missing P2 magic header prefix.");
+/// Opens a file and reads it with `read`, adding the file's path to IO errors.
+fn read_file<T>(
+    path: &std::path::Path,
+    read: impl FnOnce(std::io::BufReader<std::fs::File>) -> Result<T>,
+) -> Result<T> {
+    let file = std::fs::File::open(path).map_err(|err| Error::io(path, err))?;
+    read(std::io::BufReader::new(file)).map_err(|err| match err {
+        Error::Io { path: None, source } => Error::io(path, source),
+        err => err,
+    })
+}
 
-    let mut height = None;
-    let mut width = None;
-    let mut data = Vec::new();
+/// Parses a PGM P2 image, see `read_pgm_image`.
+///
+/// Panics if the image is malformed, see `try_parse_pgm_image` for a non-panicking version.
+pub fn parse_pgm_image(contents: &str) -> Image<u8> {
+    try_parse_pgm_image(contents).unwrap_or_else(|err| panic!("Invalid PGM image: {}", err))
+}
 
-    #[derive(Eq, PartialEq)]
-    enum Mode {
-        Width,
-        Height,
-        MaxPixelValue,
-        Pixels,
+/// Same as `parse_pgm_image`, but returns an error if the image is malformed.
+pub fn try_parse_pgm_image(text: &str) -> Result<Image<u8>> {
+    if !text.starts_with("P2") {
+        return Err(Error::parse(1, 1, "Missing P2 magic header prefix."));
     }
-    let mut mode = Mode::Width;
+    read_pgm_from(text.as_bytes())
+}
 
-    loop {
-        // Remove whitespace.
-        contents = contents.trim_start();
+/// Writes the header and the samples of an image. Each pixel has `format.kind.channels()`
+/// samples, in [0, max_val].
+fn write_image(
+    writer: &mut dyn std::io::Write,
+    format: Format,
+    width: usize,
+    height: usize,
+    max_val: u16,
+    samples: impl Iterator<Item = u16>,
+) -> std::io::Result<()> {
+    writeln!(writer, "{}", format.magic())?;
+    // Width, then height, as ASCII.
+    writeln!(writer, "{} {}", width, height)?;
+    if format.kind != Kind::Bitmap {
+        writeln!(writer, "{}", max_val)?;
+    }
+
+    let samples_per_row = width * format.kind.channels();
+    if samples_per_row == 0 {
+        return Ok(());
+    }
+    let mut row = Vec::with_capacity(samples_per_row);
+    let mut samples = samples.peekable();
+    while samples.peek().is_some() {
+        row.clear();
+        row.extend(samples.by_ref().take(samples_per_row));
 
-        if contents.is_empty() {
-            break;
+        match (format.encoding, format.kind) {
+            (Encoding::Ascii, Kind::Bitmap) => {
+                // Bitmap samples don't need to be separated, but lines should be at most 70
+                // chars long.
+                for chunk in row.chunks(70) {
+                    let line: String = chunk
+                        .iter()
+                        .map(|&s| if s == 0 { '0' } else { '1' })
+                        .collect();
+                    writeln!(writer, "{}", line)?;
+                }
+            }
+            (Encoding::Ascii, _) => {
+                let mut line = String::new();
+                for s in &row {
+                    let s = s.to_string();
+                    if !line.is_empty() && line.len() + 1 + s.len() > 70 {
+                        writeln!(writer, "{}", line)?;
+                        line.clear();
+                    }
+                    if !line.is_empty() {
+                        line.push(' ');
+                    }
+                    line.push_str(&s);
+                }
+                writeln!(writer, "{}", line)?;
+            }
+            (Encoding::Binary, Kind::Bitmap) => {
+                // 8 pixels per byte, MSB first, with each row padded to a whole byte.
+                let bytes: Vec<u8> = row
+                    .chunks(8)
+                    .map(|chunk| {
+                        chunk
+                            .iter()
+                            .enumerate()
+                            .fold(0u8, |byte, (i, &s)| byte | ((s != 0) as u8) << (7 - i))
+                    })
+                    .collect();
+                writer.write_all(&bytes)?;
+            }
+            (Encoding::Binary, _) if max_val <= 255 => {
+                let bytes: Vec<u8> = row.iter().map(|&s| s as u8).collect();
+                writer.write_all(&bytes)?;
+            }
+            (Encoding::Binary, _) => {
+                // 2 bytes per sample, big endian.
+                let bytes: Vec<u8> = row.iter().flat_map(|s| s.to_be_bytes()).collect();
+                writer.write_all(&bytes)?;
+            }
         }
+    }
+    Ok(())
+}
+
+/// The header of a Netpbm image.
+struct Header {
+    /// None for PAM images.
+    format: Option<Format>,
+    width: usize,
+    height: usize,
+    /// The number of samples per pixel.
+    channels: usize,
+    /// 1 for bitmaps.
+    max_val: u16,
+    /// Where the max value is in the file, for error messages.
+    max_val_pos: Pos,
+    /// Only for PAM images.
+    tuple_type: Option<TupleType>,
+}
+
+impl Header {
+    /// PAM images are always binary.
+    fn encoding(&self) -> Encoding {
+        self.format.map_or(Encoding::Binary, |f| f.encoding)
+    }
 
-        if contents.starts_with('#') {
-            // Found a comment line, such as the one Gimp inserts. Read till end of line and
-            // discard.
-            let next_linefeed = contents.find('\n').unwrap_or(contents.len());
-            contents = &contents[next_linefeed..];
-            continue;
+    /// Whether the samples are single bits, ie for PBM images (but not PAM ones).
+    fn is_bitmap(&self) -> bool {
+        self.format.is_some_and(|f| f.kind == Kind::Bitmap)
+    }
+
+    /// Builds an image with the size from the header.
+    fn image<T>(&self, data: Vec<T>) -> Image<T> {
+        Image {
+            height: self.height,
+            width: self.width,
+            data,
         }
-        match mode {
-            Mode::Width => {
-                let next_ws = contents.find(char::is_whitespace).unwrap();
-                width = Some(contents[..next_ws].parse::<u16>().unwrap());
-                contents = &contents[next_ws..];
-                mode = Mode::Height;
-            }
-            Mode::Height => {
-                let next_ws = contents.find(char::is_whitespace).unwrap();
-                height = Some(contents[..next_ws].parse::<u16>().unwrap());
-                contents = &contents[next_ws..];
-                mode = Mode::MaxPixelValue;
+    }
+}
+
+/// A position in a Netpbm file, for error messages. The header is ASCII, so the column counts
+/// bytes.
+#[derive(Clone, Copy, Debug)]
+struct Pos {
+    line: usize,
+    column: usize,
+}
+
+impl Pos {
+    fn advance(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            if b == b'\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
             }
-            Mode::MaxPixelValue => {
-                let next_ws = contents.find(char::is_whitespace).unwrap();
-                let max_val = contents[..next_ws].parse::<u16>().unwrap();
-                assert!(max_val <= 255);
-                contents = &contents[next_ws..];
-                mode = Mode::Pixels;
+        }
+    }
+}
+
+/// An incremental parser for the contents of a Netpbm file, which may contain binary data.
+struct Parser<R> {
+    reader: R,
+    /// The position of the next byte to parse.
+    pos: Pos,
+}
+
+impl<R: std::io::BufRead> Parser<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            pos: Pos { line: 1, column: 1 },
+        }
+    }
+
+    fn error_at(&self, pos: Pos, msg: impl Into<String>) -> Error {
+        Error::parse(pos.line, pos.column, msg)
+    }
+
+    /// Returns the next byte without consuming it, or None at the end of the file.
+    fn peek(&mut self) -> Result<Option<u8>> {
+        loop {
+            match self.reader.fill_buf() {
+                Ok(buf) => return Ok(buf.first().copied()),
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
             }
-            Mode::Pixels => {
-                let next_ws = contents.find(char::is_whitespace).unwrap_or(contents.len());
-                let px = contents[..next_ws].parse::<u8>().unwrap();
-                data.push(px);
-                contents = &contents[next_ws..];
+        }
+    }
+
+    /// Consumes the byte returned by `peek`.
+    fn consume(&mut self, b: u8) {
+        self.reader.consume(1);
+        self.pos.advance(&[b]);
+    }
+
+    /// Reads up to `n` bytes into `buf`, or less at the end of the file.
+    fn read_up_to(&mut self, n: usize, buf: &mut Vec<u8>) -> Result<()> {
+        buf.clear();
+        use std::io::Read;
+        self.reader.by_ref().take(n as u64).read_to_end(buf)?;
+        self.pos.advance(buf);
+        Ok(())
+    }
+
+    /// Skips whitespace, and comments which last until the end of the line.
+    fn skip_whitespace_and_comments(&mut self) -> Result<()> {
+        while let Some(b) = self.peek()? {
+            if b == b'#' {
+                // Found a comment, such as the one Gimp inserts. Discard it.
+                while let Some(b) = self.peek()?.filter(|&b| b != b'\n') {
+                    self.consume(b);
+                }
+            } else if b.is_ascii_whitespace() {
+                self.consume(b);
+            } else {
+                break;
             }
         }
+        Ok(())
     }
 
-    if mode != Mode::Pixels {
-        panic!("File ended before pixel data.");
+    /// Returns the next whitespace-separated token, and its position. The token is empty at the
+    /// end of the file.
+    fn next_token(&mut self) -> Result<(Vec<u8>, Pos)> {
+        self.skip_whitespace_and_comments()?;
+        let start = self.pos;
+        let mut token = Vec::new();
+        while let Some(b) = self.peek()?.filter(|b| !b.is_ascii_whitespace()) {
+            token.push(b);
+            self.consume(b);
+        }
+        Ok((token, start))
     }
 
-    let height = height.unwrap();
-    let width = width.unwrap();
-    if data.len() != height as usize * width as usize {
-        panic!(
-            "Parsed an inconsistent number of pixels. Expected {}, parsed {}",
-            height as usize * width as usize,
-            data.len()
-        );
+    /// Parses the next whitespace-separated token as an integer in [min, max]. Returns it, and
+    /// its position.
+    fn parse_int(&mut self, what: &str, min: u32, max: u32) -> Result<(u32, Pos)> {
+        let (token, start) = self.next_token()?;
+        if token.is_empty() {
+            return Err(self.error_at(start, format!("Unexpected end of file, expected {}", what)));
+        }
+
+        let token = String::from_utf8_lossy(&token);
+        let value = token.parse::<u32>().map_err(|err| {
+            self.error_at(start, format!("Invalid {} {:?}: {}", what, token, err))
+        })?;
+        if value < min || value > max {
+            return Err(self.error_at(
+                start,
+                format!("Invalid {} {}, must be in [{}, {}]", what, value, min, max),
+            ));
+        }
+        Ok((value, start))
     }
 
-    Image {
-        height,
-        width,
-        data,
+    fn parse_header(&mut self) -> Result<Header> {
+        let start = self.pos;
+        let mut magic = Vec::new();
+        self.read_up_to(2, &mut magic)?;
+        if magic == b"P7" {
+            return self.parse_pam_header();
+        }
+        let format = Format::from_magic(&magic)
+            .ok_or_else(|| self.error_at(start, "Unknown magic number, expected P1 to P7"))?;
+
+        let (width, _) = self.parse_int("width", 0, u32::MAX)?;
+        let (height, _) = self.parse_int("height", 0, u32::MAX)?;
+        let (max_val, max_val_pos) = match format.kind {
+            Kind::Bitmap => (1, self.pos),
+            _ => self.parse_int("max value", 1, u16::MAX as u32)?,
+        };
+
+        if format.encoding == Encoding::Binary {
+            // A single whitespace char separates the header from the pixel data.
+            match self.peek()? {
+                Some(b) if b.is_ascii_whitespace() => self.consume(b),
+                _ => {
+                    return Err(self.error_at(self.pos, "Expected whitespace before the pixel data"))
+                }
+            }
+        }
+
+        Ok(Header {
+            format: Some(format),
+            width: width as usize,
+            height: height as usize,
+            channels: format.kind.channels(),
+            max_val: max_val as u16,
+            max_val_pos,
+            tuple_type: None,
+        })
+    }
+
+    /// Parses the header of a PAM image, right after the magic number. It's made of lines of
+    /// `<field> <value>`, up to an ENDHDR line.
+    fn parse_pam_header(&mut self) -> Result<Header> {
+        let mut width = None;
+        let mut height = None;
+        let mut depth = None;
+        let mut max_val = None;
+        let mut tuple_type: Option<String> = None;
+
+        let end_pos = loop {
+            let (field_name, pos) = self.next_token()?;
+            let (field, min, max) = match field_name.as_slice() {
+                b"ENDHDR" => break pos,
+                b"WIDTH" => (&mut width, 0, u32::MAX),
+                b"HEIGHT" => (&mut height, 0, u32::MAX),
+                b"DEPTH" => (&mut depth, 1, u16::MAX as u32),
+                b"MAXVAL" => (&mut max_val, 1, u16::MAX as u32),
+                b"TUPLTYPE" => {
+                    // The value is the rest of the line. Several TUPLTYPE lines are concatenated.
+                    let mut value = Vec::new();
+                    while let Some(b) = self.peek()?.filter(|&b| b != b'\n') {
+                        value.push(b);
+                        self.consume(b);
+                    }
+                    let tuple_type = tuple_type.get_or_insert_with(String::new);
+                    if !tuple_type.is_empty() {
+                        tuple_type.push(' ');
+                    }
+                    tuple_type.push_str(String::from_utf8_lossy(&value).trim());
+                    continue;
+                }
+                b"" => return Err(self.error_at(pos, "Unexpected end of file, expected ENDHDR")),
+                _ => {
+                    return Err(self.error_at(
+                        pos,
+                        format!(
+                            "Unknown header field {:?}",
+                            String::from_utf8_lossy(&field_name)
+                        ),
+                    ))
+                }
+            };
+            if field.is_some() {
+                return Err(self.error_at(
+                    pos,
+                    format!(
+                        "Duplicate header field {}",
+                        String::from_utf8_lossy(&field_name)
+                    ),
+                ));
+            }
+            let what = String::from_utf8_lossy(&field_name).to_string();
+            *field = Some(self.parse_int(&what, min, max)?);
+        };
+
+        // ENDHDR is followed by a single newline, then the pixel data.
+        match self.peek()? {
+            Some(b'\n') => self.consume(b'\n'),
+            _ => return Err(self.error_at(self.pos, "Expected a newline after ENDHDR")),
+        }
+
+        let missing = |name: &str| self.error_at(end_pos, format!("Missing {} header field", name));
+        let (width, _) = width.ok_or_else(|| missing("WIDTH"))?;
+        let (height, _) = height.ok_or_else(|| missing("HEIGHT"))?;
+        let (depth, depth_pos) = depth.ok_or_else(|| missing("DEPTH"))?;
+        let (max_val, max_val_pos) = max_val.ok_or_else(|| missing("MAXVAL"))?;
+
+        let depth = depth as u16;
+        let tuple_type = tuple_type.map(|name| TupleType::from_name(&name));
+        if let Some(expected_depth) = tuple_type.as_ref().and_then(|t| t.depth()) {
+            if depth != expected_depth {
+                return Err(self.error_at(
+                    depth_pos,
+                    format!(
+                        "Invalid DEPTH {} for TUPLTYPE {}, expected {}",
+                        depth,
+                        tuple_type.as_ref().unwrap().name(),
+                        expected_depth
+                    ),
+                ));
+            }
+        }
+
+        Ok(Header {
+            format: None,
+            width: width as usize,
+            height: height as usize,
+            channels: depth as usize,
+            max_val: max_val as u16,
+            max_val_pos,
+            tuple_type,
+        })
+    }
+
+    /// Parses the pixel data, returning all samples in [0, max_val].
+    fn parse_samples(&mut self, header: &Header) -> Result<Vec<u16>> {
+        let samples_per_row = header.width * header.channels;
+        let num_samples = samples_per_row
+            .checked_mul(header.height)
+            .ok_or_else(|| self.error_at(header.max_val_pos, "Image too large"))?;
+        // Don't trust the header for large allocations, the data might be truncated.
+        let mut samples = Vec::with_capacity(num_samples.min(1 << 20));
+
+        match (header.encoding(), header.is_bitmap()) {
+            (Encoding::Ascii, true) => {
+                // Each sample is a single '0' or '1', whitespace is optional.
+                while samples.len() < num_samples {
+                    self.skip_whitespace_and_comments()?;
+                    let sample = match self.peek()? {
+                        Some(b @ (b'0' | b'1')) => {
+                            self.consume(b);
+                            (b - b'0') as u16
+                        }
+                        Some(_) => return Err(self.error_at(self.pos, "Expected 0 or 1")),
+                        None => break,
+                    };
+                    samples.push(sample);
+                }
+            }
+            (Encoding::Ascii, _) => {
+                while samples.len() < num_samples {
+                    self.skip_whitespace_and_comments()?;
+                    if self.peek()?.is_none() {
+                        break;
+                    }
+                    let (sample, _) = self.parse_int("pixel value", 0, header.max_val as u32)?;
+                    samples.push(sample as u16);
+                }
+            }
+            // Empty images have no pixel data at all.
+            (Encoding::Binary, _) if num_samples == 0 => {}
+            (Encoding::Binary, is_bitmap) => {
+                let bytes_per_row = match is_bitmap {
+                    true => samples_per_row.div_ceil(8),
+                    _ if header.max_val <= 255 => samples_per_row,
+                    _ => samples_per_row * 2,
+                };
+                let mut row = Vec::with_capacity(bytes_per_row);
+                for _ in 0..header.height {
+                    let row_pos = self.pos;
+                    self.read_up_to(bytes_per_row, &mut row)?;
+                    if row.len() < bytes_per_row {
+                        break;
+                    }
+
+                    let row_start = samples.len();
+                    match is_bitmap {
+                        true => samples.extend(
+                            (0..samples_per_row).map(|i| ((row[i / 8] >> (7 - i % 8)) & 1) as u16),
+                        ),
+                        _ if header.max_val <= 255 => samples.extend(row.iter().map(|&b| b as u16)),
+                        _ => samples.extend(
+                            row.chunks_exact(2)
+                                .map(|b| u16::from_be_bytes([b[0], b[1]])),
+                        ),
+                    }
+                    if let Some(i) = samples[row_start..]
+                        .iter()
+                        .position(|&s| s > header.max_val)
+                    {
+                        let sample_size = bytes_per_row / samples_per_row;
+                        let mut pos = row_pos;
+                        pos.advance(&row[..i * sample_size]);
+                        return Err(self.error_at(
+                            pos,
+                            format!(
+                                "Invalid pixel value {}, must be <= {}",
+                                samples[row_start + i],
+                                header.max_val
+                            ),
+                        ));
+                    }
+                }
+                // Anything after the pixel data is left unread, since a file may hold several
+                // images.
+            }
+        }
+
+        if samples.len() != num_samples {
+            return Err(self.error_at(
+                self.pos,
+                format!(
+                    "Parsed an inconsistent number of pixels. Expected {}, parsed {}",
+                    num_samples / header.channels,
+                    samples.len() / header.channels
+                ),
+            ));
+        }
+
+        if header.encoding() == Encoding::Ascii {
+            // Only whitespace and comments may follow the pixel data.
+            self.skip_whitespace_and_comments()?;
+            if self.peek()?.is_some() {
+                return Err(self.error_at(
+                    self.pos,
+                    format!(
+                        "Too many pixels, expected {}",
+                        num_samples / header.channels
+                    ),
+                ));
+            }
+        }
+
+        Ok(samples)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::{AnyImage, Encoding, PamImage, TupleType};
+    use crate::colormap::Rgb;
+    use crate::image::Image;
+
     #[test]
     fn test_parse_pgm_image() {
         // A sample 2x3 image made with Gimp.
@@ -128,7 +1015,7 @@ mod tests {
 200
 120
 ";
-        let img = super::parse_pgm_image(&sample_image);
+        let img = super::parse_pgm_image(sample_image);
         #[rustfmt::skip]
         let expected_img = crate::netpbm::Image {
             height: 2,
@@ -138,4 +1025,270 @@ mod tests {
         };
         assert_eq!(img, expected_img);
     }
+
+    #[test]
+    fn test_parse_invalid_pgm_image() {
+        let error_pos = |contents: &str| match super::try_parse_pgm_image(contents) {
+            Err(crate::error::Error::Parse { line, column, .. }) => (line, column),
+            res => panic!("Unexpected result {:?}", res),
+        };
+        assert_eq!(error_pos("P3\n1 1\n255\n0\n"), (1, 1));
+        assert_eq!(error_pos("P2\n1 1\n255\n0 -1\n"), (4, 3));
+        assert_eq!(error_pos("P2\n1 1\n256\n0\n"), (3, 1));
+        assert_eq!(error_pos("P2\n# No pixels\n1 1"), (3, 4));
+        assert_eq!(error_pos("P2\n2 1\n255\n0\n"), (5, 1));
+    }
+
+    #[test]
+    fn test_parse_any() {
+        // Comments may appear anywhere in the header, and whitespace is optional in P1 data.
+        let img = super::parse_any(b"P1 # A bitmap\n3 # width\n2\n010\n1 1 0\n").unwrap();
+        assert_eq!(
+            img,
+            AnyImage::Bitmap(Image {
+                height: 2,
+                width: 3,
+                data: vec![false, true, false, true, true, false],
+            })
+        );
+
+        // 16-bit samples are stored big endian.
+        let img = super::parse_any(b"P5\n2 1\n# Comment\n1000\n\x03\xe8\x00\x01").unwrap();
+        assert_eq!(
+            img,
+            AnyImage::Gray16 {
+                image: Image {
+                    height: 1,
+                    width: 2,
+                    data: vec![1000, 1],
+                },
+                max_val: 1000,
+            }
+        );
+
+        // Bitmap rows are padded to a whole byte.
+        let img = super::parse_any(b"P4\n9 2\n\xff\x80\x00\x00").unwrap();
+        let mut expected = vec![true; 9];
+        expected.extend([false; 9]);
+        assert_eq!(
+            img,
+            AnyImage::Bitmap(Image {
+                height: 2,
+                width: 9,
+                data: expected,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_any() {
+        let error_pos = |contents: &[u8]| match super::parse_any(contents) {
+            Err(crate::error::Error::Parse { line, column, .. }) => (line, column),
+            res => panic!("Unexpected result {:?}", res),
+        };
+        assert_eq!(error_pos(b"P8\n1 1\n255\n0\n"), (1, 1));
+        assert_eq!(error_pos(b"P1\n2 1\n02\n"), (3, 2));
+        assert_eq!(error_pos(b"P3\n1 1\n65536\n0 0 0\n"), (3, 1));
+        assert_eq!(error_pos(b"P3\n1 1\n100\n0 101 0\n"), (4, 3));
+        assert_eq!(error_pos(b"P2\n1 1\n100\n0 1\n"), (4, 3));
+        assert_eq!(error_pos(b"P5\n2 1\n255 \x00"), (3, 6));
+        assert_eq!(error_pos(b"P5\n1 1\n100 \xff"), (3, 5));
+        assert_eq!(error_pos(b"P5\n2 2 255"), (2, 8));
+    }
+
+    #[test]
+    fn test_write_and_read_any() {
+        let gray: Vec<u16> = (0..12).map(|i| i * 5000).collect();
+        let images = [
+            AnyImage::Bitmap(Image {
+                height: 3,
+                width: 11,
+                data: (0..33).map(|i| i % 3 == 0).collect(),
+            }),
+            AnyImage::Gray8 {
+                image: Image {
+                    height: 2,
+                    width: 40,
+                    data: (0..80).map(|i| i * 3).collect(),
+                },
+                max_val: 240,
+            },
+            AnyImage::Gray16 {
+                image: Image {
+                    height: 3,
+                    width: 4,
+                    data: gray.clone(),
+                },
+                max_val: u16::MAX,
+            },
+            AnyImage::Rgb8 {
+                image: Image {
+                    height: 1,
+                    width: 2,
+                    data: vec![Rgb::new(1, 2, 3), Rgb::new(255, 0, 128)],
+                },
+                max_val: 255,
+            },
+            AnyImage::Rgb16 {
+                image: Image {
+                    height: 2,
+                    width: 2,
+                    data: gray.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect(),
+                },
+                max_val: 60000,
+            },
+        ];
+
+        for img in &images {
+            for encoding in [Encoding::Ascii, Encoding::Binary] {
+                let mut bytes = Vec::new();
+                super::write_any(img, encoding, &mut bytes).unwrap();
+                assert_eq!(&super::parse_any(&bytes).unwrap(), img, "{:?}", encoding);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_pam() {
+        let bytes = b"P7\n# A comment\nWIDTH 2\nHEIGHT 1\nDEPTH 2\nMAXVAL 255\n\
+TUPLTYPE GRAYSCALE_ALPHA\nENDHDR\n\x10\xff\x20\x00";
+        let img = match super::parse_any(bytes).unwrap() {
+            AnyImage::Pam(pam) => pam,
+            img => panic!("Unexpected image {:?}", img),
+        };
+        assert_eq!(img.tuple_type, Some(TupleType::GrayscaleAlpha));
+        assert_eq!(
+            img.to_image::<2>(),
+            Some(Image {
+                height: 1,
+                width: 2,
+                data: vec![[0x10, 0xff], [0x20, 0x00]],
+            })
+        );
+        assert_eq!(img.to_image::<4>(), None);
+
+        // Several TUPLTYPE lines are concatenated, and unknown types are kept as is.
+        let bytes = b"P7\nTUPLTYPE A\nTUPLTYPE  B C \nWIDTH 1 HEIGHT 1 DEPTH 1 MAXVAL 1000\nENDHDR\n\x03\xe8";
+        let img = super::parse_any(bytes).unwrap();
+        assert_eq!(
+            img,
+            AnyImage::Pam(PamImage {
+                height: 1,
+                width: 1,
+                depth: 1,
+                max_val: 1000,
+                tuple_type: Some(TupleType::Other("A B C".to_string())),
+                samples: vec![1000],
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_pam() {
+        let error_pos = |contents: &[u8]| match super::parse_any(contents) {
+            Err(crate::error::Error::Parse { line, column, .. }) => (line, column),
+            res => panic!("Unexpected result {:?}", res),
+        };
+        let header = "P7\nWIDTH 1\nHEIGHT 1\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n";
+        assert!(super::parse_any(format!("{}abcd", header).as_bytes()).is_ok());
+
+        assert_eq!(error_pos(format!("{}abc", header).as_bytes()), (8, 4));
+        assert_eq!(
+            error_pos(header.replace("DEPTH 4", "DEPTH 3").as_bytes()),
+            (4, 7)
+        );
+        assert_eq!(
+            error_pos(header.replace("HEIGHT 1\n", "").as_bytes()),
+            (6, 1)
+        );
+        assert_eq!(
+            error_pos(header.replace("HEIGHT", "WIDTH").as_bytes()),
+            (3, 1)
+        );
+        assert_eq!(
+            error_pos(header.replace("HEIGHT", "SIZE").as_bytes()),
+            (3, 1)
+        );
+        assert_eq!(error_pos(header.replace("ENDHDR\n", "").as_bytes()), (7, 1));
+        assert_eq!(
+            error_pos(header.replace("ENDHDR\n", "ENDHDR").as_bytes()),
+            (7, 7)
+        );
+    }
+
+    #[test]
+    fn test_write_and_read_pam() {
+        let rgba = Image {
+            height: 2,
+            width: 3,
+            data: (0..6u8).map(|i| [i, 2 * i, 3 * i, 255 - i]).collect(),
+        };
+        let images = [
+            PamImage::from_image(&rgba, Some(TupleType::RgbAlpha)),
+            PamImage {
+                height: 1,
+                width: 3,
+                depth: 5,
+                max_val: 60000,
+                tuple_type: None,
+                samples: (0..15).map(|i| i * 4000).collect(),
+            },
+        ];
+        assert_eq!(images[0].to_image::<4>().as_ref(), Some(&rgba));
+
+        for img in images {
+            let img = AnyImage::Pam(img);
+            let mut bytes = Vec::new();
+            super::write_any(&img, Encoding::Ascii, &mut bytes).unwrap();
+            assert!(bytes.starts_with(b"P7\n"));
+            assert_eq!(super::parse_any(&bytes).unwrap(), img);
+        }
+    }
+
+    #[test]
+    fn test_read_from_reader() {
+        let text = "P2\n# Comment\n3 2\n255\n0 30 60\n255 200 120\n";
+        // A tiny buffer, so that the header and pixels are split across many reads.
+        let reader = std::io::BufReader::with_capacity(1, text.as_bytes());
+        assert_eq!(
+            super::read_pgm_from(reader).unwrap(),
+            super::try_parse_pgm_image(text).unwrap()
+        );
+        for invalid in [
+            "P2\n1 1\n255\n0 -1\n",
+            "P2\n1 1\n256\n0\n",
+            "P2\n2 1\n255\n0\n",
+        ] {
+            let reader = std::io::BufReader::with_capacity(1, invalid.as_bytes());
+            assert_eq!(
+                super::read_pgm_from(reader).unwrap_err().to_string(),
+                super::try_parse_pgm_image(invalid).unwrap_err().to_string()
+            );
+        }
+
+        // Binary images are read one after the other from the same stream.
+        let mut reader: &[u8] = b"P5 2 1 255\n\x01\x02P6 1 1 255\n\x03\x04\x05";
+        let gray = super::read_pgm_from(&mut reader).unwrap();
+        assert_eq!(gray.data, vec![1, 2]);
+        let rgb = super::read_ppm_from(&mut reader).unwrap();
+        assert_eq!(rgb.data, vec![Rgb::new(3, 4, 5)]);
+        assert!(reader.is_empty());
+        assert!(super::read_ppm_from(&b"P5 1 1 255\n\x00"[..]).is_err());
+    }
+
+    #[test]
+    fn test_read_io_error() {
+        struct FailingReader;
+        impl std::io::Read for FailingReader {
+            fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("Failed"))
+            }
+        }
+
+        let reader = std::io::Read::chain(&b"P5 1 1 255\n"[..], FailingReader);
+        match super::read_pgm_from(std::io::BufReader::new(reader)) {
+            Err(crate::error::Error::Io { path: None, .. }) => {}
+            res => panic!("Unexpected result {:?}", res),
+        }
+    }
 }
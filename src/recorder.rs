@@ -0,0 +1,269 @@
+//! Recording of grid-based simulations, one snapshot per step, so that they can be saved as
+//! animations.
+
+use crate::animation::{save_animation, Format};
+use crate::colormap::Rgb;
+use crate::error::Result;
+use crate::image::Image;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+/// Receives the state of a simulation after each of its steps.
+pub trait Recorder<T> {
+    fn record(&mut self, img: &Image<T>);
+
+    /// Whether snapshots are wanted at all, so that simulations can skip preparing them.
+    fn is_recording(&self) -> bool {
+        true
+    }
+}
+
+/// Records nothing.
+impl<T> Recorder<T> for () {
+    fn record(&mut self, _img: &Image<T>) {}
+
+    fn is_recording(&self) -> bool {
+        false
+    }
+}
+
+/// Records only if there is a recorder, which is convenient for optional recordings.
+impl<T, R: Recorder<T>> Recorder<T> for Option<R> {
+    fn record(&mut self, img: &Image<T>) {
+        if let Some(recorder) = self {
+            recorder.record(img);
+        }
+    }
+
+    fn is_recording(&self) -> bool {
+        self.as_ref()
+            .is_some_and(|recorder| recorder.is_recording())
+    }
+}
+
+/// Records to both recorders, eg to watch a simulation while saving it.
+impl<T, A: Recorder<T>, B: Recorder<T>> Recorder<T> for (A, B) {
+    fn record(&mut self, img: &Image<T>) {
+        if self.0.is_recording() {
+            self.0.record(img);
+        }
+        if self.1.is_recording() {
+            self.1.record(img);
+        }
+    }
+
+    fn is_recording(&self) -> bool {
+        self.0.is_recording() || self.1.is_recording()
+    }
+}
+
+/// Records snapshots of the simulation as RGB frames, through a pixel-to-color function.
+///
+/// Simulations can run for many more steps than there should be frames in an animation, so only
+/// one step every `stride` is kept. Whenever there are more than `max_frames` frames, every other
+/// frame is dropped and the stride is doubled. The last step is always kept.
+pub struct ImageRecorder<T, F> {
+    to_rgb: F,
+    /// Each pixel becomes a square of `scale x scale` pixels in the frames, 1 by default.
+    pub scale: usize,
+    /// The color around frames which are smaller than the largest one, black by default.
+    pub padding: Rgb,
+    max_frames: usize,
+    stride: usize,
+    num_steps: usize,
+    frames: Vec<Image<Rgb>>,
+    /// The last step's frame, if it isn't in `frames`.
+    last_frame: Option<Image<Rgb>>,
+    _pixel: PhantomData<fn(&T)>,
+}
+
+impl<T, F: FnMut(&T) -> Rgb> ImageRecorder<T, F> {
+    pub fn new(to_rgb: F, max_frames: usize) -> Self {
+        assert!(
+            max_frames >= 2,
+            "Need at least 2 frames, for the first and last steps"
+        );
+        Self {
+            to_rgb,
+            scale: 1,
+            padding: Rgb::new(0, 0, 0),
+            max_frames,
+            stride: 1,
+            num_steps: 0,
+            frames: Vec::new(),
+            last_frame: None,
+            _pixel: PhantomData,
+        }
+    }
+
+    /// The number of recorded steps, including the ones which were throttled.
+    pub fn num_steps(&self) -> usize {
+        self.num_steps
+    }
+
+    /// Returns the frames, all the same size: smaller frames are centered in the largest size.
+    pub fn into_frames(self) -> Vec<Image<Rgb>> {
+        let mut frames = self.frames;
+        frames.extend(self.last_frame);
+
+        let height = frames.iter().map(|f| f.height).max().unwrap_or(0);
+        let width = frames.iter().map(|f| f.width).max().unwrap_or(0);
+        frames
+            .into_iter()
+            .map(|frame| {
+                if frame.size_hw() == (height, width) {
+                    return frame;
+                }
+                let (top, left) = ((height - frame.height) / 2, (width - frame.width) / 2);
+                let mut padded = Image::filled(height, width, self.padding);
+                for (row, col, &px) in frame.enumerate_pixels() {
+                    *padded.pixel_mut(top + row, left + col) = px;
+                }
+                padded
+            })
+            .collect()
+    }
+
+    /// Saves the frames as a looping animation, returning the number of frames.
+    pub fn save(
+        self,
+        path: &std::path::Path,
+        format: Format,
+        frame_delay: Duration,
+    ) -> Result<usize> {
+        save_animation(path, format, self.into_frames(), frame_delay)
+    }
+}
+
+impl<T, F: FnMut(&T) -> Rgb> Recorder<T> for ImageRecorder<T, F> {
+    fn record(&mut self, img: &Image<T>) {
+        let scale = self.scale;
+        let frame = Image {
+            height: img.height * scale,
+            width: img.width * scale,
+            data: img
+                .data
+                .chunks(img.width.max(1))
+                .flat_map(|row| {
+                    let row: Vec<Rgb> = row
+                        .iter()
+                        .flat_map(|px| std::iter::repeat_n((self.to_rgb)(px), scale))
+                        .collect();
+                    std::iter::repeat_n(row, scale).flatten()
+                })
+                .collect(),
+        };
+
+        if self.num_steps.is_multiple_of(self.stride) {
+            self.frames.push(frame);
+            self.last_frame = None;
+            if self.frames.len() > self.max_frames {
+                // Keep the frames of the steps which are multiples of the new stride. If this
+                // step's frame isn't one of them, it is still the last step's.
+                if self.frames.len().is_multiple_of(2) {
+                    self.last_frame = self.frames.pop();
+                }
+                let mut i = 0;
+                self.frames.retain(|_| {
+                    i += 1;
+                    i % 2 == 1
+                });
+                self.stride *= 2;
+            }
+        } else {
+            self.last_frame = Some(frame);
+        }
+        self.num_steps += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ImageRecorder, Recorder};
+    use crate::colormap::Rgb;
+    use crate::image::Image;
+
+    #[test]
+    fn throttling() {
+        let mut recorder =
+            ImageRecorder::new(|&step: &u16| Rgb::new(step as u8, (step >> 8) as u8, 0), 10);
+        for step in 0..1000u16 {
+            recorder.record(&Image::filled(1, 1, step));
+        }
+        assert_eq!(recorder.num_steps(), 1000);
+
+        let steps: Vec<u16> = recorder
+            .into_frames()
+            .iter()
+            .map(|f| f.data[0].r() as u16 | (f.data[0].g() as u16) << 8)
+            .collect();
+        assert_eq!(steps, [0, 128, 256, 384, 512, 640, 768, 896, 999]);
+
+        // The last step isn't duplicated if it was kept anyway.
+        let mut recorder = ImageRecorder::new(|&step: &u8| Rgb::new(step, 0, 0), 3);
+        for step in 0..5 {
+            recorder.record(&Image::filled(1, 1, step));
+        }
+        let steps: Vec<u8> = recorder
+            .into_frames()
+            .iter()
+            .map(|f| f.data[0].r())
+            .collect();
+        assert_eq!(steps, [0, 2, 4]);
+
+        // The last step is kept even if the stride was just doubled.
+        let mut recorder = ImageRecorder::new(|&step: &u8| Rgb::new(step, 0, 0), 3);
+        for step in 0..4 {
+            recorder.record(&Image::filled(1, 1, step));
+        }
+        let steps: Vec<u8> = recorder
+            .into_frames()
+            .iter()
+            .map(|f| f.data[0].r())
+            .collect();
+        assert_eq!(steps, [0, 2, 3]);
+
+        // Recording nothing is allowed.
+        let recorder = ImageRecorder::new(|_: &u8| Rgb::new(0, 0, 0), 2);
+        assert!(recorder.into_frames().is_empty());
+    }
+
+    #[test]
+    fn scaling_and_padding() {
+        let white = Rgb::new(255, 255, 255);
+        let gray = Rgb::new(128, 128, 128);
+        let mut recorder = ImageRecorder::new(|&lit: &bool| if lit { white } else { gray }, 10);
+        recorder.scale = 2;
+        recorder.padding = Rgb::new(1, 2, 3);
+        let mut optional = Some(recorder);
+
+        optional.record(&Image::filled(1, 1, true));
+        optional.record(&Image {
+            height: 2,
+            width: 3,
+            data: vec![true, false, true, false, true, false],
+        });
+        ().record(&Image::filled(1, 1, true));
+        assert!(optional.is_recording() && !Recorder::<bool>::is_recording(&()));
+        let mut pair = (optional, ());
+        pair.record(&Image::filled(1, 1, false));
+        let (optional, ()) = pair;
+
+        let frames = optional.unwrap().into_frames();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[1].size_hw(), (4, 6));
+        assert_eq!(
+            frames[1].data[..6],
+            [white, white, gray, gray, white, white]
+        );
+        assert_eq!(frames[1].data[6..12], frames[1].data[..6]);
+
+        let (p, w) = (Rgb::new(1, 2, 3), white);
+        assert_eq!(frames[0].size_hw(), (4, 6));
+        assert_eq!(frames[0].data[..6], [p; 6]);
+        assert_eq!(frames[0].data[6..12], [p, p, w, w, p, p]);
+        assert_eq!(frames[0].data[12..18], [p, p, w, w, p, p]);
+        assert_eq!(frames[0].data[18..], [p; 6]);
+        assert_eq!(frames[2].data[6..12], [p, p, gray, gray, p, p]);
+    }
+}
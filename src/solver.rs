@@ -0,0 +1,83 @@
+//! A common interface for the daily puzzle solvers, so that they can all be driven by a single
+//! runner binary.
+
+/// The answer to one part of a puzzle, as it would be submitted on the website.
+pub type Answer = String;
+
+/// A solver for a single day's puzzle.
+///
+/// Solving happens in 3 phases: the puzzle input text is first parsed into `Self::Input`, which is
+/// then handed to `part1` and `part2` independently.
+/// Parsing reports malformed inputs as errors, the parts can assume the input is well-formed.
+pub trait Solver {
+    /// The day of December this solver is for, in [1, 25].
+    const DAY: u8;
+    /// The puzzle's title.
+    const TITLE: &'static str;
+
+    /// The parsed representation of the puzzle input.
+    type Input;
+
+    /// Sets a day-specific option, as passed on the command line with `--name=value`.
+    /// Solvers have no options by default.
+    fn configure(&mut self, name: &str, _value: &str) -> Result<(), String> {
+        Err(format!("Day {} has no option named {:?}", Self::DAY, name))
+    }
+
+    fn parse(&self, text: &str) -> crate::error::Result<Self::Input>;
+    fn part1(&self, input: &Self::Input) -> Answer;
+    fn part2(&self, input: &Self::Input) -> Answer;
+}
+
+/// An object-safe version of `Solver`, allowing solvers for different days to be stored together
+/// and picked at runtime.
+///
+/// This is implemented for all `Solver`s; the parsed input is passed around as a `dyn Any`.
+pub trait DynSolver {
+    fn day(&self) -> u8;
+    fn title(&self) -> &'static str;
+    fn configure(&mut self, name: &str, value: &str) -> Result<(), String>;
+    fn parse(&self, text: &str) -> crate::error::Result<Box<dyn std::any::Any>>;
+    fn part1(&self, input: &dyn std::any::Any) -> Answer;
+    fn part2(&self, input: &dyn std::any::Any) -> Answer;
+}
+
+impl<S> DynSolver for S
+where
+    S: Solver,
+    S::Input: 'static,
+{
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn title(&self) -> &'static str {
+        S::TITLE
+    }
+
+    fn configure(&mut self, name: &str, value: &str) -> Result<(), String> {
+        Solver::configure(self, name, value)
+    }
+
+    fn parse(&self, text: &str) -> crate::error::Result<Box<dyn std::any::Any>> {
+        Ok(Box::new(Solver::parse(self, text)?))
+    }
+
+    fn part1(&self, input: &dyn std::any::Any) -> Answer {
+        Solver::part1(self, downcast_input::<S>(input))
+    }
+
+    fn part2(&self, input: &dyn std::any::Any) -> Answer {
+        Solver::part2(self, downcast_input::<S>(input))
+    }
+}
+
+fn downcast_input<S>(input: &dyn std::any::Any) -> &S::Input
+where
+    S: Solver,
+    S::Input: 'static,
+{
+    input
+        .downcast_ref::<S::Input>()
+        .unwrap_or_else(|| panic!("BUG: input was not parsed by the day {} solver", S::DAY))
+}
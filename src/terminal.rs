@@ -0,0 +1,106 @@
+//! Watching simulations in a terminal, rendered with `Image::render`, so that they can be looked
+//! at without an external viewer.
+
+use crate::colormap::Rgb;
+use crate::image::Image;
+use crate::recorder::Recorder;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+/// Parses a `watch=<fps>` day option, as the delay between the frames of a `LiveView`.
+pub fn frame_delay_from_fps(value: &str) -> Result<Duration, String> {
+    value
+        .parse::<f32>()
+        .ok()
+        .filter(|&fps| fps > 0.0)
+        .and_then(|fps| Duration::try_from_secs_f32(1.0 / fps).ok())
+        .ok_or_else(|| format!("Invalid number of steps per second {:?}", value))
+}
+
+/// Draws each recorded step in the terminal, over the previous one, so that simulations can be
+/// watched as they run. Waits for `frame_delay` between frames.
+pub struct LiveView<W, T, F> {
+    writer: W,
+    to_rgb: F,
+    frame_delay: Duration,
+    /// The number of lines of the previous frame, to move the cursor back over it.
+    num_lines: usize,
+    last_frame_time: Option<Instant>,
+    _pixel: PhantomData<fn(&T)>,
+}
+
+impl<W: Write, T, F: FnMut(&T) -> Rgb> LiveView<W, T, F> {
+    pub fn new(writer: W, to_rgb: F, frame_delay: Duration) -> Self {
+        Self {
+            writer,
+            to_rgb,
+            frame_delay,
+            num_lines: 0,
+            last_frame_time: None,
+            _pixel: PhantomData,
+        }
+    }
+
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write, T, F: FnMut(&T) -> Rgb> Recorder<T> for LiveView<W, T, F> {
+    fn record(&mut self, img: &Image<T>) {
+        let text = img.render_mapped(&mut self.to_rgb);
+
+        if let Some(last_frame_time) = self.last_frame_time {
+            std::thread::sleep(self.frame_delay.saturating_sub(last_frame_time.elapsed()));
+        }
+        // Move to the start of the previous frame, and clear everything below.
+        let mut out = String::new();
+        if self.num_lines > 0 {
+            write!(out, "\x1b[{}F", self.num_lines).unwrap();
+        }
+        out.push_str("\x1b[0J");
+        out.push_str(&text);
+
+        self.writer
+            .write_all(out.as_bytes())
+            .and_then(|_| self.writer.flush())
+            .expect("Failed to write to the terminal");
+        self.num_lines = img.height.div_ceil(2);
+        self.last_frame_time = Some(Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LiveView;
+    use crate::colormap::Rgb;
+    use crate::image::Image;
+    use crate::recorder::Recorder;
+    use std::time::Duration;
+
+    #[test]
+    fn frame_delay_from_fps() {
+        assert_eq!(
+            super::frame_delay_from_fps("4"),
+            Ok(Duration::from_millis(250))
+        );
+        for value in ["0", "-1", "1e-39", "NaN", "fast"] {
+            assert!(super::frame_delay_from_fps(value).is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn live_view() {
+        let gray = |&v: &u8| Rgb::new(v, v, v);
+        let mut view = LiveView::new(Vec::new(), gray, Duration::ZERO);
+        view.record(&Image::filled(3, 1, 1));
+        view.record(&Image::filled(1, 1, 2));
+
+        let out = String::from_utf8(view.into_writer()).unwrap();
+        let frame1 = Image::filled(3, 1, 1).render_mapped(gray);
+        let frame2 = Image::filled(1, 1, 2).render_mapped(gray);
+        assert_eq!(out, format!("\x1b[0J{}\x1b[2F\x1b[0J{}", frame1, frame2));
+    }
+}